@@ -0,0 +1,160 @@
+//!
+//! Append-Only Feeds
+//!
+//! Archive files and logs-as-feeds only ever grow; instead of parsing the
+//! whole document again, remember where the last complete item ended and
+//! only parse what was written after it.
+//!
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::path::Path;
+use quick_xml::Reader;
+use crate::{RssParser,RssItem,RawRssItem};
+use crate::items::RawItems;
+
+///
+/// Parser returning only the items appended since its previous call
+///
+/// ```
+/// use future_rss::AppendParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut log = String::from("<rss><channel><item><title>1</title></item>");
+///     let mut parser = AppendParser::default();
+///     assert_eq!(parser.parse_str(&log)?.len(),1);
+///
+///     log.push_str("<item><title>2</title></item><item><title>3</ti");
+///     let items = parser.parse_str(&log)?;
+///     assert_eq!(items.len(),1);
+///     assert_eq!(items[0].title,"2");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct AppendParser{
+    /// Tag configuration used for every call
+    pub parser:RssParser,
+    offset:u64,
+}
+
+impl AppendParser{
+
+    pub fn new(parser:RssParser)->Self{
+        Self{ parser, offset:0 }
+    }
+
+    /// Resume from an offset saved by a previous run
+    pub fn with_offset(mut self,offset:u64)->Self{
+        self.offset = offset;
+        self
+    }
+
+    /// Byte offset right after the last complete item parsed so far
+    pub fn offset(&self)->u64{
+        self.offset
+    }
+
+    ///
+    /// Parse the part of `xml` added since the previous call, `xml` being the
+    /// whole document; a document shorter than the offset starts over
+    ///
+    pub fn parse_str(&mut self,xml:&str)->Result<Vec<RssItem>,std::io::Error>{
+        if self.offset as usize > xml.len() || !xml.is_char_boundary(self.offset as usize) {
+            self.offset = 0;
+        }
+        let tail = &xml[self.offset as usize..];
+        let (items,consumed) = self.parse_tail(tail)?;
+        self.offset += consumed as u64;
+        Ok(items)
+    }
+
+    ///
+    /// Read and parse only the bytes appended to `path` since the previous call,
+    /// a file shorter than the offset (truncated or rotated) starts over
+    ///
+    pub fn parse_file<P:AsRef<Path>>(&mut self,path:P)->Result<Vec<RssItem>,std::io::Error>{
+        let mut file = File::open(path)?;
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut tail = Vec::new();
+        file.read_to_end(&mut tail)?;
+
+        // a writer may be in the middle of a multi-byte character
+        let valid = match std::str::from_utf8(&tail) {
+            Ok(tail) => tail.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,e)),
+        };
+        let tail = std::str::from_utf8(&tail[..valid]).expect("Failed by AppendParser::parse_file");
+        let (items,consumed) = self.parse_tail(tail)?;
+        self.offset += consumed as u64;
+        Ok(items)
+    }
+
+    fn parse_tail(&self,tail:&str)->Result<(Vec<RssItem>,usize),std::io::Error>{
+        let mut items = RawItems::new(&self.parser,Reader::from_str(tail),Some(tail),Vec::new()).fragment();
+        let mut nodes = Vec::new();
+        let mut consumed = 0;
+        while let Some(item) = items.next() {
+            nodes.push(RawRssItem::into_owned(item?)?);
+            consumed = items.item_end();
+        }
+        Ok((nodes,consumed))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::prelude::*;
+    use std::fs::OpenOptions;
+    use crate::AppendParser;
+
+    #[test]
+    fn future_rss_append_file()->Result<(),Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("future_rss_append_{}.xml",std::process::id()));
+        let append = |text:&str| OpenOptions::new().create(true).append(true).open(&path)?.write_all(text.as_bytes());
+
+        let mut parser = AppendParser::default();
+        append("<rss><channel><title>Log</title><item><title>1</title></item>")?;
+        assert_eq!(parser.parse_file(&path)?.len(),1);
+        assert!(parser.parse_file(&path)?.is_empty());
+
+        append("<item><title>2</title></item><item><title>3")?;
+        let items = parser.parse_file(&path)?;
+        assert_eq!(items.len(),1);
+        assert_eq!(items[0].title,"2");
+
+        append("</title></item></channel></rss>")?;
+        let items = parser.parse_file(&path)?;
+        assert_eq!(items.len(),1);
+        assert_eq!(items[0].title,"3");
+
+        std::fs::write(&path,"<rss><item><title>new</title></item></rss>")?;
+        let items = parser.parse_file(&path);
+        std::fs::remove_file(&path)?;
+        assert_eq!(items?[0].title,"new");
+        Ok(())
+    }
+
+    #[test]
+    fn future_rss_append_unterminated()->Result<(),Box<dyn std::error::Error>> {
+        let mut log = String::from("<rss><channel><item><title>1</title>");
+        let mut parser = AppendParser::default();
+        assert!(parser.parse_str(&log)?.is_empty());
+
+        // the next start tag closes item 1, item 2 is picked up once complete
+        log.push_str("<item><title>2</title>");
+        assert_eq!(parser.parse_str(&log)?[0].title,"1");
+        assert_eq!(parser.offset() as usize,log.find("<item><title>2").unwrap());
+        log.push_str("</item>");
+        let items = parser.parse_str(&log)?;
+        assert_eq!(items.len(),1);
+        assert_eq!(items[0].title,"2");
+        Ok(())
+    }
+}
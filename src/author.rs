@@ -0,0 +1,97 @@
+//!
+//! Item Authors
+//!
+//! RSS defines `<author>` as `email (Name)`, while feeds in the wild also
+//! write `Name <email>`, a bare address or a bare name; [`Author`] splits
+//! all of them so readers get a clean byline and mailers an address.
+//!
+
+///
+/// Author split into address and display name, the text it was read
+/// from is kept as is
+///
+/// ```
+/// use future_rss::Author;
+///
+/// fn main(){
+///     let author = Author::parse("lawyer@boyer.net (Lawyer Boyer)");
+///     assert_eq!(author.email.as_deref(),Some("lawyer@boyer.net"));
+///     assert_eq!(author.name.as_deref(),Some("Lawyer Boyer"));
+///     assert_eq!(author.byline(),"Lawyer Boyer");
+///     assert_eq!(author.raw,"lawyer@boyer.net (Lawyer Boyer)");
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct Author{
+    pub raw:String,
+    pub email:Option<String>,
+    pub name:Option<String>,
+}
+
+impl Author{
+
+    pub fn parse(raw:&str)->Self{
+        let text = raw.trim();
+        let (email,name) = if let Some((email,rest)) = text.split_once('(') {
+            // email (Name)
+            (email,rest.rsplit_once(')').map_or(rest,|(name,_)| name))
+        }else if let Some((name,rest)) = text.split_once('<') {
+            // Name <email>
+            (rest.split_once('>').map_or(rest,|(email,_)| email),name)
+        }else if is_email(text) {
+            (text,"")
+        }else {
+            ("",text)
+        };
+        let email = email.trim();
+        let email = email.strip_prefix("mailto:").unwrap_or(email);
+        let name = name.trim().trim_matches('"').trim();
+        Self{
+            raw:raw.to_string(),
+            email:Some(email).filter(|e| is_email(e)).map(String::from),
+            name:Some(name).filter(|n| !n.is_empty()).map(String::from),
+        }
+    }
+
+    /// Name to show: the display name, else the address, else the raw text
+    pub fn byline(&self)->&str{
+        self.name.as_deref()
+            .or(self.email.as_deref())
+            .unwrap_or_else(|| self.raw.trim())
+    }
+}
+
+fn is_email(text:&str)->bool{
+    match text.split_once('@') {
+        Some((user,host)) => !user.is_empty() && host.contains('.') && !text.contains(char::is_whitespace),
+        None => false,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{Author,RssItem};
+
+    #[test]
+    fn future_rss_author_parse(){
+        for (raw,email,name) in [
+            ("lawyer@boyer.net (Lawyer Boyer)",Some("lawyer@boyer.net"),Some("Lawyer Boyer")),
+            ("\"Tom Jerry\" <tom@examples.com>",Some("tom@examples.com"),Some("Tom Jerry")),
+            (" mailto:tom@examples.com ",Some("tom@examples.com"),None),
+            ("MeteorCat",None,Some("MeteorCat")),
+            ("noreply@examples.com (Editors (EN))",Some("noreply@examples.com"),Some("Editors (EN)")),
+            ("nobody (Anonymous)",None,Some("Anonymous")),
+            ("",None,None),
+        ] {
+            let author = Author::parse(raw);
+            assert_eq!((author.email.as_deref(),author.name.as_deref()),(email,name),"{}",raw);
+            assert_eq!(author.raw,raw);
+        }
+        assert_eq!(Author::parse("tom@examples.com").byline(),"tom@examples.com");
+        assert_eq!(Author::parse(" ( ) ").byline(),"( )");
+
+        let item = RssItem{ author:String::from("tom@examples.com (Tom)"), ..RssItem::default() };
+        assert_eq!(item.author_details().byline(),"Tom");
+    }
+}
@@ -0,0 +1,269 @@
+//!
+//! Batch Refresh
+//!
+//! Refreshes many [`FeedWatcher`]s at once on a pool of threads, bounded by a
+//! global concurrency cap and a per-host cap so a single server is never
+//! hammered by one aggregator.
+//!
+
+use std::collections::{HashMap,VecDeque};
+use std::sync::{Condvar,Mutex};
+use std::thread;
+use reqwest::blocking::Client;
+use crate::{FeedWatcher,FeedEvent,PollOutcome,CancelToken};
+
+/// Default number of feeds refreshed at the same time
+pub static BATCH_DEFAULT_CONCURRENCY:usize = 8;
+
+/// Default number of feeds refreshed at the same time against one host
+pub static BATCH_DEFAULT_PER_HOST:usize = 2;
+
+///
+/// Outcome of refreshing one feed
+///
+#[derive(Debug)]
+pub struct RefreshSummary{
+    pub url:String,
+    /// Items never seen before
+    pub added:usize,
+    /// Known items whose content changed
+    pub updated:usize,
+    /// Server answered 304
+    pub not_modified:bool,
+    pub error:Option<std::io::Error>,
+}
+
+///
+/// Batch Refresh Builder
+///
+/// ```no_run
+/// use future_rss::{BatchRefresh,FeedWatcher};
+///
+/// fn main(){
+///     let mut feeds = vec![
+///         FeedWatcher::new("https://www.zhihu.com/rss","utf8"),
+///         FeedWatcher::new("https://sspai.com/feed","utf8"),
+///     ];
+///     let summary = BatchRefresh::new()
+///         .max_concurrency(16)
+///         .per_host(1)
+///         .run(&mut feeds);
+///     for feed in summary {
+///         println!("{} +{} ~{} 304:{} {:?}",feed.url,feed.added,feed.updated,feed.not_modified,feed.error);
+///     }
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct BatchRefresh{
+    max_concurrency:usize,
+    per_host:usize,
+    client:Client,
+    cancel:CancelToken,
+}
+
+struct Queue<'a>{
+    pending:VecDeque<(usize,Option<String>,&'a mut FeedWatcher)>,
+    hosts:HashMap<String,usize>,
+}
+
+impl Default for BatchRefresh{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchRefresh{
+
+    pub fn new()->Self{
+        Self{
+            max_concurrency:BATCH_DEFAULT_CONCURRENCY,
+            per_host:BATCH_DEFAULT_PER_HOST,
+            client:crate::network::default_client(),
+            cancel:CancelToken::new(),
+        }
+    }
+
+    /// Global cap on simultaneous requests, at least 1
+    pub fn max_concurrency(mut self,limit:usize)->Self{
+        self.max_concurrency = limit.max(1);
+        self
+    }
+
+    /// Cap on simultaneous requests against the same host, at least 1
+    pub fn per_host(mut self,limit:usize)->Self{
+        self.per_host = limit.max(1);
+        self
+    }
+
+    /// Share a preconfigured client between all requests
+    pub fn client(mut self,client:Client)->Self{
+        self.client = client;
+        self
+    }
+
+    ///
+    /// Stop starting new refreshes once `token` is cancelled,
+    /// skipped feeds report an `Interrupted` error
+    ///
+    pub fn cancel_token(mut self,token:CancelToken)->Self{
+        self.cancel = token;
+        self
+    }
+
+    ///
+    /// Refresh every watcher, summaries come back in input order
+    ///
+    pub fn run(&self,watchers:&mut [FeedWatcher])->Vec<RefreshSummary>{
+        self.run_all(watchers.iter_mut().collect())
+    }
+
+    ///
+    /// Refresh only the watchers whose [`AdaptiveSchedule`](crate::AdaptiveSchedule) says
+    /// a poll is due, summaries of those come back in input order
+    ///
+    pub fn run_due(&self,watchers:&mut [FeedWatcher])->Vec<RefreshSummary>{
+        self.run_all(watchers.iter_mut().filter(|w| w.is_due()).collect())
+    }
+
+    fn run_all(&self,watchers:Vec<&mut FeedWatcher>)->Vec<RefreshSummary>{
+        let total = watchers.len();
+        let urls = watchers.iter().map(|w| w.url.clone()).collect::<Vec<_>>();
+        let queue = Mutex::new(Queue{
+            pending:watchers.into_iter()
+                .enumerate()
+                .map(|(i,w)| (i,w.host(),w))
+                .collect(),
+            hosts:HashMap::new(),
+        });
+        let ready = Condvar::new();
+        let results = Mutex::new((0..total).map(|_| None).collect::<Vec<_>>());
+
+        thread::scope(|scope| {
+            for _ in 0..self.max_concurrency.min(total) {
+                scope.spawn(|| {
+                    while let Some((index,host,watcher)) = self.next_job(&queue,&ready) {
+                        let summary = refresh(watcher,&self.client);
+                        results.lock().unwrap()[index] = Some(summary);
+
+                        let mut queue = queue.lock().unwrap();
+                        if let Some(host) = host {
+                            if let Some(count) = queue.hosts.get_mut(&host) {
+                                *count -= 1;
+                            }
+                        }
+                        ready.notify_all();
+                    }
+                });
+            }
+        });
+
+        results.into_inner()
+            .unwrap()
+            .into_iter()
+            .zip(urls)
+            .map(|(summary,url)| summary.unwrap_or_else(|| RefreshSummary{
+                url,
+                added:0,
+                updated:0,
+                not_modified:false,
+                error:Some(CancelToken::error()),
+            }))
+            .collect()
+    }
+
+    /// Take the first pending feed whose host still has capacity,
+    /// waiting while every pending host is saturated
+    #[allow(clippy::type_complexity)]
+    fn next_job<'a>(&self,queue:&Mutex<Queue<'a>>,ready:&Condvar)->Option<(usize,Option<String>,&'a mut FeedWatcher)>{
+        let mut queue = queue.lock().unwrap();
+        loop {
+            if queue.pending.is_empty() || self.cancel.is_cancelled() {
+                return None;
+            }
+            let per_host = self.per_host;
+            let hosts = &queue.hosts;
+            let position = queue.pending.iter().position(|(_,host,_)| match host {
+                Some(host) => hosts.get(host).copied().unwrap_or(0) < per_host,
+                None => true,
+            });
+            if let Some(position) = position {
+                let job = queue.pending.remove(position).unwrap();
+                if let Some(host) = &job.1 {
+                    *queue.hosts.entry(host.clone()).or_insert(0) += 1;
+                }
+                return Some(job);
+            }
+            queue = ready.wait(queue).unwrap();
+        }
+    }
+}
+
+fn refresh(watcher:&mut FeedWatcher,client:&Client)->RefreshSummary{
+    let mut summary = RefreshSummary{
+        url:watcher.url.clone(),
+        added:0,
+        updated:0,
+        not_modified:false,
+        error:None,
+    };
+    match watcher.poll_with(client) {
+        Ok(PollOutcome::NotModified) => summary.not_modified = true,
+        Ok(PollOutcome::Fetched(events)) => {
+            for event in events.iter() {
+                match event {
+                    FeedEvent::New(_) => summary.added += 1,
+                    FeedEvent::Updated(_) => summary.updated += 1,
+                }
+            }
+        }
+        Err(e) => summary.error = Some(e),
+    }
+    summary
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+    use crate::{BatchRefresh,FeedWatcher,CancelToken};
+    use crate::mock::{serve,FEED};
+
+    #[test]
+    fn future_rss_batch_per_host(){
+        let server = serve(FEED);
+        server.set_delay(Duration::from_millis(50));
+        let mut feeds = (0..4)
+            .map(|i| FeedWatcher::new(&server.url(&format!("/feed/{}",i)),"utf8"))
+            .collect::<Vec<_>>();
+        feeds.push(FeedWatcher::new("not a url","utf8"));
+
+        let batch = BatchRefresh::new().max_concurrency(4).per_host(1);
+        let summary = batch.run(&mut feeds);
+        assert_eq!(summary.len(),5);
+        assert!(summary[..4].iter().all(|s| s.added == 1 && s.error.is_none()));
+        assert!(summary[4].error.is_some());
+        assert_eq!(server.peak(),1);
+
+        let summary = batch.run(&mut feeds);
+        assert!(summary[..4].iter().all(|s| s.not_modified));
+        assert!(batch.run_due(&mut feeds).is_empty());
+    }
+
+    #[test]
+    fn future_rss_batch_cancel(){
+        let server = serve(FEED);
+        server.set_delay(Duration::from_millis(50));
+        let mut feeds = (0..3)
+            .map(|i| FeedWatcher::new(&server.url(&format!("/feed/{}",i)),"utf8"))
+            .collect::<Vec<_>>();
+        let token = CancelToken::new();
+        token.cancel();
+
+        let summary = BatchRefresh::new().cancel_token(token).run(&mut feeds);
+        assert_eq!(summary.len(),3);
+        assert!(summary.iter().all(|s| {
+            s.error.as_ref().map(|e| e.kind()) == Some(ErrorKind::Interrupted)
+        }));
+    }
+}
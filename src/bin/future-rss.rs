@@ -0,0 +1,297 @@
+//!
+//! Command Line
+//!
+//! `future-rss fetch <url>` prints the items of a feed as json, ndjson or a
+//! table, with the same tag overrides as [`RssParser`]; `future-rss watch <url>`
+//! keeps polling and prints only the items that appear afterwards.
+//!
+
+use std::io::Write;
+use std::time::Duration;
+use future_rss::{RssItem,RssParser,FeedWatcher,FeedEvent,PollOutcome,NetworkSettings,Query};
+
+static USAGE:&str = "\
+Usage: future-rss fetch <url> [options]
+       future-rss watch <url> [--interval 5m] [options]
+
+Options:
+    --format <json|ndjson|table>    Output format (default: json, ndjson for watch)
+    --interval <n[s|m|h]>           Time between two polls of watch (default: 5m)
+    --charset <label>               Charset used when the server sends none (default: utf8)
+    --query <query>                 Print only matching items, e.g. 'title ~ rust AND published > 2024-01-01'
+    --node-tag <tag>                Tag of one item (default: item)
+    --title-tag <tag>
+    --link-tag <tag>
+    --author-tag <tag>              e.g. dc:creator
+    --description-tag <tag>
+    --guid-tag <tag>
+    --publish-tag <tag>
+    --updated-tag <tag>             e.g. updated for Atom
+";
+
+/// Default time between two polls of `watch`
+static WATCH_DEFAULT_INTERVAL:Duration = Duration::from_secs(5 * 60);
+
+/// Widest column of the table output, longer values are cut
+static TABLE_MAX_WIDTH:usize = 60;
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+enum Format{
+    Json,
+    Ndjson,
+    Table,
+}
+
+#[derive(Debug)]
+struct Options{
+    url:String,
+    charset:String,
+    format:Format,
+    interval:Duration,
+    query:Option<Query>,
+    parser:RssParser,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+enum Command{
+    Fetch,
+    Watch,
+    Help,
+}
+
+impl Default for Options{
+    fn default() -> Self {
+        Self{
+            url:String::new(),
+            charset:String::from("utf8"),
+            format:Format::Json,
+            interval:WATCH_DEFAULT_INTERVAL,
+            query:None,
+            parser:RssParser::new(),
+        }
+    }
+}
+
+fn parse_args<I:Iterator<Item=String>>(mut args:I)->Result<(Command,Options),String>{
+    let command = match args.next().as_deref() {
+        Some("fetch") => Command::Fetch,
+        Some("watch") => Command::Watch,
+        Some("-h") | Some("--help") | Some("help") | None => return Ok((Command::Help,Options::default())),
+        Some(other) => return Err(format!("Unknown command: {}",other)),
+    };
+
+    let mut url = None;
+    let mut options = Options::default();
+    if command == Command::Watch {
+        options.format = Format::Ndjson;
+    }
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if url.replace(arg).is_some() {
+                return Err(String::from("Only one url is accepted"));
+            }
+            continue;
+        }
+        let value = args.next().ok_or_else(|| format!("Missing value for {}",arg))?;
+        let parser = &mut options.parser;
+        match arg.as_str() {
+            "--format" => options.format = match value.as_str() {
+                "json" => Format::Json,
+                "ndjson" => Format::Ndjson,
+                "table" => Format::Table,
+                other => return Err(format!("Unknown format: {}",other)),
+            },
+            "--interval" => options.interval = parse_interval(&value)
+                .ok_or_else(|| format!("Invalid interval: {}",value))?,
+            "--charset" => options.charset = value,
+            "--query" => options.query = Some(Query::parse(&value).map_err(|e| e.to_string())?),
+            "--node-tag" => parser.node_tag = value,
+            "--title-tag" => parser.title_tag = value,
+            "--link-tag" => parser.link_tag = value,
+            "--author-tag" => parser.author_tag = value,
+            "--description-tag" => parser.description_tag = value,
+            "--guid-tag" => parser.guid_tag = value,
+            "--publish-tag" => parser.publish_tag = value,
+            "--updated-tag" => parser.updated_tag = value,
+            other => return Err(format!("Unknown option: {}",other)),
+        }
+    }
+    options.url = url.ok_or_else(|| String::from("Missing <url>"))?;
+    Ok((command,options))
+}
+
+/// `30s`, `5m`, `1h` or plain seconds, never zero
+fn parse_interval(value:&str)->Option<Duration>{
+    let (number,unit) = match value.char_indices().find(|(_,c)| !c.is_ascii_digit()) {
+        Some((i,_)) => value.split_at(i),
+        None => (value,"s"),
+    };
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .and_then(|n| n.checked_mul(seconds))
+        .map(Duration::from_secs)
+}
+
+fn print_items<W:Write>(out:&mut W,items:&[RssItem],format:Format)->std::io::Result<()>{
+    match format {
+        Format::Json => {
+            let items = items.iter().map(RssItem::to_json).collect::<Vec<_>>();
+            writeln!(out,"[{}]",items.join(","))
+        }
+        Format::Ndjson => {
+            for item in items {
+                writeln!(out,"{}",item.to_json())?;
+            }
+            Ok(())
+        }
+        Format::Table => {
+            let rows = items.iter()
+                .map(|i| [i.title.as_str(),i.author.as_str(),i.publish.as_str(),i.link.as_str()])
+                .collect::<Vec<_>>();
+            let header = ["TITLE","AUTHOR","PUBLISH","LINK"];
+            let mut widths = header.map(|h| h.chars().count());
+            for row in rows.iter() {
+                for (width,cell) in widths.iter_mut().zip(row.iter()) {
+                    *width = (*width).max(cell.chars().count()).min(TABLE_MAX_WIDTH);
+                }
+            }
+            for row in std::iter::once(&header).chain(rows.iter()) {
+                let cells = row.iter()
+                    .zip(widths.iter())
+                    .map(|(cell,width)| {
+                        let cell = cell.chars().take(*width).collect::<String>();
+                        format!("{:<width$}",cell,width = *width)
+                    })
+                    .collect::<Vec<_>>();
+                writeln!(out,"{}",cells.join("  ").trim_end())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn fetch(options:Options)->std::io::Result<()>{
+    let Options{ url, charset, format, query, mut parser, .. } = options;
+    let mut items = parser.parse_url(&url,&charset)?;
+    if let Some(query) = query {
+        items.retain(|item| query.matches(item));
+    }
+    print_items(&mut std::io::stdout().lock(),&items,format)
+}
+
+///
+/// Poll forever, the first poll only records what is already there
+///
+fn watch(options:Options)->std::io::Result<()>{
+    let mut watcher = FeedWatcher::new(&options.url,&options.charset);
+    watcher.parser = options.parser;
+    let query = options.query;
+    let client = NetworkSettings::from_env().client()?;
+    let mut seeded = false;
+    loop {
+        match watcher.poll_with(&client) {
+            Ok(PollOutcome::Fetched(events)) => {
+                let added = events.into_iter()
+                    .filter_map(|event| match event {
+                        FeedEvent::New(item) => Some(item),
+                        FeedEvent::Updated(_) => None,
+                    })
+                    .filter(|item| query.as_ref().map_or(true,|query| query.matches(item)))
+                    .collect::<Vec<_>>();
+                if seeded && !added.is_empty() {
+                    let mut out = std::io::stdout().lock();
+                    print_items(&mut out,&added,options.format)?;
+                    out.flush()?;
+                }
+                seeded = true;
+            }
+            Ok(PollOutcome::NotModified) => {}
+            Err(e) => eprintln!("future-rss: {}",e),
+        }
+        std::thread::sleep(options.interval);
+    }
+}
+
+fn main(){
+    let (command,options) = match parse_args(std::env::args().skip(1)) {
+        Ok((Command::Help,_)) => {
+            print!("{}",USAGE);
+            return;
+        }
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprint!("{}\n\n{}",e,USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let result = match command {
+        Command::Watch => watch(options),
+        _ => fetch(options),
+    };
+    if let Err(e) = result {
+        eprintln!("future-rss: {}",e);
+        std::process::exit(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use future_rss::RssItem;
+    use std::time::Duration;
+    use super::{parse_args,parse_interval,print_items,Command,Format};
+
+    fn args(line:&str)->impl Iterator<Item=String> + '_{
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn future_rss_cli_args(){
+        let (command,options) = parse_args(args("fetch https://example.com/rss --format ndjson --author-tag dc:creator")).unwrap();
+        assert_eq!(command,Command::Fetch);
+        assert_eq!(options.url,"https://example.com/rss");
+        assert_eq!(options.format,Format::Ndjson);
+        assert_eq!(options.parser.author_tag,"dc:creator");
+
+        assert!(parse_args(args("fetch")).is_err());
+        assert!(parse_args(args("fetch url --format xml")).is_err());
+        assert_eq!(parse_args(args("")).unwrap().0,Command::Help);
+
+        let (command,options) = parse_args(args("watch https://example.com/rss --interval 30s")).unwrap();
+        assert_eq!(command,Command::Watch);
+        assert_eq!(options.format,Format::Ndjson);
+        assert_eq!(options.interval,Duration::from_secs(30));
+        assert_eq!(parse_interval("5m"),Some(Duration::from_secs(300)));
+        assert_eq!(parse_interval("2"),Some(Duration::from_secs(2)));
+        assert_eq!(parse_interval("0m"),None);
+        assert_eq!(parse_interval("5d"),None);
+        assert_eq!(parse_interval("18446744073709551615h"),None);
+        assert_eq!(parse_interval("18446744073709551615s"),Some(Duration::from_secs(u64::MAX)));
+
+        let line = vec!["fetch","url","--query","title ~ \"hello world\""];
+        let (_,options) = parse_args(line.into_iter().map(String::from)).unwrap();
+        let item = RssItem{ title:String::from("Hello World!"), ..RssItem::default() };
+        assert!(options.query.unwrap().matches(&item));
+        assert!(parse_args(args("fetch url --query title")).is_err());
+    }
+
+    #[test]
+    fn future_rss_cli_output(){
+        let item = RssItem{ title:String::from("Hey!"), ..RssItem::default() };
+        let mut out = Vec::new();
+        print_items(&mut out,&[item.clone(),item],Format::Ndjson).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(),2);
+
+        let mut out = Vec::new();
+        print_items(&mut out,&[],Format::Table).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),"TITLE  AUTHOR  PUBLISH  LINK\n");
+    }
+}
@@ -0,0 +1,142 @@
+//!
+//! Cache Backends
+//!
+//! The validators a [`FeedWatcher`](crate::FeedWatcher) sends along and the
+//! items a [`FeedDiff`](crate::FeedDiff) has seen live in process memory by
+//! default; a [`CacheBackend`] moves them to a store several instances of an
+//! aggregator share, so a feed is fetched and deduplicated once between them.
+//! A [`SeenStore`] lets exactly one of them claim every new item.
+//!
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Prefix of every key written by this crate
+pub static CACHE_KEY_PREFIX:&str = "future_rss:";
+
+///
+/// Key-value store shared by watchers and diff engines
+///
+/// Values are short strings: validators and content hashes.
+///
+pub trait CacheBackend:std::fmt::Debug+Send+Sync{
+
+    fn get(&self,key:&str)->Result<Option<String>,std::io::Error>;
+
+    fn set(&self,key:&str,value:&str)->Result<(),std::io::Error>;
+
+    fn remove(&self,key:&str)->Result<(),std::io::Error>;
+}
+
+///
+/// Set of item keys that workers claim atomically
+///
+/// Unlike a [`FeedDiff`](crate::FeedDiff) on a [`CacheBackend`], which
+/// reads before it writes, two workers inserting the same key at once get
+/// one `true` between them.
+///
+pub trait SeenStore:std::fmt::Debug+Send+Sync{
+
+    /// Add `key`, true when no one had added it yet
+    fn insert(&self,key:&str)->Result<bool,std::io::Error>;
+
+    fn contains(&self,key:&str)->Result<bool,std::io::Error>;
+}
+
+///
+/// In-process backend, shared between the watchers of one process
+///
+/// ```
+/// use std::sync::Arc;
+/// use future_rss::{CacheBackend,FeedDiff,MemoryCache,RssItem};
+///
+/// fn main(){
+///     let cache:Arc<dyn CacheBackend> = Arc::new(MemoryCache::new());
+///     let item = RssItem{ guid:String::from("unique key"), ..RssItem::default() };
+///
+///     let mut first = FeedDiff::with_backend(cache.clone(),"feed:");
+///     assert_eq!(first.diff(vec![item.clone()]).len(),1);
+///     // a second instance knows the item already
+///     let mut second = FeedDiff::with_backend(cache,"feed:");
+///     assert!(second.diff(vec![item]).is_empty());
+/// }
+/// ```
+#[derive(Debug,Default)]
+pub struct MemoryCache{
+    entries:Mutex<HashMap<String,String>>,
+}
+
+impl MemoryCache{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    pub fn len(&self)->usize{
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.entries().is_empty()
+    }
+
+    fn entries(&self)->std::sync::MutexGuard<'_,HashMap<String,String>>{
+        // a panic elsewhere leaves the map consistent
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CacheBackend for MemoryCache{
+
+    fn get(&self,key:&str)->Result<Option<String>,std::io::Error>{
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn set(&self,key:&str,value:&str)->Result<(),std::io::Error>{
+        self.entries().insert(key.to_string(),value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self,key:&str)->Result<(),std::io::Error>{
+        self.entries().remove(key);
+        Ok(())
+    }
+}
+
+impl SeenStore for MemoryCache{
+
+    fn insert(&self,key:&str)->Result<bool,std::io::Error>{
+        let mut entries = self.entries();
+        if entries.contains_key(key) {
+            return Ok(false);
+        }
+        entries.insert(key.to_string(),String::new());
+        Ok(true)
+    }
+
+    fn contains(&self,key:&str)->Result<bool,std::io::Error>{
+        Ok(self.entries().contains_key(key))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{CacheBackend,SeenStore,MemoryCache};
+
+    #[test]
+    fn future_rss_memory_cache(){
+        let cache = MemoryCache::new();
+        assert_eq!(cache.get("a").unwrap(),None);
+        cache.set("a","1").unwrap();
+        cache.set("a","2").unwrap();
+        assert_eq!(cache.get("a").unwrap().as_deref(),Some("2"));
+        assert_eq!(cache.len(),1);
+        cache.remove("a").unwrap();
+        assert!(cache.is_empty());
+
+        assert!(cache.insert("b").unwrap());
+        assert!(!cache.insert("b").unwrap());
+        assert!(SeenStore::contains(&cache,"b").unwrap());
+    }
+}
@@ -0,0 +1,132 @@
+//!
+//! Cancellation
+//!
+//! A [`CancelToken`] is cloned into long refreshes; once cancelled, feeds not
+//! yet fetched are skipped. The blocking calls return as soon as the requests
+//! in flight are done, the async ones drop theirs right away.
+//!
+
+use std::collections::HashMap;
+use std::sync::{Arc,Mutex};
+use std::sync::atomic::{AtomicBool,Ordering};
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+use std::sync::atomic::AtomicUsize;
+use std::task::Waker;
+
+///
+/// Shared cancellation flag, every clone observes the same state
+///
+/// ```
+/// use future_rss::CancelToken;
+///
+/// fn main(){
+///     let token = CancelToken::new();
+///     let handle = token.clone();
+///     handle.cancel();
+///     assert!(token.is_cancelled());
+/// }
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct CancelToken{
+    shared:Arc<Shared>,
+}
+
+#[derive(Debug,Default)]
+struct Shared{
+    cancelled:AtomicBool,
+    /// Tasks waiting on the token, woken by [`CancelToken::cancel`]
+    wakers:Mutex<HashMap<usize,Waker>>,
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    next_waiter:AtomicUsize,
+}
+
+impl CancelToken{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    /// Ask every holder of the token to stop
+    pub fn cancel(&self){
+        self.shared.cancelled.store(true,Ordering::SeqCst);
+        let wakers = std::mem::take(&mut *self.shared.wakers.lock().unwrap());
+        for (_,waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self)->bool{
+        self.shared.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Error reported for work skipped because of the token
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub(crate) fn error()->std::io::Error{
+        std::io::Error::new(std::io::ErrorKind::Interrupted,"Cancelled by CancelToken")
+    }
+
+    ///
+    /// Drive `future` until it is done or the token is cancelled, whichever
+    /// comes first; a cancelled future is dropped and not polled again
+    ///
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub(crate) async fn run<F:std::future::Future>(&self,future:F)->Result<F::Output,std::io::Error>{
+        use std::task::Poll;
+        let waiter = self.shared.next_waiter.fetch_add(1,Ordering::SeqCst);
+        let _waiting = Waiting{ shared:&self.shared, waiter };
+        let mut future = Box::pin(future);
+        std::future::poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(Err(Self::error()));
+            }
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+            self.shared.wakers.lock().unwrap().insert(waiter,cx.waker().clone());
+            // cancelled between the check and the registration: nobody wakes us
+            match self.is_cancelled() {
+                true => Poll::Ready(Err(Self::error())),
+                false => Poll::Pending,
+            }
+        }).await
+    }
+}
+
+/// Forgets the waker of a finished [`CancelToken::run`]
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+struct Waiting<'a>{
+    shared:&'a Shared,
+    waiter:usize,
+}
+
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+impl Drop for Waiting<'_>{
+    fn drop(&mut self) {
+        self.shared.wakers.lock().unwrap().remove(&self.waiter);
+    }
+}
+
+
+#[cfg(all(test,feature = "http-async",not(target_arch = "wasm32")))]
+mod tests {
+    use std::time::Duration;
+    use crate::CancelToken;
+    use crate::runtime::tests::block_on;
+
+    #[test]
+    fn future_rss_cancel_run(){
+        let token = CancelToken::new();
+        assert_eq!(block_on(token.run(async { 1 })).unwrap(),1);
+        assert!(token.shared.wakers.lock().unwrap().is_empty());
+
+        // a pending future gives way once another thread cancels
+        let handle = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            handle.cancel();
+        });
+        let error = block_on(token.run(std::future::pending::<()>())).unwrap_err();
+        assert_eq!(error.kind(),std::io::ErrorKind::Interrupted);
+        assert!(block_on(token.run(async { 1 })).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//!
+//! Item Categories
+//!
+//! `<category domain="...">Tech/Rust/Async</category>` names a taxonomy with
+//! its `domain` and a place in it with slashes; [`Category`] keeps both so
+//! readers can build category trees. Atom's `<category term scheme/>` is
+//! read the same way.
+//!
+
+use crate::RssParser;
+
+/// &lt;category&gt;...&lt;/category&gt;
+pub static RSS_DEFAULT_CATEGORY_TAG:&str = "category";
+
+///
+/// Category of an item
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0">
+///             <item>
+///                 <title>Hey!</title>
+///                 <category domain="https://examples.com/topics">Tech/Rust/Async</category>
+///                 <category>News</category>
+///             </item>
+///         </rss>
+///         "#
+///     ))?;
+///     let categories = parser.parse_categories()?;
+///     assert_eq!(categories[0][0].path,["Tech","Rust","Async"]);
+///     assert_eq!(categories[0][0].domain.as_deref(),Some("https://examples.com/topics"));
+///     assert_eq!(categories[0][1].name,"News");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct Category{
+    /// Full text, `Tech/Rust/Async`
+    pub name:String,
+    /// Taxonomy the name belongs to, Atom's `scheme`
+    pub domain:Option<String>,
+    /// Levels of the name from the root, `["Tech","Rust","Async"]`
+    pub path:Vec<String>,
+}
+
+impl Category{
+
+    pub fn new(name:&str,domain:Option<&str>)->Self{
+        let name = name.trim();
+        Self{
+            name:name.to_string(),
+            domain:domain.map(str::trim).filter(|d| !d.is_empty()).map(String::from),
+            path:name.split('/').map(str::trim).filter(|level| !level.is_empty()).map(String::from).collect(),
+        }
+    }
+
+    /// Category sits at or below `ancestor` in the same taxonomy
+    pub fn is_under(&self,ancestor:&Category)->bool{
+        self.domain == ancestor.domain && self.path.starts_with(&ancestor.path)
+    }
+}
+
+///
+/// Categories of every item of `xml`, read by the item parser itself
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<Vec<Category>>,std::io::Error>{
+    Ok(parser.parse_str(xml)?.into_iter().map(|item| item.categories).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,Category};
+
+    #[test]
+    fn future_rss_categories(){
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0">
+                    <channel>
+                        <category>Channel</category>
+                        <item>
+                            <category domain="dmoz"> Tech / Rust/ </category>
+                            <category><![CDATA[Q&A]]></category>
+                            <category domain="x"></category>
+                        </item>
+                        <item><title>none</title></item>
+                        <item><category term="Tech/Rust/Async" scheme="dmoz"/></item>
+                    </channel>
+                </rss>
+        "#)).unwrap();
+        let items = parser.parse_categories().unwrap();
+        assert_eq!(items.len(),3);
+        assert_eq!(items[0].len(),2);
+        assert_eq!(items[0][0],Category{
+            name:String::from("Tech / Rust/"),
+            domain:Some(String::from("dmoz")),
+            path:vec![String::from("Tech"),String::from("Rust")],
+        });
+        assert_eq!(items[0][1].path,["Q&A"]);
+        assert!(items[1].is_empty());
+        assert!(items[2][0].is_under(&items[0][0]));
+        assert!(!items[0][0].is_under(&items[2][0]));
+        assert!(!items[2][0].is_under(&Category::new("Tech",None)));
+
+        // parsed items carry the same categories
+        let parsed = parser.parse_borrowed().unwrap().into_iter().map(|item| item.categories).collect::<Vec<_>>();
+        assert_eq!(parsed,items);
+    }
+}
@@ -0,0 +1,218 @@
+//!
+//! Channel Metadata
+//!
+//! Feed-level details, read from the RSS `<channel>` or the Atom `<feed>`
+//! root, so output built from the items keeps the context of its source.
+//!
+
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use crate::Timestamp;
+
+///
+/// Title, link, description, language, last build date and image of a feed
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0"><channel>
+///             <title>Examples</title>
+///             <link>https://examples.com</link>
+///             <language>en-us</language>
+///             <lastBuildDate>Thu, 28 May 2020 15:00:00 GMT</lastBuildDate>
+///             <image><url>https://examples.com/logo.png</url><title>Examples</title></image>
+///             <item><title>Hey!</title></item>
+///         </channel></rss>
+///         "#
+///     ))?;
+///     let channel = parser.parse_channel()?;
+///     assert_eq!(channel.title,"Examples");
+///     assert_eq!(channel.language,"en-us");
+///     assert_eq!(channel.image,"https://examples.com/logo.png");
+///     assert_eq!(channel.last_build_date_at().unwrap().to_rfc3339(),"2020-05-28T15:00:00Z");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct Channel{
+    pub title:String,
+    pub link:String,
+    /// RSS `description`, Atom `subtitle`
+    pub description:String,
+    /// RSS `language`, the `xml:lang` of an Atom feed
+    pub language:String,
+    /// RSS `lastBuildDate`, Atom `updated`, as found
+    pub last_build_date:String,
+    /// Url of the RSS `image`, Atom `logo` or `icon`
+    pub image:String,
+}
+
+impl Channel{
+
+    /// Last build date in UTC, `None` when missing or not understood
+    pub fn last_build_date_at(&self)->Option<Timestamp>{
+        Timestamp::parse(&self.last_build_date)
+    }
+
+    pub(crate) fn json(&self)->json::JsonValue{
+        json::object!{
+            "title": self.title.as_str(),
+            "link": self.link.as_str(),
+            "description": self.description.as_str(),
+            "language": self.language.as_str(),
+            "last_build_date": self.last_build_date.as_str(),
+            "image": self.image.as_str(),
+        }
+    }
+}
+
+///
+/// Channel of `xml`, from the children of `<channel>` or `<feed>`; the
+/// first value of each wins
+///
+pub(crate) fn parse_channel(xml:&str)->Result<Channel,std::io::Error>{
+    let error = |e:quick_xml::Error| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by RssParser::parse_channel: {:?}",e));
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    reader.expand_empty_elements(true);
+
+    let mut channel = Channel::default();
+    let mut path:Vec<String> = Vec::new();
+    let mut buff = Vec::new();
+    loop {
+        buff.clear();
+        match reader.read_event(&mut buff).map_err(error)? {
+            Event::Start(ref e) => {
+                let name = String::from_utf8_lossy(e.name()).to_ascii_lowercase();
+                let root = is_root(path.last());
+                if name == "feed" && path.is_empty() {
+                    for attribute in e.attributes().flatten() {
+                        if attribute.key == b"xml:lang" && channel.language.is_empty() {
+                            channel.language = attribute.unescape_and_decode_value(&reader).map_err(error)?;
+                        }
+                    }
+                }
+                // atom links carry the url in href, the alternate one is the site
+                if root && name == "link" && channel.link.is_empty() {
+                    let mut href = None;
+                    let mut alternate = true;
+                    for attribute in e.attributes().flatten() {
+                        match attribute.key {
+                            b"href" => href = Some(attribute.unescape_and_decode_value(&reader).map_err(error)?),
+                            b"rel" => alternate = attribute.value.as_ref() == b"alternate",
+                            _ => (),
+                        }
+                    }
+                    if let (Some(href),true) = (href,alternate) {
+                        channel.link = href;
+                    }
+                }
+                path.push(name);
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(ref e) => {
+                if let Some(field) = field(&mut channel,&path) {
+                    *field = e.unescape_and_decode(&reader).map_err(error)?;
+                }
+            }
+            Event::CData(ref e) => {
+                if let Some(field) = field(&mut channel,&path) {
+                    *field = String::from_utf8_lossy(e.escaped()).into_owned();
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(channel)
+}
+
+/// Still empty field of the channel the innermost element of `path` fills
+fn field<'c>(channel:&'c mut Channel,path:&[String])->Option<&'c mut String>{
+    let depth = path.len();
+    // <image><url>...</url></image> of an RSS channel
+    if depth >= 3 && is_root(path.get(depth - 3)) && path[depth - 2] == "image" && path[depth - 1] == "url" {
+        return Some(&mut channel.image).filter(|field| field.is_empty());
+    }
+    if depth < 2 || !is_root(path.get(depth - 2)) {
+        return None;
+    }
+    let field = match path.last().map(String::as_str) {
+        Some("title") => &mut channel.title,
+        Some("link") => &mut channel.link,
+        Some("description") | Some("subtitle") => &mut channel.description,
+        Some("language") => &mut channel.language,
+        Some("lastbuilddate") | Some("updated") => &mut channel.last_build_date,
+        Some("logo") | Some("icon") => &mut channel.image,
+        _ => return None,
+    };
+    Some(field).filter(|field| field.is_empty())
+}
+
+fn is_root(name:Option<&String>)->bool{
+    matches!(name.map(String::as_str),Some("channel") | Some("feed"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,Channel};
+
+    #[test]
+    fn future_rss_channel_atom(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+                <feed xmlns="http://www.w3.org/2005/Atom" xml:lang="de">
+                    <title><![CDATA[Tom & Jerry]]></title>
+                    <subtitle>Cartoons</subtitle>
+                    <updated>2020-05-28T15:00:00Z</updated>
+                    <logo>https://examples.com/logo.png</logo>
+                    <icon>https://examples.com/favicon.ico</icon>
+                    <link rel="self" href="https://examples.com/atom"/>
+                    <link href="https://examples.com/"/>
+                    <entry><title>Hey!</title><link href="https://examples.com/1"/></entry>
+                </feed>
+        "#));
+        assert_eq!(parser.parse_channel().unwrap(),Channel{
+            title:String::from("Tom & Jerry"),
+            link:String::from("https://examples.com/"),
+            description:String::from("Cartoons"),
+            language:String::from("de"),
+            last_build_date:String::from("2020-05-28T15:00:00Z"),
+            image:String::from("https://examples.com/logo.png"),
+        });
+    }
+
+    #[test]
+    fn future_rss_channel_json(){
+        let mut parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0"><channel>
+                    <title>Examples &amp; more</title>
+                    <image><title>Logo</title><link>https://examples.com/logo.png</link></image>
+                    <link>https://examples.com</link>
+                    <description>hello.world!</description>
+                    <item><title>Hey!</title><link>https://examples.com/1</link></item>
+                </channel></rss>
+        "#)).unwrap();
+        assert!(json::parse(&parser.parse_json().unwrap()).unwrap().is_array());
+
+        parser.json_channel = true;
+        let json = json::parse(&parser.parse_json().unwrap()).unwrap();
+        assert_eq!(json["channel"]["title"],"Examples & more");
+        assert_eq!(json["channel"]["link"],"https://examples.com");
+        assert_eq!(json["channel"]["description"],"hello.world!");
+        assert_eq!(json["channel"]["language"],"");
+        assert_eq!(json["channel"]["image"],"");
+        assert_eq!(json["items"][0]["title"],"Hey!");
+
+        let (channel,items) = parser.parse_feed().unwrap();
+        assert_eq!((channel.title.as_str(),items.len()),("Examples & more",1));
+    }
+}
@@ -0,0 +1,213 @@
+//!
+//! rssCloud
+//!
+//! A channel's `<cloud>` names a server that calls back whenever the feed
+//! changes; [`RssCloud`] reads it and registers for those notifications,
+//! for the platforms still pushing updates this way instead of WebSub.
+//!
+
+use quick_xml::Reader;
+use quick_xml::events::{Event,BytesText};
+
+/// &lt;cloud/&gt; of the channel
+pub static RSS_DEFAULT_CLOUD_TAG:&str = "cloud";
+
+///
+/// Notification server announced by a channel
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0"><channel>
+///             <cloud domain="rpc.examples.com" port="80" path="/RPC2"
+///                 registerProcedure="rssCloud.pleaseNotify" protocol="xml-rpc"/>
+///         </channel></rss>
+///         "#
+///     ))?;
+///     let cloud = parser.parse_cloud()?.unwrap();
+///     assert_eq!(cloud.url(),"http://rpc.examples.com/RPC2");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct RssCloud{
+    pub domain:String,
+    pub port:u16,
+    pub path:String,
+    pub register_procedure:String,
+    /// `xml-rpc`, `http-post` or `soap`
+    pub protocol:String,
+}
+
+impl RssCloud{
+
+    /// Endpoint the registration is sent to
+    pub fn url(&self)->String{
+        let scheme = if self.port == 443 { "https" } else { "http" };
+        let path = if self.path.starts_with('/') { self.path.clone() } else { format!("/{}",self.path) };
+        match self.port {
+            80 | 443 => format!("{}://{}{}",scheme,self.domain,path),
+            port => format!("{}://{}:{}{}",scheme,self.domain,port,path),
+        }
+    }
+
+    ///
+    /// Content type and body asking the cloud to call `notify_procedure`,
+    /// or post, on `notify_port` and `notify_path` of the caller when
+    /// `feed` changes; `Unsupported` for soap
+    ///
+    pub fn registration(&self,feed:&str,notify_procedure:&str,notify_port:u16,notify_path:&str)->Result<(&'static str,String),std::io::Error>{
+        match self.protocol.to_ascii_lowercase().as_str() {
+            "http-post" => Ok(("application/x-www-form-urlencoded",url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("notifyProcedure",notify_procedure)
+                .append_pair("port",&notify_port.to_string())
+                .append_pair("path",notify_path)
+                .append_pair("protocol","http-post")
+                .append_pair("url1",feed)
+                .finish())),
+            "xml-rpc" => Ok(("text/xml",format!(
+                "<?xml version=\"1.0\"?><methodCall><methodName>{}</methodName><params>\
+                 <param><value><string>{}</string></value></param>\
+                 <param><value><i4>{}</i4></value></param>\
+                 <param><value><string>{}</string></value></param>\
+                 <param><value><string>xml-rpc</string></value></param>\
+                 <param><value><array><data><value><string>{}</string></value></data></array></value></param>\
+                 </params></methodCall>",
+                escape(&self.register_procedure),escape(notify_procedure),notify_port,escape(notify_path),escape(feed)
+            ))),
+            protocol => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Failed by RssCloud::registration: {} protocol",protocol)
+            )),
+        }
+    }
+
+    ///
+    /// Register for notifications of `feed`, see [`RssCloud::registration`];
+    /// a refusal of the cloud comes back as an error
+    ///
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn register(&self,client:&reqwest::blocking::Client,feed:&str,notify_procedure:&str,notify_port:u16,notify_path:&str)->Result<(),std::io::Error>{
+        let (content_type,body) = self.registration(feed,notify_procedure,notify_port,notify_path)?;
+        let answer = client.post(&self.url())
+            .header("Content-Type",content_type)
+            .body(body)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        let refused = answer.contains("<fault>") || answer.to_ascii_lowercase().contains("success=\"false\"");
+        if refused {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Failed by RssCloud::register: {}",answer.trim())
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn escape(text:&str)->String{
+    String::from_utf8_lossy(BytesText::from_plain_str(text).escaped()).into_owned()
+}
+
+///
+/// First `<cloud>` of `xml`, `None` when the channel has none or it lacks
+/// a domain
+///
+pub(crate) fn parse_cloud(xml:&str)->Result<Option<RssCloud>,std::io::Error>{
+    let error = |e:quick_xml::Error| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by RssParser::parse_cloud: {:?}",e));
+    let mut reader = Reader::from_str(xml);
+    reader.expand_empty_elements(true);
+    let mut buff = Vec::new();
+    loop {
+        buff.clear();
+        match reader.read_event(&mut buff).map_err(error)? {
+            Event::Start(ref e) if e.name().eq_ignore_ascii_case(RSS_DEFAULT_CLOUD_TAG.as_bytes()) => {
+                let mut cloud = RssCloud::default();
+                for attribute in e.attributes() {
+                    let attribute = attribute.map_err(error)?;
+                    let value = attribute.unescape_and_decode_value(&reader).map_err(error)?;
+                    match attribute.key.to_ascii_lowercase().as_slice() {
+                        b"domain" => cloud.domain = value.trim().to_string(),
+                        b"port" => cloud.port = value.trim().parse().unwrap_or(80),
+                        b"path" => cloud.path = value.trim().to_string(),
+                        b"registerprocedure" => cloud.register_procedure = value.trim().to_string(),
+                        b"protocol" => cloud.protocol = value.trim().to_string(),
+                        _ => (),
+                    }
+                }
+                return Ok(Some(cloud).filter(|cloud| !cloud.domain.is_empty()));
+            }
+            Event::Eof => return Ok(None),
+            _ => (),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,RssCloud};
+
+    fn cloud(protocol:&str)->RssCloud{
+        RssCloud{
+            domain:String::from("127.0.0.1"),
+            port:5337,
+            path:String::from("RPC2"),
+            register_procedure:String::from("rssCloud.pleaseNotify"),
+            protocol:String::from(protocol),
+        }
+    }
+
+    #[test]
+    fn future_rss_cloud_parse(){
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0"><channel>
+                    <cloud domain="127.0.0.1" port="5337" path="RPC2" registerProcedure="rssCloud.pleaseNotify" protocol="http-post"/>
+                    <item><title>Hey!</title></item>
+                </channel></rss>
+        "#)).unwrap();
+        assert_eq!(parser.parse_cloud().unwrap(),Some(cloud("http-post")));
+        assert_eq!(cloud("soap").url(),"http://127.0.0.1:5337/RPC2");
+
+        let parser = RssParser::from_str(String::from(r#"<?xml version="1.0"?><rss><channel><cloud port="80"/></channel></rss>"#)).unwrap();
+        assert_eq!(parser.parse_cloud().unwrap(),None);
+    }
+
+    #[test]
+    fn future_rss_cloud_registration(){
+        let (content_type,body) = cloud("http-post").registration("https://examples.com/rss?a=1&b=2","",8080,"/notify").unwrap();
+        assert_eq!(content_type,"application/x-www-form-urlencoded");
+        assert_eq!(body,"notifyProcedure=&port=8080&path=%2Fnotify&protocol=http-post&url1=https%3A%2F%2Fexamples.com%2Frss%3Fa%3D1%26b%3D2");
+
+        let (content_type,body) = cloud("XML-RPC").registration("https://examples.com/rss?a=1&b=2","app.notify",8080,"/RPC2").unwrap();
+        assert_eq!(content_type,"text/xml");
+        assert!(body.contains("<methodName>rssCloud.pleaseNotify</methodName>"));
+        assert!(body.contains("<i4>8080</i4>"));
+        assert!(body.contains("<string>https://examples.com/rss?a=1&amp;b=2</string>"));
+
+        let err = cloud("soap").registration("https://examples.com/rss","",80,"/").unwrap_err();
+        assert_eq!(err.kind(),std::io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_cloud_register(){
+        use crate::mock::serve;
+
+        let client = reqwest::blocking::Client::new();
+        let server = serve(r#"<?xml version="1.0"?><notifyResult success="true" msg="Registered"/>"#);
+        let cloud = RssCloud{ port:server.port(),..cloud("http-post") };
+        assert!(cloud.register(&client,"https://examples.com/rss","",8080,"/notify").is_ok());
+
+        let server = serve(r#"<?xml version="1.0"?><notifyResult success="false" msg="Unreachable"/>"#);
+        let cloud = RssCloud{ port:server.port(),..cloud };
+        let err = cloud.register(&client,"https://examples.com/rss","",8080,"/notify").unwrap_err();
+        assert_eq!(err.kind(),std::io::ErrorKind::PermissionDenied);
+    }
+}
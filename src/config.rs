@@ -0,0 +1,376 @@
+//!
+//! Configuration Files
+//!
+//! Tag mappings, HTTP options, schedules, keyword filters and the feed list
+//! read from a JSON file, so tools built on the crate are configured without
+//! recompiling. Every feed inherits the top level settings and may override
+//! its charset, tags and schedule.
+//!
+
+use std::path::Path;
+use std::time::Duration;
+use json::JsonValue;
+use crate::{RssParser,RssItem,AdaptiveSchedule,Query,FieldMapping};
+
+///
+/// Settings of one feed, defaults already merged in
+///
+#[derive(Debug,Clone)]
+pub struct FeedConfig{
+    pub url:String,
+    pub charset:String,
+    pub parser:RssParser,
+    pub schedule:AdaptiveSchedule,
+    /// See [`FeedWatcher::hash_body`](crate::FeedWatcher::hash_body)
+    pub hash_body:bool,
+}
+
+///
+/// Parser and aggregator settings
+///
+/// ```
+/// use future_rss::Config;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let config = Config::from_json(r#"{
+///         "tags": { "author": "dc:creator" },
+///         "http": { "timeout": "30s" },
+///         "schedule": { "min": "10m" },
+///         "filter": { "exclude": ["sponsored"] },
+///         "feeds": [
+///             { "url": "https://www.zhihu.com/rss" },
+///             { "url": "https://example.com/atom", "charset": "gbk", "tags": { "node": "entry" } }
+///         ]
+///     }"#)?;
+///     assert_eq!(config.feeds[1].parser.node_tag,"entry");
+///     assert_eq!(config.feeds[1].parser.author_tag,"dc:creator");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct Config{
+    /// Charset used when the server announces none
+    pub charset:String,
+    /// Tag mappings shared by every feed
+    pub parser:RssParser,
+    pub schedule:AdaptiveSchedule,
+    /// Whole request timeout
+    pub timeout:Option<Duration>,
+    pub connect_timeout:Option<Duration>,
+    pub user_agent:Option<String>,
+    /// Keep only items mentioning one of these words, when not empty
+    pub include:Vec<String>,
+    /// Drop items mentioning any of these words
+    pub exclude:Vec<String>,
+    /// Keep only items matching this query, see [`Query`]
+    pub query:Option<Query>,
+    pub feeds:Vec<FeedConfig>,
+}
+
+impl Default for Config{
+    fn default() -> Self {
+        Self{
+            charset:String::from("utf8"),
+            parser:RssParser::new(),
+            schedule:AdaptiveSchedule::new(),
+            timeout:None,
+            connect_timeout:None,
+            user_agent:None,
+            include:Vec::new(),
+            exclude:Vec::new(),
+            query:None,
+            feeds:Vec::new(),
+        }
+    }
+}
+
+impl Config{
+
+    ///
+    /// Load a JSON configuration file
+    ///
+    /// TOML files are refused with `Unsupported`, this build has no TOML reader.
+    ///
+    pub fn from_path<P:AsRef<Path>>(path:P)->Result<Self,std::io::Error>{
+        let path = path.as_ref();
+        if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("toml")) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Failed by Config::from_path: {} is TOML, only JSON is supported",path.display())
+            ));
+        }
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(text:&str)->Result<Self,std::io::Error>{
+        let root = json::parse(text).map_err(|e| invalid("",e.to_string()))?;
+        let mut config = Config::default();
+        for (key,value) in object(&root,"")? {
+            match key {
+                "charset" => config.charset = string(value,key)?,
+                "tags" => tags(value,&mut config.parser)?,
+                "fields" => config.parser.fields = fields(value,"fields")?,
+                "schedule" => schedule(value,&mut config.schedule)?,
+                "http" => for (key,value) in object(value,"http")? {
+                    match key {
+                        "timeout" => config.timeout = Some(duration(value,"http.timeout")?),
+                        "connect_timeout" => config.connect_timeout = Some(duration(value,"http.connect_timeout")?),
+                        "user_agent" => config.user_agent = Some(string(value,"http.user_agent")?),
+                        _ => return Err(unknown("http.",key)),
+                    }
+                },
+                "filter" => for (key,value) in object(value,"filter")? {
+                    match key {
+                        "include" => config.include = strings(value,"filter.include")?,
+                        "exclude" => config.exclude = strings(value,"filter.exclude")?,
+                        "query" => config.query = Some(Query::parse(&string(value,"filter.query")?).map_err(|e| invalid("filter.query",e))?),
+                        _ => return Err(unknown("filter.",key)),
+                    }
+                },
+                "feeds" => {}
+                _ => return Err(unknown("",key)),
+            }
+        }
+
+        if !root["feeds"].is_null() && !root["feeds"].is_array() {
+            return Err(invalid("feeds","expected an array"));
+        }
+        for feed in root["feeds"].members() {
+            let mut merged = FeedConfig{
+                url:String::new(),
+                charset:config.charset.clone(),
+                parser:config.parser.clone(),
+                schedule:config.schedule.clone(),
+                hash_body:false,
+            };
+            for (key,value) in object(feed,"feeds")? {
+                match key {
+                    "url" => merged.url = string(value,"feeds.url")?,
+                    "charset" => merged.charset = string(value,"feeds.charset")?,
+                    "tags" => tags(value,&mut merged.parser)?,
+                    "fields" => merged.parser.fields = fields(value,"feeds.fields")?,
+                    "schedule" => schedule(value,&mut merged.schedule)?,
+                    "hash_body" => merged.hash_body = value.as_bool().ok_or_else(|| invalid("feeds.hash_body","expected a boolean"))?,
+                    _ => return Err(unknown("feeds.",key)),
+                }
+            }
+            if merged.url.is_empty() {
+                return Err(invalid("feeds.url","missing"));
+            }
+            config.feeds.push(merged);
+        }
+        Ok(config)
+    }
+
+    ///
+    /// Item passes the keyword filter and the query, words are matched
+    /// case-insensitively against title and description
+    ///
+    pub fn accepts(&self,item:&RssItem)->bool{
+        let text = format!("{}\n{}",item.title,item.description).to_lowercase();
+        let mentions = |word:&String| text.contains(&word.to_lowercase());
+        (self.include.is_empty() || self.include.iter().any(mentions))
+            && !self.exclude.iter().any(mentions)
+            && self.query.as_ref().map_or(true,|query| query.matches(item))
+    }
+
+    /// Client with the configured timeouts and user agent, the environment
+    /// provides what the file leaves out, see [`NetworkSettings`](crate::NetworkSettings)
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn client(&self)->Result<reqwest::blocking::Client,std::io::Error>{
+        let mut settings = crate::NetworkSettings::from_env();
+        if let Some(agent) = &self.user_agent {
+            settings = settings.user_agent(agent);
+        }
+        if let Some(timeout) = self.timeout {
+            settings = settings.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            settings = settings.connect_timeout(timeout);
+        }
+        settings.client()
+    }
+
+    /// One watcher per configured feed
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn watchers(&self)->Vec<crate::FeedWatcher>{
+        self.feeds.iter()
+            .map(|feed| {
+                let mut watcher = crate::FeedWatcher::new(&feed.url,&feed.charset);
+                watcher.parser = feed.parser.clone();
+                watcher.schedule = feed.schedule.clone();
+                watcher.hash_body = feed.hash_body;
+                watcher
+            })
+            .collect()
+    }
+}
+
+fn tags(value:&JsonValue,parser:&mut RssParser)->Result<(),std::io::Error>{
+    for (key,value) in object(value,"tags")? {
+        let tag = match key {
+            "node" => &mut parser.node_tag,
+            "title" => &mut parser.title_tag,
+            "link" => &mut parser.link_tag,
+            "author" => &mut parser.author_tag,
+            "description" => &mut parser.description_tag,
+            "content" => &mut parser.content_tag,
+            "guid" => &mut parser.guid_tag,
+            "publish" => &mut parser.publish_tag,
+            "updated" => &mut parser.updated_tag,
+            _ => return Err(unknown("tags.",key)),
+        };
+        *tag = string(value,"tags")?;
+    }
+    Ok(())
+}
+
+/// `[{"name": "creator", "tags": ["dc:creator"], "required": false}]`
+fn fields(value:&JsonValue,at:&str)->Result<Vec<FieldMapping>,std::io::Error>{
+    if !value.is_array() {
+        return Err(invalid(at,"expected an array"));
+    }
+    value.members()
+        .map(|field| {
+            let mut mapping = FieldMapping::new("",&[]);
+            for (key,value) in object(field,at)? {
+                match key {
+                    "name" => mapping.output_name = string(value,&format!("{}.name",at))?,
+                    "tags" => mapping.tags = strings(value,&format!("{}.tags",at))?,
+                    "required" => mapping.required = value.as_bool().ok_or_else(|| invalid(&format!("{}.required",at),"expected a boolean"))?,
+                    _ => return Err(unknown(&format!("{}.",at),key)),
+                }
+            }
+            if mapping.output_name.is_empty() {
+                return Err(invalid(&format!("{}.name",at),"missing"));
+            }
+            Ok(mapping)
+        })
+        .collect()
+}
+
+fn schedule(value:&JsonValue,schedule:&mut AdaptiveSchedule)->Result<(),std::io::Error>{
+    for (key,value) in object(value,"schedule")? {
+        match key {
+            "min" => schedule.min_interval = duration(value,"schedule.min")?,
+            "max" => schedule.max_interval = duration(value,"schedule.max")?,
+            "initial" => schedule.initial_interval = duration(value,"schedule.initial")?,
+            _ => return Err(unknown("schedule.",key)),
+        }
+    }
+    Ok(())
+}
+
+fn object<'a>(value:&'a JsonValue,at:&str)->Result<json::object::Iter<'a>,std::io::Error>{
+    match value {
+        JsonValue::Object(object) => Ok(object.iter()),
+        _ => Err(invalid(at,"expected an object")),
+    }
+}
+
+fn string(value:&JsonValue,at:&str)->Result<String,std::io::Error>{
+    value.as_str().map(String::from).ok_or_else(|| invalid(at,"expected a string"))
+}
+
+fn strings(value:&JsonValue,at:&str)->Result<Vec<String>,std::io::Error>{
+    if !value.is_array() {
+        return Err(invalid(at,"expected an array of strings"));
+    }
+    value.members().map(|v| string(v,at)).collect()
+}
+
+/// Seconds as a number, or a string such as `30s`, `5m`, `1h`
+fn duration(value:&JsonValue,at:&str)->Result<Duration,std::io::Error>{
+    if let Some(seconds) = value.as_f64() {
+        return Duration::try_from_secs_f64(seconds).map_err(|e| invalid(at,e.to_string()));
+    }
+    let text = value.as_str().ok_or_else(|| invalid(at,"expected a duration"))?.trim();
+    let split = text.find(|c:char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let unit = match &text[split..] {
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" => 24.0 * 60.0 * 60.0,
+        _ => return Err(invalid(at,format!("unknown unit in {}",text))),
+    };
+    text[..split].parse::<f64>()
+        .ok()
+        .and_then(|n| Duration::try_from_secs_f64(n * unit).ok())
+        .ok_or_else(|| invalid(at,format!("invalid duration {}",text)))
+}
+
+fn invalid<E:std::fmt::Display>(at:&str,error:E)->std::io::Error{
+    let at = if at.is_empty() { String::new() } else { format!(" {}:",at) };
+    std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by Config:{} {}",at,error))
+}
+
+fn unknown(prefix:&str,key:&str)->std::io::Error{
+    invalid(&format!("{}{}",prefix,key),"unknown key")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{Config,RssItem};
+
+    #[test]
+    fn future_rss_config(){
+        let config = Config::from_json(r#"{
+            "charset": "gbk",
+            "tags": { "author": "dc:creator" },
+            "http": { "timeout": 12.5, "connect_timeout": "2s", "user_agent": "reader/1.0" },
+            "schedule": { "min": "1m", "max": "2h" },
+            "filter": { "include": ["Rust"], "exclude": ["sponsored"] },
+            "feeds": [
+                { "url": "https://example.com/rss" },
+                { "url": "https://example.com/atom", "charset": "utf8", "tags": { "node": "entry" },
+                  "schedule": { "initial": "30s" }, "hash_body": true }
+            ]
+        }"#).unwrap();
+        assert_eq!(config.timeout,Some(Duration::from_millis(12500)));
+        assert_eq!(config.connect_timeout,Some(Duration::from_secs(2)));
+        assert_eq!(config.feeds.len(),2);
+        assert_eq!(config.feeds[0].charset,"gbk");
+        assert_eq!(config.feeds[0].parser.node_tag,"item");
+        assert_eq!(config.feeds[1].parser.node_tag,"entry");
+        assert_eq!(config.feeds[1].parser.author_tag,"dc:creator");
+        assert_eq!(config.feeds[1].schedule.min_interval,Duration::from_secs(60));
+        assert_eq!(config.feeds[1].schedule.max_interval,Duration::from_secs(7200));
+        assert_eq!(config.feeds[1].schedule.initial_interval,Duration::from_secs(30));
+        assert!(config.feeds[1].hash_body);
+
+        let item = |title:&str| RssItem{ title:String::from(title), ..RssItem::default() };
+        assert!(config.accepts(&item("Learning rust")));
+        assert!(!config.accepts(&item("Rust, sponsored")));
+        assert!(!config.accepts(&item("Go")));
+
+        let config = Config::from_json(r#"{"filter":{"query":"title ~ rust AND NOT title ~ go"}}"#).unwrap();
+        assert!(config.accepts(&item("Rust")));
+        assert!(!config.accepts(&item("Rust and Go")));
+        assert!(Config::from_json(r#"{"filter":{"query":"title >"}}"#).unwrap_err().to_string().contains("filter.query"));
+
+        let config = Config::from_json(r#"{
+            "fields": [{ "name": "creator", "tags": ["dc:creator","author"], "required": true }],
+            "feeds": [{ "url": "https://example.com/rss" }, { "url": "https://example.com/atom", "fields": [] }]
+        }"#).unwrap();
+        assert_eq!(config.feeds[0].parser.fields[0].tags,["dc:creator","author"]);
+        assert!(config.feeds[0].parser.fields[0].required);
+        assert!(config.feeds[1].parser.fields.is_empty());
+        assert!(Config::from_json(r#"{"fields":[{"tags":[]}]}"#).is_err());
+
+        assert!(Config::from_json(r#"{"tag":{}}"#).unwrap_err().to_string().contains("tag: unknown key"));
+        assert!(Config::from_json(r#"{"feeds":[{"charset":"utf8"}]}"#).is_err());
+        assert!(Config::from_json(r#"{"schedule":{"min":"5w"}}"#).is_err());
+        assert!(Config::from_path("feeds.toml").is_err());
+    }
+
+    #[test]
+    fn future_rss_config_path(){
+        let path = std::env::temp_dir().join(format!("future_rss_config_{}.json",std::process::id()));
+        std::fs::write(&path,r#"{"feeds":[{"url":"https://example.com/rss"}]}"#).unwrap();
+        let config = Config::from_path(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(config.unwrap().feeds[0].url,"https://example.com/rss");
+    }
+}
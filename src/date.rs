@@ -0,0 +1,613 @@
+//!
+//! Publish Dates
+//!
+//! Feeds mix RFC 2822 dates in any zone, RFC 3339 and bare `YYYY-MM-DD HH:MM:SS`
+//! strings; [`Timestamp`] reads all of them into one UTC instant so items from
+//! different sources sort and store the same way. Dates written with month
+//! names in another language, `28. Mai 2020` or `2020年5月28日`, are read
+//! through the tables below.
+//!
+
+use std::fmt;
+
+static MONTHS:[&str;12] = ["Jan","Feb","Mar","Apr","May","Jun","Jul","Aug","Sep","Oct","Nov","Dec"];
+static WEEKDAYS:[&str;7] = ["Sun","Mon","Tue","Wed","Thu","Fri","Sat"];
+
+/// Month names by month, in English, German, French, Spanish, Italian,
+/// Portuguese, Dutch and Russian; any prefix of three letters or more matches
+static LOCALE_MONTHS:[&[&str];12] = [
+    &["january","januar","jänner","janvier","enero","gennaio","janeiro","januari","январь","января"],
+    &["february","februar","février","fevrier","febrero","febbraio","fevereiro","februari","февраль","февраля"],
+    &["march","märz","maerz","mars","marzo","março","marco","maart","март","марта"],
+    &["april","avril","abril","aprile","апрель","апреля"],
+    &["may","mai","mayo","maggio","maio","mei","май","мая"],
+    &["june","juni","juin","junio","giugno","junho","июнь","июня"],
+    &["july","juli","juillet","julio","luglio","julho","июль","июля"],
+    &["august","août","aout","agosto","augustus","август","августа"],
+    &["september","septembre","septiembre","setiembre","settembre","setembro","сентябрь","сентября"],
+    &["october","oktober","octobre","octubre","ottobre","outubro","октябрь","октября"],
+    &["november","novembre","noviembre","novembro","ноябрь","ноября"],
+    &["december","dezember","décembre","decembre","diciembre","dicembre","dezembro","декабрь","декабря"],
+];
+
+/// Day names in the languages of [`LOCALE_MONTHS`], skipped when met;
+/// any prefix of two letters or more matches
+static LOCALE_WEEKDAYS:&[&str] = &[
+    "sunday","monday","tuesday","wednesday","thursday","friday","saturday",
+    "sonntag","montag","dienstag","mittwoch","donnerstag","freitag","samstag","sonnabend",
+    "dimanche","lundi","mardi","mercredi","jeudi","vendredi","samedi",
+    "domingo","lunes","martes","miércoles","miercoles","jueves","viernes","sábado","sabado",
+    "domenica","lunedì","martedì","mercoledì","giovedì","venerdì","sabato",
+    "segunda","terça","terca","quarta","quinta","sexta","feira",
+    "zondag","maandag","dinsdag","woensdag","donderdag","vrijdag","zaterdag",
+    "воскресенье","понедельник","вторник","среда","четверг","пятница","суббота",
+];
+
+/// Words that join the parts of a written date, `le 28 mai` or `28 de mayo de 2020`
+static LOCALE_FILLERS:&[&str] = &["le","de","del","à","a","um","am","den","der","op","om","às","alle","г","uhr","h"];
+
+///
+/// Instant in UTC, seconds since the unix epoch
+///
+/// ```
+/// use future_rss::Timestamp;
+///
+/// fn main(){
+///     let a = Timestamp::parse("Thu, 28 May 2020 23:00:00 +0800").unwrap();
+///     let b = Timestamp::parse("2020-05-28T15:00:00Z").unwrap();
+///     assert_eq!(a,b);
+///     assert_eq!(a.to_rfc3339(),"2020-05-28T15:00:00Z");
+/// }
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Timestamp{
+    seconds:i64,
+}
+
+impl Timestamp{
+
+    pub fn from_unix(seconds:i64)->Self{
+        Self{ seconds }
+    }
+
+    pub fn unix(&self)->i64{
+        self.seconds
+    }
+
+    ///
+    /// Read an RFC 2822, RFC 3339 or `YYYY-MM-DD[ HH:MM:SS]` date, falling
+    /// back to month and day names in other languages; dates without an
+    /// offset are taken as UTC
+    ///
+    pub fn parse(text:&str)->Option<Self>{
+        parse_builtin(text,0)
+    }
+
+    /// `2020-05-28T15:00:00Z`
+    pub fn to_rfc3339(&self)->String{
+        let (year,month,day,hour,minute,second) = self.parts();
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",year,month,day,hour,minute,second)
+    }
+
+    /// `Thu, 28 May 2020 15:00:00 +0000`
+    pub fn to_rfc2822(&self)->String{
+        let (year,month,day,hour,minute,second) = self.parts();
+        let weekday = (self.seconds.div_euclid(86400) + 4).rem_euclid(7) as usize;
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} +0000",
+            WEEKDAYS[weekday],day,MONTHS[month as usize - 1],year,hour,minute,second
+        )
+    }
+
+    fn parts(&self)->(i64,u32,u32,i64,i64,i64){
+        let (year,month,day) = civil_from_days(self.seconds.div_euclid(86400));
+        let time = self.seconds.rem_euclid(86400);
+        (year,month,day,time / 3600,time % 3600 / 60,time % 60)
+    }
+}
+
+impl fmt::Display for Timestamp{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str(&self.to_rfc3339())
+    }
+}
+
+///
+/// Date reading for feeds [`Timestamp::parse`] gets wrong: formats of their
+/// own tried first, and the zone of dates that name none
+///
+/// Formats take `%Y` (year), `%y` (two digit year), `%m`, `%d` or `%e`
+/// (day), `%H`, `%I` with `%p` (AM/PM), `%M`, `%S`, `%b` or `%B` (month
+/// name, any language [`Timestamp::parse`] knows), `%a` or `%A` (day name,
+/// skipped), `%z` (zone) and `%%`; a space matches any run of whitespace.
+///
+/// ```
+/// use future_rss::{DateParser,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let dates = DateParser::new()
+///         .format("%d/%m/%Y %H:%M")
+///         .default_offset(8 * 3600);
+///     assert_eq!(dates.parse("28/05/2020 23:00").unwrap().to_rfc3339(),"2020-05-28T15:00:00Z");
+///     // the built-in formats still apply, with their own zone when they have one
+///     assert_eq!(dates.parse("Thu, 28 May 2020 15:00:00 GMT").unwrap().to_rfc3339(),"2020-05-28T15:00:00Z");
+///
+///     let mut parser = RssParser::new();
+///     parser.date_parser = Some(dates);
+///     parser.set_xml(String::from("<rss><item><pubDate>2020-05-28 23:00:00</pubDate></item></rss>"));
+///     assert_eq!(parser.parse_vec()?[0].publish,"2020-05-28T15:00:00Z");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct DateParser{
+    formats:Vec<String>,
+    default_offset:i64,
+}
+
+impl DateParser{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    /// Try `format` before the built-in formats, after the ones added before it
+    pub fn format(mut self,format:&str)->Self{
+        self.formats.push(format.to_string());
+        self
+    }
+
+    /// Seconds east of UTC of dates without a zone, UTC by default
+    pub fn default_offset(mut self,seconds:i64)->Self{
+        self.default_offset = seconds;
+        self
+    }
+
+    pub fn formats(&self)->&[String]{
+        &self.formats
+    }
+
+    pub fn parse(&self,text:&str)->Option<Timestamp>{
+        let text = text.trim();
+        self.formats.iter()
+            .find_map(|format| parse_format(format,text,self.default_offset))
+            .or_else(|| parse_builtin(text,self.default_offset))
+    }
+}
+
+fn parse_builtin(text:&str,default:i64)->Option<Timestamp>{
+    let text = text.trim();
+    if text.as_bytes().first().is_some_and(u8::is_ascii_digit) && text.as_bytes().get(4) == Some(&b'-') {
+        parse_iso(text,default)
+    }else {
+        parse_rfc2822(text,default)
+    }.or_else(|| parse_localized(text,default))
+}
+
+/// `text` read with a [`DateParser`] format, all of it
+fn parse_format(format:&str,text:&str,default:i64)->Option<Timestamp>{
+    let (mut year,mut month,mut day) = (None,None,None);
+    let (mut hour,mut minute,mut second,mut pm) = (0,0,0,None);
+    let mut zone = None;
+    let mut rest = text;
+    let mut spec = format.chars();
+    while let Some(c) = spec.next() {
+        if c.is_whitespace() {
+            rest = rest.trim_start();
+            continue;
+        }
+        if c != '%' {
+            let next = rest.chars().next()?;
+            if !next.to_lowercase().eq(c.to_lowercase()) {
+                return None;
+            }
+            rest = &rest[next.len_utf8()..];
+            continue;
+        }
+        match spec.next()? {
+            'Y' => year = Some(digits(&mut rest,4,4)?),
+            'y' => year = digits(&mut rest,2,2).map(|y| if y < 50 { y + 2000 } else { y + 1900 }),
+            'm' => month = Some(digits(&mut rest,1,2)?),
+            'd' | 'e' => {
+                rest = rest.trim_start();
+                day = Some(digits(&mut rest,1,2)?);
+            }
+            'H' | 'I' => hour = digits(&mut rest,1,2)?,
+            'M' => minute = digits(&mut rest,2,2)?,
+            'S' => second = digits(&mut rest,2,2)?,
+            'p' => {
+                let word = letters(&mut rest).to_ascii_lowercase();
+                pm = Some(match word.as_str() {
+                    "am" | "a.m" => false,
+                    "pm" | "p.m" => true,
+                    _ => return None,
+                });
+                rest = rest.strip_prefix('.').unwrap_or(rest);
+            }
+            'b' | 'B' => {
+                let word = letters(&mut rest).to_lowercase();
+                month = Some(self::month(&word).or_else(|| locale_month(&word))?);
+                rest = rest.strip_prefix('.').unwrap_or(rest);
+            }
+            'a' | 'A' => {
+                letters(&mut rest);
+                rest = rest.strip_prefix('.').unwrap_or(rest);
+            }
+            'z' => {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                zone = Some(offset(&rest[..end])?);
+                rest = &rest[end..];
+            }
+            '%' => rest = rest.strip_prefix('%')?,
+            _ => return None,
+        }
+    }
+    if !rest.trim().is_empty() {
+        return None;
+    }
+    if let Some(pm) = pm {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + if pm { 12 } else { 0 };
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    timestamp(year?,month?,day?,(hour,minute,second.min(59)),zone.unwrap_or(default))
+}
+
+/// Take `min` to `max` leading digits off `text`
+fn digits(text:&mut &str,min:usize,max:usize)->Option<i64>{
+    let end = text.bytes().take(max).take_while(u8::is_ascii_digit).count();
+    if end < min {
+        return None;
+    }
+    let value = number(&text[..end])?;
+    *text = &text[end..];
+    Some(value)
+}
+
+/// Take the leading letters off `text`, dots between them included
+fn letters<'t>(text:&mut &'t str)->&'t str{
+    let end = text.find(|c:char| !c.is_alphabetic() && c != '.').unwrap_or(text.len());
+    let word = text[..end].trim_end_matches('.');
+    *text = &text[word.len()..];
+    word
+}
+
+/// `YYYY-MM-DD`, then `T` or a space and `HH:MM[:SS[.fff]]`, then `Z` or an offset
+fn parse_iso(text:&str,default:i64)->Option<Timestamp>{
+    let date = text.get(..10)?;
+    let mut fields = date.split('-');
+    let year = number(fields.next()?)?;
+    let month = number(fields.next()?)?;
+    let day = number(fields.next()?)?;
+    let rest = &text[10..];
+    if rest.is_empty() {
+        return timestamp(year,month,day,(0,0,0),default);
+    }
+    let rest = rest.strip_prefix(['T','t',' '])?.trim_start();
+    let end = rest.find(|c:char| !c.is_ascii_digit() && c != ':' && c != '.').unwrap_or(rest.len());
+    let (time,zone) = rest.split_at(end);
+    let time = time.split('.').next()?;
+    let zone = match zone.trim() {
+        "" => default,
+        zone => offset(zone)?,
+    };
+    timestamp(year,month,day,clock(time)?,zone)
+}
+
+/// `[Thu,] 28 May 2020 15:00[:00] [zone]`
+fn parse_rfc2822(text:&str,default:i64)->Option<Timestamp>{
+    let text = match text.find(',') {
+        Some(comma) => &text[comma + 1..],
+        None => text,
+    };
+    let mut tokens = text.split_whitespace();
+    let day = number(tokens.next()?)?;
+    let month = month(tokens.next()?)?;
+    let year = match tokens.next()? {
+        year if year.len() == 2 => number(year).map(|y| if y < 50 { y + 2000 } else { y + 1900 })?,
+        year => number(year)?,
+    };
+    let time = clock(tokens.next()?)?;
+    let zone = match tokens.next() {
+        Some(zone) => offset(zone)?,
+        None => default,
+    };
+    timestamp(year,month,day,time,zone)
+}
+
+///
+/// Day, month name and year in any order, with optional day name, clock and
+/// zone, e.g. `Mittwoch, 27. Mai 2020 10:00 +0200` or `jeu. 28 mai 2020`
+///
+fn parse_localized(text:&str,default:i64)->Option<Timestamp>{
+    if text.contains(['年','년']) {
+        return parse_cjk(text,default);
+    }
+    let text = text.to_lowercase();
+    let (mut day,mut year,mut time,mut zone) = (None,None,None,None);
+    // (month, also a day name) of every month-like word
+    let mut months = Vec::new();
+    for token in text.split([' ',',','\t']).filter(|t| !t.is_empty()) {
+        if token.starts_with(['+','-']) || ["z","ut","utc","gmt"].contains(&token) {
+            zone = Some(offset(token)?);
+            continue;
+        }
+        if token.contains(':') {
+            time = Some(clock(token)?);
+            continue;
+        }
+        let word = token.trim_end_matches('.');
+        if let Some(value) = number(word) {
+            match (word.len(),day) {
+                (4,_) => year = Some(value),
+                (1..=2,None) => day = Some(value),
+                _ => return None,
+            }
+            continue;
+        }
+        let weekday = word.chars().count() >= 2 && LOCALE_WEEKDAYS.iter().any(|name| name.starts_with(word));
+        match locale_month(word) {
+            Some(month) => months.push((month,weekday)),
+            None if weekday || LOCALE_FILLERS.contains(&word) => (),
+            None => return None,
+        }
+    }
+    // `mar.` is March and Tuesday in Spanish, a second month settles it
+    let month = match months.as_slice() {
+        [(month,_)] => *month,
+        _ => match months.iter().filter(|(_,weekday)| !weekday).collect::<Vec<_>>().as_slice() {
+            [(month,_)] => *month,
+            _ => return None,
+        },
+    };
+    timestamp(year?,month,day?,time.unwrap_or((0,0,0)),zone.unwrap_or(default))
+}
+
+fn locale_month(word:&str)->Option<i64>{
+    if word.chars().count() < 3 {
+        return None;
+    }
+    let mut found = None;
+    for (month,names) in LOCALE_MONTHS.iter().enumerate() {
+        if names.iter().any(|name| name.starts_with(word)) {
+            if found.is_some() {
+                return None;
+            }
+            found = Some(month as i64 + 1);
+        }
+    }
+    found
+}
+
+///
+/// Chinese, Japanese and Korean dates, `2020年5月28日 星期四 下午3:00`
+/// or `2020년 5월 28일 15:00`
+///
+fn parse_cjk(text:&str,default:i64)->Option<Timestamp>{
+    let (year,rest) = text.split_once(['年','년'])?;
+    let (month,rest) = rest.split_once(['月','월'])?;
+    let (day,rest) = rest.split_once(['日','일'])?;
+    let (mut time,mut zone) = (None,default);
+    for token in rest.split([' ','(',')','（','）']).filter(|t| !t.is_empty()) {
+        if token.starts_with(['+','-']) || token.eq_ignore_ascii_case("utc") || token.eq_ignore_ascii_case("gmt") {
+            zone = offset(token)?;
+            continue;
+        }
+        let (pm,clock_text) = match ["下午","午後","오후","上午","午前","오전"].iter().find(|p| token.starts_with(**p)) {
+            Some(prefix) => (["下午","午後","오후"].contains(prefix),&token[prefix.len()..]),
+            None => (false,token),
+        };
+        if clock_text.contains(':') {
+            let (hour,minute,second) = clock(clock_text)?;
+            time = Some((if pm && hour < 12 { hour + 12 } else { hour },minute,second));
+        }else if !["星期","周","曜","요일"].iter().any(|name| token.contains(name)) && !"日月火水木金土".contains(token) {
+            return None;
+        }
+    }
+    timestamp(number(year.trim())?,number(month.trim())?,number(day.trim())?,time.unwrap_or((0,0,0)),zone)
+}
+
+fn number(text:&str)->Option<i64>{
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn month(name:&str)->Option<i64>{
+    let name = name.get(..3)?;
+    MONTHS.iter().position(|m| m.eq_ignore_ascii_case(name)).map(|m| m as i64 + 1)
+}
+
+/// `HH:MM[:SS]`
+fn clock(text:&str)->Option<(i64,i64,i64)>{
+    let mut fields = text.split(':');
+    let hour = number(fields.next()?)?;
+    let minute = number(fields.next()?)?;
+    let second = match fields.next() {
+        Some(second) => number(second)?,
+        None => 0,
+    };
+    if fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    // a leap second is folded into the one before it
+    Some((hour,minute,second.min(59)))
+}
+
+/// Seconds east of UTC for `Z`, `+HH:MM`, `+HHMM`, `+HH` and the RFC 2822 zone names
+fn offset(zone:&str)->Option<i64>{
+    let hours = match zone.to_ascii_uppercase().as_str() {
+        "" | "Z" | "UT" | "UTC" | "GMT" => 0,
+        "EDT" => -4,
+        "EST" | "CDT" => -5,
+        "CST" | "MDT" => -6,
+        "MST" | "PDT" => -7,
+        "PST" => -8,
+        _ => {
+            let sign = match zone.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let digits = zone[1..].replace(':',"");
+            let (hours,minutes) = match digits.len() {
+                2 => (number(&digits)?,0),
+                4 => (number(&digits[..2])?,number(&digits[2..])?),
+                _ => return None,
+            };
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            return Some(sign * (hours * 3600 + minutes * 60));
+        }
+    };
+    Some(hours * 3600)
+}
+
+/// Years beyond four digits are refused, feeds never mean them and the
+/// day arithmetic below would overflow on the largest ones
+fn timestamp(year:i64,month:i64,day:i64,(hour,minute,second):(i64,i64,i64),offset:i64)->Option<Timestamp>{
+    if !(0..=9999).contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year,month) {
+        return None;
+    }
+    let days = days_from_civil(year,month,day);
+    (days * 86400 + hour * 3600 + minute * 60 + second).checked_sub(offset).map(Timestamp::from_unix)
+}
+
+fn days_in_month(year:i64,month:i64)->i64{
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year:i64,month:i64,day:i64)->i64{
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days:i64)->(i64,u32,u32){
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (if month <= 2 { era * 400 + yoe + 1 } else { era * 400 + yoe },month,day)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{Timestamp,DateParser};
+
+    #[test]
+    fn future_rss_timestamp_parse(){
+        let utc = Timestamp::from_unix(1590678000);
+        for text in [
+            "Thu, 28 May 2020 15:00:00 GMT",
+            "28 May 2020 15:00 +0000",
+            "Thu, 28 May 20 11:00:00 EDT",
+            "Fri, 29 may 2020 00:30:00 +09:30",
+            "2020-05-28T15:00:00Z",
+            "2020-05-28T17:00:00.123+02:00",
+            "2020-05-28 15:00:00",
+            " 2020-05-28t10:00:00-0500 ",
+        ] {
+            assert_eq!(Timestamp::parse(text),Some(utc),"{}",text);
+        }
+        assert_eq!(Timestamp::parse("2020-05-28").unwrap().unix(),1590624000);
+        assert_eq!(Timestamp::parse("1969-12-31T23:59:60Z").unwrap().unix(),-1);
+
+        for text in ["","yesterday","2020-02-30","Thu, 28 Foo 2020 15:00:00 GMT","2020-05-28T25:00:00Z","2020-05-28T15:00:00 CET"] {
+            assert_eq!(Timestamp::parse(text),None,"{}",text);
+        }
+        // years that would overflow the day arithmetic
+        for text in ["28 May 999999999999999999 15:00:00 GMT","999999999999-05-28","2020年5月28日 99999999999999:00"] {
+            assert_eq!(Timestamp::parse(text),None,"{}",text);
+        }
+        assert_eq!(DateParser::new().default_offset(i64::MIN).parse("2020-05-28 15:00:00"),None);
+    }
+
+    #[test]
+    fn future_rss_timestamp_format(){
+        let time = Timestamp::parse("Sat, 29 Feb 2020 23:59:59 -0100").unwrap();
+        assert_eq!(time.to_rfc3339(),"2020-03-01T00:59:59Z");
+        assert_eq!(time.to_rfc2822(),"Sun, 01 Mar 2020 00:59:59 +0000");
+        assert_eq!(Timestamp::parse(&time.to_rfc2822()),Some(time));
+        assert_eq!(Timestamp::from_unix(0).to_string(),"1970-01-01T00:00:00Z");
+        assert!(Timestamp::from_unix(0) < time);
+    }
+
+    #[test]
+    fn future_rss_timestamp_locale(){
+        let utc = Timestamp::from_unix(1590678000);
+        for text in [
+            "Donnerstag, 28. Mai 2020 17:00:00 +0200",
+            "jeu. 28 mai 2020 15:00:00 GMT",
+            "jueves, 28 de mayo de 2020 10:00 -0500",
+            "mar., 28 may. 2020 15:00",
+            "28 maggio 2020 15:00",
+            "28 de maio de 2020 às 12:00 -03:00",
+            "28 мая 2020 г. 18:00 +0300",
+            "May 28, 2020 15:00:00",
+            "2020年5月28日 星期四 下午11:00 +0800",
+            "2020年05月28日(木) 15:00:00",
+            "2020년 5월 28일 15:00",
+        ] {
+            assert_eq!(Timestamp::parse(text),Some(utc),"{}",text);
+        }
+        assert_eq!(Timestamp::parse("28. Dezember 2020").unwrap().to_rfc3339(),"2020-12-28T00:00:00Z");
+        assert_eq!(Timestamp::parse("juil. 4, 2020").unwrap().to_rfc3339(),"2020-07-04T00:00:00Z");
+
+        for text in ["28 ma 2020","28 mai","jui 28 2020","28 mai 2020 bientôt","2020年13月1日","28 mai juin 2020"] {
+            assert_eq!(Timestamp::parse(text),None,"{}",text);
+        }
+    }
+
+    #[test]
+    fn future_rss_date_parser(){
+        let utc = Timestamp::from_unix(1590678000);
+        let dates = DateParser::new()
+            .format("%d/%m/%Y %H:%M")
+            .format("%A, %B %e, %Y at %I:%M %p %z")
+            .format("%y%m%d %H%M%S");
+        for text in [
+            "28/05/2020 15:00",
+            " 28/5/2020   15:00 ",
+            "Thursday, May 28, 2020 at 3:00 PM +0000",
+            "jeudi, mai 28, 2020 at 5:00 p.m. +02:00",
+            "200528 150000",
+            "2020-05-28T15:00:00Z",
+        ] {
+            assert_eq!(dates.parse(text),Some(utc),"{}",text);
+        }
+        for text in ["28/05/2020","28/13/2020 15:00","Thursday, May 28, 2020 at 13:00 PM +0000","28/05/2020 15:00 extra"] {
+            assert_eq!(dates.parse(text),None,"{}",text);
+        }
+        assert_eq!(dates.formats().len(),3);
+
+        // dates without a zone are read in the default one, the others keep theirs
+        let tokyo = DateParser::new().format("%Y.%m.%d %H:%M").default_offset(9 * 3600);
+        assert_eq!(tokyo.parse("2020.05.29 00:00"),Some(utc));
+        assert_eq!(tokyo.parse("2020-05-29 00:00:00"),Some(utc));
+        assert_eq!(tokyo.parse("Fri, 29 May 2020 00:00:00"),Some(utc));
+        assert_eq!(tokyo.parse("2020年5月29日 00:00"),Some(utc));
+        assert_eq!(tokyo.parse("2020-05-28T15:00:00Z"),Some(utc));
+        assert_eq!(Timestamp::parse("2020-05-29 00:00:00").unwrap().unix() - utc.unix(),9 * 3600);
+    }
+}
@@ -0,0 +1,144 @@
+//!
+//! Streaming Charset Decoding
+//!
+//! Transcodes legacy encodings (GBK, Shift_JIS, ISO-8859-x, ...) to utf8 chunk
+//! by chunk while the parser reads, so a foreign-language feed never has to
+//! be decoded into one big `String` first.
+//!
+
+use std::io::{BufRead,Read};
+use encoding_rs::{Decoder,Encoding};
+
+/// Raw bytes read from the inner reader per chunk
+pub static DECODE_DEFAULT_CHUNK:usize = 8 * 1024;
+
+///
+/// Utf8 view of a reader in another charset
+///
+/// ```
+/// use std::io::Read;
+/// use future_rss::DecodeReader;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     // "你好" in GBK
+///     let gbk:&[u8] = &[0xc4,0xe3,0xba,0xc3];
+///     let mut reader = DecodeReader::for_label(gbk,"gbk").unwrap();
+///     let mut text = String::new();
+///     reader.read_to_string(&mut text)?;
+///     assert_eq!(text,"你好");
+///     Ok(())
+/// }
+/// ```
+pub struct DecodeReader<R>{
+    inner:R,
+    decoder:Decoder,
+    input:Vec<u8>,
+    output:Vec<u8>,
+    position:usize,
+    finished:bool,
+}
+
+impl<R:Read> DecodeReader<R>{
+
+    pub fn new(inner:R,encoding:&'static Encoding)->Self{
+        Self::with_chunk(inner,encoding,DECODE_DEFAULT_CHUNK)
+    }
+
+    /// Read `chunk` raw bytes at a time instead of [`DECODE_DEFAULT_CHUNK`], at least 1
+    pub fn with_chunk(inner:R,encoding:&'static Encoding,chunk:usize)->Self{
+        Self{
+            inner,
+            decoder:encoding.new_decoder(),
+            input:vec![0;chunk.max(1)],
+            output:Vec::new(),
+            position:0,
+            finished:false,
+        }
+    }
+
+    /// Decoder for a charset label such as `gbk` or `iso-8859-1`, None if unknown
+    pub fn for_label(inner:R,label:&str)->Option<Self>{
+        Encoding::for_label(label.trim().as_bytes()).map(|encoding| Self::new(inner,encoding))
+    }
+
+    pub fn into_inner(self)->R{
+        self.inner
+    }
+}
+
+impl<R:Read> BufRead for DecodeReader<R>{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        while self.position == self.output.len() && !self.finished {
+            let read = loop {
+                match self.inner.read(&mut self.input) {
+                    Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    result => break result?,
+                }
+            };
+            let last = read == 0;
+            let capacity = self.decoder
+                .max_utf8_buffer_length(read)
+                .ok_or_else(|| std::io::Error::other("Failed by DecodeReader::fill_buf"))?;
+
+            self.output.clear();
+            self.output.resize(capacity,0);
+            // the destination holds the worst case, so the whole chunk is consumed
+            let (_,_,written,_) = self.decoder.decode_to_utf8(&self.input[..read],&mut self.output,last);
+            self.output.truncate(written);
+            self.position = 0;
+            self.finished = last;
+        }
+        Ok(&self.output[self.position..])
+    }
+
+    fn consume(&mut self,amt:usize){
+        self.position = (self.position + amt).min(self.output.len());
+    }
+}
+
+impl<R:Read> Read for DecodeReader<R>{
+    fn read(&mut self,buf:&mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use crate::{DecodeReader,RssParser};
+
+    /// Hands out one byte per read to split multibyte sequences
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_>{
+        fn read(&mut self,buf:&mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn future_rss_decode_gbk(){
+        let xml = "<rss><item><title>你好，世界</title><author>作者</author></item></rss>";
+        let (gbk,_,_) = encoding_rs::GBK.encode(xml);
+        let mut decoded = String::new();
+        DecodeReader::for_label(Trickle(&gbk),"GBK")
+            .unwrap()
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded,xml);
+
+        let mut parser = RssParser::new();
+        parser.set_xml(decoded);
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"你好，世界");
+    }
+}
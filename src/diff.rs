@@ -0,0 +1,403 @@
+//!
+//! Item Change Detection
+//!
+//! Remembers a content hash per item key so repeated fetches of the same feed
+//! can tell brand-new entries apart from entries the publisher edited, and
+//! condenses two snapshots into a [`FeedDelta`] for clients that already
+//! hold the first one.
+//!
+
+use std::collections::{HashMap,HashSet};
+use std::sync::Arc;
+use crate::{RssItem,CacheBackend};
+
+///
+/// Change detected between two fetches
+///
+#[derive(Debug,Clone,PartialEq)]
+pub enum FeedEvent{
+    /// Item key was never seen before
+    New(RssItem),
+    /// Item key is known but its content, or its `updated` date when it
+    /// has one, changed
+    Updated(RssItem),
+}
+
+impl FeedEvent{
+
+    /// Item carried by the event
+    pub fn item(&self)->&RssItem{
+        match self {
+            FeedEvent::New(item) | FeedEvent::Updated(item) => item,
+        }
+    }
+
+    pub fn item_mut(&mut self)->&mut RssItem{
+        match self {
+            FeedEvent::New(item) | FeedEvent::Updated(item) => item,
+        }
+    }
+}
+
+///
+/// Feed Diff Engine
+///
+/// ```
+/// use future_rss::{FeedDiff,FeedEvent,RssItem};
+///
+/// fn main(){
+///     let mut diff = FeedDiff::new();
+///     let mut item = RssItem::default();
+///     item.guid = String::from("unique key");
+///     item.title = String::from("Hey!");
+///
+///     let events = diff.diff(vec![item.clone()]);
+///     assert_eq!(events,vec![FeedEvent::New(item.clone())]);
+///
+///     item.title = String::from("Hey! (corrected)");
+///     let events = diff.diff(vec![item.clone()]);
+///     assert_eq!(events,vec![FeedEvent::Updated(item)]);
+/// }
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct FeedDiff{
+    seen:HashMap<String,u64>,
+    /// Shared store with the prefix of its keys
+    backend:Option<(Arc<dyn CacheBackend>,String)>,
+}
+
+impl FeedDiff{
+
+    pub fn new()->Self{
+        Self{
+            seen:HashMap::new(),
+            backend:None,
+        }
+    }
+
+    ///
+    /// Diff engine keeping the hashes in `backend` as well, under `prefix`
+    /// followed by the item key, so every instance sharing the backend and
+    /// prefix reports an item once
+    ///
+    pub fn with_backend(backend:Arc<dyn CacheBackend>,prefix:&str)->Self{
+        Self{
+            seen:HashMap::new(),
+            backend:Some((backend,prefix.to_string())),
+        }
+    }
+
+    ///
+    /// Compare items against everything seen so far and remember them,
+    /// unchanged items produce no event
+    ///
+    /// When the backend fails, items are compared with what this instance
+    /// has seen; [`FeedDiff::try_diff`] reports the failure instead.
+    ///
+    pub fn diff(&mut self,items:Vec<RssItem>)->Vec<FeedEvent>{
+        self.compare(items,false).unwrap_or_default()
+    }
+
+    ///
+    /// [`FeedDiff::diff`] that stops at the first backend error
+    ///
+    pub fn try_diff(&mut self,items:Vec<RssItem>)->Result<Vec<FeedEvent>,std::io::Error>{
+        self.compare(items,true)
+    }
+
+    fn compare(&mut self,items:Vec<RssItem>,strict:bool)->Result<Vec<FeedEvent>,std::io::Error>{
+        let mut events = Vec::new();
+        for item in items.into_iter() {
+            let hash = content_hash(&item);
+            let local = self.seen.insert(item.key().to_string(),hash);
+            let previous = match &self.backend {
+                None => local,
+                Some((backend,prefix)) => match remember(backend.as_ref(),&format!("{}{}",prefix,item.key()),hash) {
+                    Ok(shared) => shared,
+                    Err(e) if strict => return Err(e),
+                    Err(_) => local,
+                },
+            };
+            match previous {
+                None => events.push(FeedEvent::New(item)),
+                Some(old) if old != hash => events.push(FeedEvent::Updated(item)),
+                Some(_) => (),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Item was seen by a previous call to [`FeedDiff::diff`], here or
+    /// through the backend
+    pub fn contains(&self,key:&str)->bool{
+        self.seen.contains_key(key) || self.backend.as_ref().is_some_and(|(backend,prefix)| {
+            matches!(backend.get(&format!("{}{}",prefix,key)),Ok(Some(_)))
+        })
+    }
+
+    pub fn len(&self)->usize{
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.seen.is_empty()
+    }
+
+    /// Forget what this instance has seen, the backend keeps its entries
+    pub fn clear(&mut self){
+        self.seen.clear();
+    }
+}
+
+/// Store `hash` under `key`, returning the hash stored before
+fn remember(backend:&dyn CacheBackend,key:&str,hash:u64)->Result<Option<u64>,std::io::Error>{
+    let previous = backend.get(key)?.and_then(|previous| previous.parse().ok());
+    if previous != Some(hash) {
+        backend.set(key,&hash.to_string())?;
+    }
+    Ok(previous)
+}
+
+///
+/// Changes between two snapshots of a feed
+///
+/// ```
+/// use future_rss::{FeedDelta,RssItem};
+///
+/// fn main(){
+///     let item = |guid:&str,title:&str| RssItem{ guid:guid.to_string(), title:title.to_string(), ..RssItem::default() };
+///     let before = vec![item("a","one"),item("b","two")];
+///     let after = vec![item("b","two!"),item("c","three")];
+///     let delta = FeedDelta::between(&before,&after);
+///     assert_eq!(delta.removed,["a"]);
+///     assert_eq!(
+///         delta.to_json(),
+///         r#"{"added":[{"title":"three","link":"","author":"","description":"","guid":"c","publish":"","updated":""}],"updated":[{"title":"two!","link":"","author":"","description":"","guid":"b","publish":"","updated":""}],"removed":["a"]}"#
+///     );
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct FeedDelta{
+    /// Items whose key the previous snapshot lacks
+    pub added:Vec<RssItem>,
+    /// Items whose content changed, compared like [`FeedDiff`] does
+    pub updated:Vec<RssItem>,
+    /// Keys of the items that disappeared, see [`RssItem::key`]
+    pub removed:Vec<String>,
+}
+
+impl FeedDelta{
+
+    /// Delta turning `previous` into `current`, in the order of `current`
+    pub fn between(previous:&[RssItem],current:&[RssItem])->Self{
+        let mut diff = FeedDiff::new();
+        diff.diff(previous.to_vec());
+        let kept = current.iter().map(RssItem::key).collect::<HashSet<_>>();
+        let mut delta = FeedDelta{
+            removed:previous.iter()
+                .map(RssItem::key)
+                .filter(|key| !kept.contains(key))
+                .map(String::from)
+                .collect(),
+            ..FeedDelta::default()
+        };
+        for event in diff.diff(current.to_vec()) {
+            match event {
+                FeedEvent::New(item) => delta.added.push(item),
+                FeedEvent::Updated(item) => delta.updated.push(item),
+            }
+        }
+        delta
+    }
+
+    /// Nothing changed
+    pub fn is_empty(&self)->bool{
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    /// `{"added":[...],"updated":[...],"removed":["key",...]}`
+    pub fn to_json(&self)->String{
+        json::object!{
+            "added": self.added.iter().map(RssItem::json).collect::<Vec<_>>(),
+            "updated": self.updated.iter().map(RssItem::json).collect::<Vec<_>>(),
+            "removed": self.removed.clone(),
+        }.dump()
+    }
+}
+
+///
+/// 64-bit FNV-1a, the same on every platform and toolchain, unlike
+/// `DefaultHasher`: its values are stored in shared backends and compared
+/// by other processes and later builds, so the algorithm must never change
+///
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher{
+
+    pub(crate) fn new()->Self{
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self,bytes:&[u8]){
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// `text` followed by a byte no utf8 text holds, so fields can't run into each other
+    pub(crate) fn field(&mut self,text:&str){
+        self.write(text.as_bytes());
+        self.write(&[0xff]);
+    }
+
+    pub(crate) fn finish(&self)->u64{
+        self.0
+    }
+}
+
+///
+/// The feed's own `updated` date when the item has one, so only edits the
+/// publisher announces count; the item's text, full body and enclosures
+/// otherwise. Stable across builds, see [`StableHasher`]
+///
+pub(crate) fn content_hash(item:&RssItem)->u64{
+    let mut hasher = StableHasher::new();
+    if !item.updated.trim().is_empty() {
+        match item.updated_at() {
+            Some(updated) => hasher.write(&updated.unix().to_le_bytes()),
+            None => hasher.field(item.updated.trim()),
+        }
+        return hasher.finish();
+    }
+    hasher.field(&item.title);
+    hasher.field(&item.link);
+    hasher.field(&item.author);
+    hasher.field(&item.description);
+    hasher.field(&item.content);
+    hasher.field(&item.publish);
+    for enclosure in &item.enclosures {
+        hasher.field(&enclosure.url);
+        hasher.field(&enclosure.length.map(|length| length.to_string()).unwrap_or_default());
+        hasher.field(&enclosure.media_type.as_ref().map(ToString::to_string).unwrap_or_default());
+    }
+    hasher.finish()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::{FeedDiff,FeedDelta,FeedEvent,RssItem,CacheBackend,MemoryCache};
+
+    fn item(guid:&str,title:&str)->RssItem{
+        RssItem{
+            guid:guid.to_string(),
+            title:title.to_string(),
+            ..RssItem::default()
+        }
+    }
+
+    #[test]
+    fn future_rss_diff_stable_hash(){
+        let mut hasher = super::StableHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(),0xaf63_dc4c_8601_ec8c);
+        assert_eq!(super::content_hash(&item("1","Hey!")),super::content_hash(&item("2","Hey!")));
+        // values stored by earlier builds stay comparable
+        assert_eq!(super::content_hash(&RssItem::default()),0x7085_37aa_d943_f66f);
+    }
+
+    #[test]
+    fn future_rss_diff_updated(){
+        let mut diff = FeedDiff::new();
+        assert_eq!(diff.diff(vec![item("a","one"),item("b","two")]).len(),2);
+        assert!(diff.diff(vec![item("a","one"),item("b","two")]).is_empty());
+
+        let events = diff.diff(vec![item("a","one"),item("b","two!"),item("c","three")]);
+        assert_eq!(events,vec![
+            FeedEvent::Updated(item("b","two!")),
+            FeedEvent::New(item("c","three")),
+        ]);
+        assert_eq!(diff.len(),3);
+
+        // the full body and the attachments count as content too
+        let body = |content:&str,url:&str| RssItem{
+            content:content.to_string(),
+            enclosures:vec![crate::Enclosure::new(Some(url),Some("1"),Some("audio/mpeg"))],
+            ..item("a","one")
+        };
+        assert_eq!(diff.diff(vec![body("<p>1</p>","1.mp3")]).len(),1);
+        assert!(diff.diff(vec![body("<p>1</p>","1.mp3")]).is_empty());
+        assert_eq!(diff.diff(vec![body("<p>2</p>","1.mp3")]),vec![FeedEvent::Updated(body("<p>2</p>","1.mp3"))]);
+        assert_eq!(diff.diff(vec![body("<p>2</p>","2.mp3")]).len(),1);
+    }
+
+    #[test]
+    fn future_rss_diff_updated_date(){
+        let edited = |title:&str,updated:&str| RssItem{ updated:updated.to_string(), ..item("a",title) };
+        let mut diff = FeedDiff::new();
+        assert_eq!(diff.diff(vec![edited("one","2020-05-28T15:00:00Z")]).len(),1);
+        // content changes the publisher did not announce are ignored
+        assert!(diff.diff(vec![edited("one!","2020-05-28T15:00:00Z")]).is_empty());
+        assert!(diff.diff(vec![edited("one!","Thu, 28 May 2020 17:00:00 +0200")]).is_empty());
+
+        let events = diff.diff(vec![edited("one!","2020-05-29T08:00:00Z")]);
+        assert_eq!(events,vec![FeedEvent::Updated(edited("one!","2020-05-29T08:00:00Z"))]);
+    }
+
+    /// Backend that is always unreachable
+    #[derive(Debug)]
+    struct Unreachable;
+
+    impl CacheBackend for Unreachable{
+        fn get(&self,_:&str)->Result<Option<String>,std::io::Error>{
+            Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused,"unreachable"))
+        }
+        fn set(&self,key:&str,_:&str)->Result<(),std::io::Error>{
+            self.get(key).map(|_| ())
+        }
+        fn remove(&self,key:&str)->Result<(),std::io::Error>{
+            self.get(key).map(|_| ())
+        }
+    }
+
+    #[test]
+    fn future_rss_diff_backend(){
+        let cache = Arc::new(MemoryCache::new());
+        let mut first = FeedDiff::with_backend(cache.clone(),"a:");
+        let mut second = FeedDiff::with_backend(cache.clone(),"a:");
+        assert_eq!(first.diff(vec![item("a","one")]).len(),1);
+        assert!(second.try_diff(vec![item("a","one")]).unwrap().is_empty());
+        assert_eq!(second.try_diff(vec![item("a","one!")]).unwrap(),vec![FeedEvent::Updated(item("a","one!"))]);
+        assert!(first.diff(vec![item("a","one!")]).is_empty());
+        assert!(FeedDiff::with_backend(cache.clone(),"a:").contains("a"));
+        assert!(!FeedDiff::with_backend(cache,"b:").contains("a"));
+
+        let mut offline = FeedDiff::with_backend(Arc::new(Unreachable),"a:");
+        assert!(offline.try_diff(vec![item("a","one")]).is_err());
+        assert!(offline.diff(vec![item("a","one")]).is_empty());
+    }
+
+    #[test]
+    fn future_rss_diff_delta(){
+        let before = vec![item("a","one"),item("b","two"),item("c","three")];
+        assert!(FeedDelta::between(&before,&before).is_empty());
+        assert_eq!(FeedDelta::between(&before,&before).to_json(),r#"{"added":[],"updated":[],"removed":[]}"#);
+
+        let after = vec![item("d","four"),item("c","three"),item("a","one!")];
+        let delta = FeedDelta::between(&before,&after);
+        assert_eq!(delta.added,[item("d","four")]);
+        assert_eq!(delta.updated,[item("a","one!")]);
+        assert_eq!(delta.removed,["b"]);
+
+        let delta = FeedDelta::between(&[],&after);
+        assert_eq!((delta.added.len(),delta.removed.len()),(3,0));
+
+        let mut parser = crate::RssParser::new();
+        parser.set_xml(String::from("<rss><item><guid>a</guid><title>one</title></item><item><guid>e</guid></item></rss>"));
+        let delta = json::parse(&parser.parse_json_delta(&before).unwrap()).unwrap();
+        assert_eq!(delta["added"][0]["guid"],"e");
+        assert_eq!(delta["removed"].len(),2);
+    }
+}
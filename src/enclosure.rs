@@ -0,0 +1,232 @@
+//!
+//! Enclosures
+//!
+//! `<enclosure url length type/>` attaches a file to an item; [`Enclosure`]
+//! reads its size as a number and its type as a checked [`MediaType`], and
+//! lists what is missing or wrong so download managers know what to trust.
+//! Atom's `<link rel="enclosure"/>` is read the same way.
+//!
+
+use quick_xml::events::BytesStart;
+use crate::RssParser;
+use crate::extension::scan_items;
+
+/// &lt;enclosure/&gt;
+pub static RSS_DEFAULT_ENCLOSURE_TAG:&str = "enclosure";
+
+/// Top-level types registered with IANA
+static MEDIA_TOP_LEVEL_TYPES:[&str;10] = ["application","audio","example","font","image","message","model","multipart","text","video"];
+
+///
+/// MIME type such as `audio/mpeg`, lowercase and without parameters
+///
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct MediaType{
+    pub top_level:String,
+    pub subtype:String,
+}
+
+impl MediaType{
+
+    ///
+    /// `type/subtype[; parameters]` with a registered top-level type and a
+    /// subtype made of the characters RFC 6838 allows, `None` otherwise
+    ///
+    pub fn parse(text:&str)->Option<Self>{
+        let essence = text.split(';').next()?.trim().to_ascii_lowercase();
+        let (top_level,subtype) = essence.split_once('/')?;
+        let restricted = |name:&str| {
+            name.len() <= 127
+                && name.starts_with(|c:char| c.is_ascii_alphanumeric())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+        };
+        if !MEDIA_TOP_LEVEL_TYPES.contains(&top_level) || !restricted(subtype) {
+            return None;
+        }
+        Some(Self{ top_level:top_level.to_string(), subtype:subtype.to_string() })
+    }
+}
+
+impl std::fmt::Display for MediaType{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        write!(f,"{}/{}",self.top_level,self.subtype)
+    }
+}
+
+///
+/// Problem with the metadata of an enclosure
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum EnclosureIssue{
+    MissingUrl,
+    MissingLength,
+    /// Length that is no byte count, as found
+    InvalidLength(String),
+    MissingType,
+    /// Type that is no valid MIME type, as found
+    InvalidType(String),
+}
+
+///
+/// File attached to an item
+///
+/// ```
+/// use future_rss::{RssParser,EnclosureIssue};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0">
+///             <item>
+///                 <title>Episode 1</title>
+///                 <enclosure url="https://examples.com/1.mp3" length="24986239" type="audio/mpeg"/>
+///             </item>
+///             <item>
+///                 <title>Episode 2</title>
+///                 <enclosure url="https://examples.com/2.mp3" length="about 20MB" type="mp3"/>
+///             </item>
+///         </rss>
+///         "#
+///     ))?;
+///     let enclosures = parser.parse_enclosures()?;
+///     assert_eq!(enclosures[0][0].length,Some(24986239));
+///     assert_eq!(enclosures[0][0].media_type.as_ref().unwrap().to_string(),"audio/mpeg");
+///     assert!(enclosures[0][0].is_valid());
+///     assert_eq!(enclosures[1][0].issues,[
+///         EnclosureIssue::InvalidLength(String::from("about 20MB")),
+///         EnclosureIssue::InvalidType(String::from("mp3")),
+///     ]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct Enclosure{
+    pub url:String,
+    /// Size in bytes, 0 when the publisher does not know it
+    pub length:Option<u64>,
+    pub media_type:Option<MediaType>,
+    /// Everything missing or wrong, empty for a valid enclosure
+    pub issues:Vec<EnclosureIssue>,
+}
+
+impl Enclosure{
+
+    /// Enclosure of the raw attribute values, checking each of them
+    pub fn new(url:Option<&str>,length:Option<&str>,media_type:Option<&str>)->Self{
+        let mut enclosure = Enclosure::default();
+        match url.map(str::trim).filter(|url| !url.is_empty()) {
+            Some(url) => enclosure.url = url.to_string(),
+            None => enclosure.issues.push(EnclosureIssue::MissingUrl),
+        }
+        match length.map(str::trim).filter(|length| !length.is_empty()) {
+            Some(length) => match length.parse::<u64>() {
+                Ok(length) => enclosure.length = Some(length),
+                Err(_) => enclosure.issues.push(EnclosureIssue::InvalidLength(length.to_string())),
+            },
+            None => enclosure.issues.push(EnclosureIssue::MissingLength),
+        }
+        match media_type.map(str::trim).filter(|media_type| !media_type.is_empty()) {
+            Some(text) => match MediaType::parse(text) {
+                Some(media_type) => enclosure.media_type = Some(media_type),
+                None => enclosure.issues.push(EnclosureIssue::InvalidType(text.to_string())),
+            },
+            None => enclosure.issues.push(EnclosureIssue::MissingType),
+        }
+        enclosure
+    }
+
+    pub fn is_valid(&self)->bool{
+        self.issues.is_empty()
+    }
+}
+
+///
+/// Enclosure of an `<enclosure/>` or `<link rel="enclosure"/>` start tag,
+/// `None` for any other element
+///
+pub(crate) fn from_element(e:&BytesStart)->Option<Enclosure>{
+    let name = e.name();
+    let link = name.eq_ignore_ascii_case(b"link");
+    if !link && !name.eq_ignore_ascii_case(RSS_DEFAULT_ENCLOSURE_TAG.as_bytes()) {
+        return None;
+    }
+    let (mut url,mut length,mut media_type,mut rel) = (None,None,None,None);
+    for attribute in e.attributes().flatten() {
+        let value = attribute.unescaped_value()
+            .map(|value| String::from_utf8_lossy(&value).into_owned())
+            .ok();
+        match attribute.key {
+            b"url" if !link => url = value,
+            b"href" if link => url = value,
+            b"length" => length = value,
+            b"type" => media_type = value,
+            b"rel" => rel = value,
+            _ => (),
+        }
+    }
+    if link && rel.as_deref() != Some("enclosure") {
+        return None;
+    }
+    Some(Enclosure::new(url.as_deref(),length.as_deref(),media_type.as_deref()))
+}
+
+///
+/// Enclosures of every item of `xml`
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<Vec<Enclosure>>,std::io::Error>{
+    scan_items(parser,xml,"RssParser::parse_enclosures",|enclosures:&mut Vec<Enclosure>,element| {
+        let enclosure = match element.name.as_str() {
+            name if name == RSS_DEFAULT_ENCLOSURE_TAG => {
+                Enclosure::new(element.attribute("url"),element.attribute("length"),element.attribute("type"))
+            }
+            "link" if element.attribute("rel") == Some("enclosure") => {
+                Enclosure::new(element.attribute("href"),element.attribute("length"),element.attribute("type"))
+            }
+            _ => return,
+        };
+        enclosures.push(enclosure);
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,Enclosure,EnclosureIssue,MediaType};
+
+    #[test]
+    fn future_rss_media_type(){
+        let media_type = MediaType::parse(" Video/MP4; codecs=\"avc1\"").unwrap();
+        assert_eq!((media_type.top_level.as_str(),media_type.subtype.as_str()),("video","mp4"));
+        assert_eq!(MediaType::parse("application/vnd.apple.mpegurl").unwrap().to_string(),"application/vnd.apple.mpegurl");
+        for broken in ["","mp3","audio/","audio/mpeg/x","podcast/mpeg","audio/-mpeg","audio/mp eg"] {
+            assert_eq!(MediaType::parse(broken),None,"{}",broken);
+        }
+    }
+
+    #[test]
+    fn future_rss_enclosures(){
+        let mut parser = RssParser::atom();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+                <feed xmlns="http://www.w3.org/2005/Atom">
+                    <entry>
+                        <link href="https://examples.com/1"/>
+                        <link rel="enclosure" href="https://examples.com/1.ogg" length="0" type="audio/ogg"/>
+                        <link rel="enclosure" length="-1"/>
+                    </entry>
+                    <entry><title>No attachments</title></entry>
+                </feed>
+        "#));
+        let enclosures = parser.parse_enclosures().unwrap();
+        assert_eq!(enclosures.len(),2);
+        assert_eq!(enclosures[0][0].length,Some(0));
+        assert!(enclosures[0][0].is_valid());
+        assert_eq!(enclosures[0][1].issues,[
+            EnclosureIssue::MissingUrl,
+            EnclosureIssue::InvalidLength(String::from("-1")),
+            EnclosureIssue::MissingType,
+        ]);
+        assert!(enclosures[1].is_empty());
+        assert_eq!(Enclosure::new(Some("x"),None,None).issues,[EnclosureIssue::MissingLength,EnclosureIssue::MissingType]);
+    }
+}
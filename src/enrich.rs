@@ -0,0 +1,279 @@
+//!
+//! OpenGraph Enrichment
+//!
+//! Minimal feeds often ship a bare link; [`PageMeta`] reads the OpenGraph,
+//! Twitter card and plain `<meta>` tags of the linked page so the missing
+//! title, description, author and picture of an item can be filled in.
+//!
+
+use std::io::Read;
+use crate::{RssItem,HttpFetcher,Enclosure};
+
+/// Bytes of a page read at most, the meta tags live in its &lt;head&gt;
+pub static ENRICH_DEFAULT_MAX_BYTES:u64 = 256 * 1024;
+
+///
+/// Metadata announced by a html page
+///
+/// ```
+/// use future_rss::{PageMeta,RssItem};
+///
+/// fn main(){
+///     let meta = PageMeta::from_html(r#"<head>
+///         <meta property="og:title" content="Hey!">
+///         <meta name="description" content="hello.world!">
+///     </head>"#);
+///     let mut item = RssItem{ link:String::from("https://examples.com/1"), ..RssItem::default() };
+///     assert!(meta.fill(&mut item));
+///     assert_eq!(item.title,"Hey!");
+///     assert_eq!(item.description,"hello.world!");
+/// }
+/// ```
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct PageMeta{
+    pub title:String,
+    pub description:String,
+    pub image:String,
+    pub author:String,
+}
+
+impl PageMeta{
+
+    ///
+    /// Read the meta tags of a html document, OpenGraph values win over
+    /// Twitter cards, which win over plain meta tags and &lt;title&gt;
+    ///
+    pub fn from_html(html:&str)->Self{
+        let head = match find_ignore_case(html,"</head") {
+            Some(end) => &html[..end],
+            None => html,
+        };
+        let mut meta = PageMeta::default();
+        let mut ranks = [usize::MAX;4];
+        let mut rest = head;
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start + 1..];
+            let end = rest.find('>').unwrap_or(rest.len());
+            let tag = &rest[..end];
+            if starts_with_ignore_case(tag,"title") && ranks[0] == usize::MAX {
+                if let Some(close) = find_ignore_case(&rest[end..],"</title") {
+                    meta.title = unescape(rest[end..][1..close].trim());
+                }
+            }
+            if starts_with_ignore_case(tag,"meta") {
+                let attrs = attributes(&tag[4..]);
+                let key = attrs.iter()
+                    .find(|(name,_)| name == "property" || name == "name")
+                    .map(|(_,value)| value.to_ascii_lowercase());
+                let content = attrs.iter().find(|(name,_)| name == "content").map(|(_,value)| value);
+                if let (Some(key),Some(content)) = (key,content) {
+                    meta.set(&key,content,&mut ranks);
+                }
+            }
+            rest = &rest[end..];
+        }
+        meta
+    }
+
+    ///
+    /// Download `url` and read its meta tags, the body is decoded from the
+    /// announced charset or `charset`
+    ///
+    pub fn fetch(fetcher:&dyn HttpFetcher,url:&str,charset:&str)->Result<Self,std::io::Error>{
+        let response = fetcher.fetch(url,&[("Accept","text/html")])?.error_for_status()?;
+        let encoding = crate::fetcher::encoding(&response.charset().unwrap_or_else(|| charset.to_string()));
+        let mut body = Vec::new();
+        response.body.take(ENRICH_DEFAULT_MAX_BYTES).read_to_end(&mut body)?;
+        Ok(Self::from_html(&encoding.decode(&body).0))
+    }
+
+    ///
+    /// Copy values into the empty fields of `item`, true when one was filled;
+    /// the image becomes the enclosure of an item without any, its length
+    /// and type unknown
+    ///
+    pub fn fill(&self,item:&mut RssItem)->bool{
+        let mut filled = false;
+        for (field,value) in [
+            (&mut item.title,&self.title),
+            (&mut item.description,&self.description),
+            (&mut item.author,&self.author),
+        ] {
+            if field.trim().is_empty() && !value.is_empty() {
+                *field = value.clone();
+                filled = true;
+            }
+        }
+        if item.enclosures.is_empty() && !self.image.is_empty() {
+            item.enclosures.push(Enclosure::new(Some(&self.image),None,None));
+            filled = true;
+        }
+        filled
+    }
+
+    /// Keep `content` unless a better ranked key already set the field
+    fn set(&mut self,key:&str,content:&str,ranks:&mut [usize;4]){
+        let (field,rank) = match key {
+            "og:title" => (0,0),
+            "twitter:title" => (0,1),
+            "og:description" => (1,0),
+            "twitter:description" => (1,1),
+            "description" => (1,2),
+            "og:image" => (2,0),
+            "twitter:image" => (2,1),
+            "article:author" => (3,0),
+            "twitter:creator" => (3,1),
+            "author" => (3,2),
+            _ => return,
+        };
+        if rank >= ranks[field] || content.trim().is_empty() {
+            return;
+        }
+        ranks[field] = rank;
+        let value = unescape(content.trim());
+        match field {
+            0 => self.title = value,
+            1 => self.description = value,
+            2 => self.image = value,
+            _ => self.author = value,
+        }
+    }
+}
+
+/// Lowercase attribute names and their values, quoted or not
+fn attributes(tag:&str)->Vec<(String,&str)>{
+    let mut attrs = Vec::new();
+    let mut rest = tag.trim_end_matches('/');
+    loop {
+        rest = rest.trim_start();
+        let name_end = rest.find(|c:char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+        if name_end == 0 {
+            return attrs;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                match value.chars().next() {
+                    Some(quote) if quote == '"' || quote == '\'' => {
+                        let end = value[1..].find(quote).map(|i| i + 1).unwrap_or(value.len());
+                        rest = value.get(end + 1..).unwrap_or("");
+                        &value[1..end]
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        rest = &value[end..];
+                        &value[..end]
+                    }
+                }
+            }
+            None => "",
+        };
+        attrs.push((name,value));
+    }
+}
+
+/// Resolve the entities html pages use in meta values
+fn unescape(value:&str)->String{
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            entity => entity.strip_prefix('#')
+                .and_then(|n| match n.strip_prefix(['x','X']) {
+                    Some(hex) => u32::from_str_radix(hex,16).ok(),
+                    None => n.parse().ok(),
+                })
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn starts_with_ignore_case(value:&str,prefix:&str)->bool{
+    value.len() >= prefix.len()
+        && value.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+        && value[prefix.len()..].chars().next().map_or(true,|c| c.is_whitespace() || c == '/')
+}
+
+fn find_ignore_case(value:&str,needle:&str)->Option<usize>{
+    value.as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{PageMeta,RssItem,EnclosureIssue};
+
+    #[test]
+    fn future_rss_page_meta(){
+        let meta = PageMeta::from_html(r#"<!DOCTYPE html>
+            <html><HEAD>
+                <title>Plain &amp; simple</title>
+                <meta name="twitter:title" content="Card title">
+                <meta property='og:title' content="Tom &amp; Jerry &#8212; &#x41;"/>
+                <meta name=description content=short>
+                <meta content="https://examples.com/a.png" property="og:image">
+                <meta name="author" content="MeteorCat">
+                <meta name="viewport" content="width=device-width">
+            </head>
+            <body><meta property="og:description" content="ignored"></body></html>"#);
+        assert_eq!(meta.title,"Tom & Jerry \u{2014} A");
+        assert_eq!(meta.description,"short");
+        assert_eq!(meta.image,"https://examples.com/a.png");
+        assert_eq!(meta.author,"MeteorCat");
+
+        assert_eq!(PageMeta::from_html("<title>Only &bogus; title</title>").title,"Only &bogus; title");
+        assert_eq!(PageMeta::from_html("<titles>no</titles>"),PageMeta::default());
+
+        let mut item = RssItem{ title:String::from("Kept"), ..RssItem::default() };
+        assert!(meta.fill(&mut item));
+        assert_eq!(item.title,"Kept");
+        assert_eq!(item.author,"MeteorCat");
+        assert_eq!(item.enclosures[0].url,"https://examples.com/a.png");
+        assert_eq!(item.enclosures[0].issues,[EnclosureIssue::MissingLength,EnclosureIssue::MissingType]);
+        assert!(!meta.fill(&mut item));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_page_meta_fetch(){
+        let server = crate::mock::serve(r#"<head><meta property="og:title" content="Hey!"></head>"#);
+        let client = reqwest::blocking::Client::new();
+        let meta = PageMeta::fetch(&client,&server.url("/page"),"utf8").unwrap();
+        assert_eq!(meta.title,"Hey!");
+    }
+}
@@ -0,0 +1,125 @@
+//!
+//! Extension Elements
+//!
+//! Elements beyond the six item fields, iTunes tags or categories, are read
+//! in a pass of their own over the document: every element inside an item
+//! is handed over with its attributes and text, in item order. Items the
+//! item hooks veto are left out, like in the main pass.
+//!
+
+use quick_xml::Reader;
+use quick_xml::events::{Event,BytesStart};
+use crate::RssParser;
+
+///
+/// Element found inside an item, names and attribute keys are lowercase
+///
+pub(crate) struct Element{
+    pub(crate) name:String,
+    /// Names of the enclosing elements below the item, outermost first
+    pub(crate) parents:Vec<String>,
+    pub(crate) attributes:Vec<(String,String)>,
+    pub(crate) text:String,
+}
+
+impl Element{
+
+    pub(crate) fn attribute(&self,key:&str)->Option<&str>{
+        self.attributes.iter()
+            .find(|(name,_)| name == key)
+            .map(|(_,value)| value.as_str())
+    }
+}
+
+///
+/// One `T` per item [`RssParser::parse_vec`] keeps from `xml`, items are
+/// found with the parser's node tag and `read` sees each of their elements
+/// once it is closed, children before their parent
+///
+pub(crate) fn scan_items<T,F>(parser:&RssParser,xml:&str,context:&str,read:F)->Result<Vec<T>,std::io::Error>
+    where T:Default,F:FnMut(&mut T,&Element)
+{
+    let items = scan_document(parser,xml,context,read)?;
+    if parser.hooks.is_empty() {
+        return Ok(items);
+    }
+    // the hooks decide which items are left
+    let (_,kept) = parser.parse_indexed(xml)?;
+    Ok(kept_items(items,&kept))
+}
+
+///
+/// Entries of `items`, one per item of the document, at the document
+/// indices `kept`
+///
+pub(crate) fn kept_items<T:Default>(items:Vec<T>,kept:&[usize])->Vec<T>{
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    kept.iter()
+        .map(|&index| items.get_mut(index).and_then(Option::take).unwrap_or_default())
+        .collect()
+}
+
+fn scan_document<T,F>(parser:&RssParser,xml:&str,context:&str,mut read:F)->Result<Vec<T>,std::io::Error>
+    where T:Default,F:FnMut(&mut T,&Element)
+{
+    let error = |e:quick_xml::Error| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by {}: {:?}",context,e));
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    reader.expand_empty_elements(true);
+    let node = parser.node_tag.to_ascii_lowercase();
+
+    let mut items = Vec::new();
+    let mut current:Option<T> = None;
+    let mut open:Vec<Element> = Vec::new();
+    let mut buff = Vec::new();
+    loop {
+        buff.clear();
+        match reader.read_event(&mut buff).map_err(error)? {
+            Event::Start(ref e) => {
+                let name = String::from_utf8_lossy(e.name()).to_ascii_lowercase();
+                if name == node {
+                    // an unterminated item is complete once the next one starts
+                    items.extend(current.replace(T::default()));
+                    open.clear();
+                }else if current.is_some() {
+                    let parents = open.iter().map(|parent| parent.name.clone()).collect();
+                    open.push(Element{ name, parents, attributes:attributes(&reader,e).map_err(error)?, text:String::new() });
+                }
+            }
+            Event::Text(ref e) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&e.unescape_and_decode(&reader).map_err(error)?);
+                }
+            }
+            Event::CData(ref e) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(e.escaped()));
+                }
+            }
+            Event::End(ref e) => {
+                if e.name().eq_ignore_ascii_case(node.as_bytes()) {
+                    items.extend(current.take());
+                    open.clear();
+                }else if let (Some(item),Some(element)) = (current.as_mut(),open.pop()) {
+                    read(item,&element);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    items.extend(current);
+    Ok(items)
+}
+
+fn attributes(reader:&Reader<&[u8]>,start:&BytesStart)->Result<Vec<(String,String)>,quick_xml::Error>{
+    start.attributes()
+        .map(|attribute| {
+            let attribute = attribute?;
+            Ok((
+                String::from_utf8_lossy(attribute.key).to_ascii_lowercase(),
+                attribute.unescape_and_decode_value(reader)?,
+            ))
+        })
+        .collect()
+}
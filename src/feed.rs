@@ -0,0 +1,355 @@
+//!
+//! Unified Feed Model
+//!
+//! RSS, Atom and JSON Feed documents all read into one [`Feed`] of
+//! [`Entry`]s, so application code never branches on the format; what only
+//! one format knows stays reachable through [`Entry::extensions`].
+//!
+
+use std::collections::BTreeMap;
+use json::JsonValue;
+use crate::{RssParser,RssItem,RawRssItem,Channel,Category,Enclosure,MediaExtension};
+use crate::extension::{scan_items,kept_items};
+
+///
+/// Format a document was written in
+///
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum FeedFormat{
+    /// RSS 0.9x, 2.0 and RSS 1.0 (RDF)
+    Rss,
+    Atom,
+    /// <https://jsonfeed.org>
+    JsonFeed,
+}
+
+impl FeedFormat{
+
+    /// Format of `text` from its root element, or its leading `{` for JSON Feed
+    pub fn detect(text:&str)->Option<Self>{
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with('{') {
+            return Some(FeedFormat::JsonFeed);
+        }
+        match root_element(text)?.as_str() {
+            "rss" | "rdf:rdf" => Some(FeedFormat::Rss),
+            "feed" => Some(FeedFormat::Atom),
+            _ => None,
+        }
+    }
+}
+
+/// Lowercase name of the first element of `xml`, after prolog and comments
+pub(crate) fn root_element(xml:&str)->Option<String>{
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if !rest.starts_with(|c:char| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        let end = rest.find(|c:char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(rest.len());
+        return Some(rest[..end].to_ascii_lowercase());
+    }
+    None
+}
+
+///
+/// Entry of a feed in any format
+///
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct Entry{
+    /// iTunes fields sit in `RssItem::podcast` with the `podcast` feature
+    pub item:RssItem,
+    pub media:MediaExtension,
+    /// Values only one format knows, by name: namespaced elements such as
+    /// `dc:subject` and the values of the parser's selectors for xml,
+    /// `_`-prefixed keys as json text for JSON Feed
+    pub extensions:BTreeMap<String,String>,
+}
+
+///
+/// Feed in any format
+///
+/// ```
+/// use future_rss::{Feed,FeedFormat};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let atom = Feed::parse(r#"<?xml version="1.0" encoding="utf-8"?>
+///         <feed xmlns="http://www.w3.org/2005/Atom">
+///             <title>Examples</title>
+///             <entry><title>Hey!</title><link href="https://examples.com/1"/></entry>
+///         </feed>"#)?;
+///     let json = Feed::parse(r#"{
+///         "version": "https://jsonfeed.org/version/1.1",
+///         "title": "Examples",
+///         "items": [{ "id": "1", "title": "Hey!", "url": "https://examples.com/1" }]
+///     }"#)?;
+///     assert_eq!(atom.raw_format,FeedFormat::Atom);
+///     assert_eq!(json.raw_format,FeedFormat::JsonFeed);
+///     assert_eq!(atom.entries[0].item.link,json.entries[0].item.link);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub struct Feed{
+    pub raw_format:FeedFormat,
+    pub channel:Channel,
+    pub entries:Vec<Entry>,
+}
+
+impl Feed{
+
+    ///
+    /// Read a document in any of the [`FeedFormat`]s, `InvalidData` when
+    /// the format is not recognised
+    ///
+    pub fn parse(text:&str)->Result<Self,std::io::Error>{
+        match FeedFormat::detect(text) {
+            Some(FeedFormat::Rss) if root_element(text).as_deref() == Some("rdf:rdf") => parse_xml(&RssParser::rdf(),text,FeedFormat::Rss),
+            Some(FeedFormat::Rss) => parse_xml(&RssParser::new(),text,FeedFormat::Rss),
+            Some(FeedFormat::Atom) => parse_xml(&RssParser::atom(),text,FeedFormat::Atom),
+            Some(FeedFormat::JsonFeed) => parse_json_feed(text),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Failed by Feed::parse: neither RSS, Atom nor JSON Feed"
+            )),
+        }
+    }
+
+    ///
+    /// [`Feed::parse`] with the tags and selectors of `parser` for xml
+    /// documents, selected values land in [`Entry::extensions`]
+    ///
+    pub fn parse_using(parser:&RssParser,text:&str)->Result<Self,std::io::Error>{
+        match FeedFormat::detect(text) {
+            Some(FeedFormat::JsonFeed) => parse_json_feed(text),
+            Some(format) => parse_xml(parser,text,format),
+            None => Self::parse(text),
+        }
+    }
+
+    /// Items of the entries, dropping everything else
+    pub fn into_items(self)->Vec<RssItem>{
+        self.entries.into_iter().map(|entry| entry.item).collect()
+    }
+}
+
+///
+/// Values the tag parser cannot capture: Atom links live in attributes
+/// and Atom entries may carry only &lt;content&gt;
+///
+#[derive(Default)]
+struct XmlExtras{
+    link:Option<String>,
+    content:Option<String>,
+    extensions:BTreeMap<String,String>,
+}
+
+fn parse_xml(parser:&RssParser,xml:&str,format:FeedFormat)->Result<Feed,std::io::Error>{
+    // the hooks run once, the other passes see every item and are lined up with the kept ones
+    let (items,kept) = parser.parse_indexed(xml)?;
+    let every = parser.without_hooks();
+    let mut media = kept_items(crate::media::parse_items(&every,xml)?,&kept).into_iter();
+    let selected = if parser.selectors.is_empty() { Vec::new() }else { kept_items(crate::select::parse_items(&every,xml)?,&kept) };
+    let mut selected = selected.into_iter();
+    let extras = scan_items(&every,xml,"Feed::parse",|extras:&mut XmlExtras,element| {
+        match element.name.as_str() {
+            "link" if extras.link.is_none() && matches!(element.attribute("rel"),None | Some("alternate")) => {
+                extras.link = element.attribute("href").map(String::from);
+            }
+            "content" if extras.content.is_none() => extras.content = Some(element.text.clone()),
+            name if name.contains(':') && !element.text.trim().is_empty() => {
+                extras.extensions.entry(name.to_string()).or_insert_with(|| element.text.trim().to_string());
+            }
+            _ => (),
+        }
+    })?;
+    let mut extras = kept_items(extras,&kept).into_iter();
+
+    let entries = items.into_iter().map(RawRssItem::into_owned).map(|item| {
+        let mut item = item?;
+        let mut extras = extras.next().unwrap_or_default();
+        extras.extensions.extend(selected.next().unwrap_or_default());
+        if item.link.is_empty() {
+            item.link = extras.link.unwrap_or_default();
+        }
+        if item.description.is_empty() {
+            item.description = extras.content.unwrap_or_default();
+        }
+        Ok(Entry{
+            item,
+            media:media.next().unwrap_or_default(),
+            extensions:extras.extensions,
+        })
+    }).collect::<Result<_,std::io::Error>>()?;
+
+    Ok(Feed{
+        raw_format:format,
+        channel:crate::channel::parse_channel(xml)?,
+        entries,
+    })
+}
+
+fn parse_json_feed(text:&str)->Result<Feed,std::io::Error>{
+    let root = json::parse(text)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by Feed::parse: {}",e)))?;
+    if !root["items"].is_array() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,"Failed by Feed::parse: JSON Feed without items"));
+    }
+    let channel = Channel{
+        title:text_of(&root["title"]),
+        link:text_of(&root["home_page_url"]),
+        description:text_of(&root["description"]),
+        language:text_of(&root["language"]),
+        image:text_of(&root["icon"]),
+        ..Channel::default()
+    };
+    let entries = root["items"].members().map(|entry| {
+        let author = if entry["authors"].is_array() { &entry["authors"][0] } else { &entry["author"] };
+        let description = ["content_html","content_text","summary"].iter()
+            .map(|key| text_of(&entry[*key]))
+            .find(|text| !text.is_empty())
+            .unwrap_or_default();
+        let content = ["content_html","content_text"].iter()
+            .map(|key| text_of(&entry[*key]))
+            .find(|text| !text.is_empty())
+            .unwrap_or_else(|| description.clone());
+        let url = match text_of(&entry["url"]) {
+            url if url.is_empty() => text_of(&entry["external_url"]),
+            url => url,
+        };
+        let categories:Vec<Category> = entry["tags"].members().map(|tag| Category::new(&text_of(tag),None)).collect();
+        Entry{
+            item:RssItem{
+                title:text_of(&entry["title"]),
+                link:url,
+                author:text_of(&author["name"]),
+                content,
+                description,
+                guid:text_of(&entry["id"]),
+                publish:text_of(&entry["date_published"]),
+                updated:text_of(&entry["date_modified"]),
+                enclosures:entry["attachments"].members().map(|attachment| Enclosure::new(
+                    Some(&text_of(&attachment["url"])),
+                    Some(&text_of(&attachment["size_in_bytes"])),
+                    Some(&text_of(&attachment["mime_type"])),
+                )).collect(),
+                categories,
+                extensions:BTreeMap::new(),
+                #[cfg(feature = "podcast")]
+                podcast:None,
+            },
+            media:MediaExtension::default(),
+            extensions:entry.entries()
+                .filter(|(key,_)| key.starts_with('_'))
+                .map(|(key,value)| (key.to_string(),value.dump()))
+                .collect(),
+        }
+    }).collect();
+    Ok(Feed{ raw_format:FeedFormat::JsonFeed, channel, entries })
+}
+
+/// Strings as they are, numbers as their text, anything else empty
+pub(crate) fn text_of(value:&JsonValue)->String{
+    match value {
+        JsonValue::String(_) | JsonValue::Short(_) | JsonValue::Number(_) => value.to_string(),
+        _ => String::new(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{Feed,FeedFormat,RssParser};
+
+    #[test]
+    fn future_rss_feed_detect(){
+        assert_eq!(FeedFormat::detect("\u{feff}<?xml version=\"1.0\"?><!-- hi --><rss version=\"2.0\"/>"),Some(FeedFormat::Rss));
+        assert_eq!(FeedFormat::detect("<rdf:RDF xmlns:rdf=\"x\"></rdf:RDF>"),Some(FeedFormat::Rss));
+        assert_eq!(FeedFormat::detect("<?xml version=\"1.0\"?>\n<feed>"),Some(FeedFormat::Atom));
+        assert_eq!(FeedFormat::detect(" {\"items\":[]}"),Some(FeedFormat::JsonFeed));
+        assert_eq!(FeedFormat::detect("<html><body/></html>"),None);
+        assert!(Feed::parse("<html></html>").is_err());
+        assert!(Feed::parse("{\"title\":\"no items\"}").is_err());
+    }
+
+    #[test]
+    fn future_rss_feed_formats(){
+        let rss = Feed::parse(r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+                <channel>
+                    <title>Examples</title>
+                    <item>
+                        <title>Hey!</title><link>https://examples.com/1</link><guid>1</guid>
+                        <category>News</category><dc:subject>greetings</dc:subject>
+                        <itunes:duration>1:00</itunes:duration>
+                        <media:thumbnail url="https://examples.com/1.jpg"/>
+                    </item>
+                </channel>
+            </rss>"#).unwrap();
+        let atom = Feed::parse(r#"<?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+                <title>Examples</title>
+                <entry>
+                    <title>Hey!</title><id>1</id>
+                    <link rel="edit" href="https://examples.com/edit/1"/>
+                    <link href="https://examples.com/1"/>
+                    <author><name>MeteorCat</name></author>
+                    <content type="html">hello.world!</content>
+                    <category term="News"/>
+                    <updated>2020-05-28T15:00:00Z</updated>
+                </entry>
+            </feed>"#).unwrap();
+        let json = Feed::parse(r#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Examples",
+            "items": [{
+                "id": 1, "title": "Hey!", "url": "https://examples.com/1",
+                "authors": [{ "name": "MeteorCat" }], "content_text": "hello.world!",
+                "date_modified": "2020-05-28T15:00:00Z", "tags": ["News"],
+                "_examples": { "views": 3 }
+            }]
+        }"#).unwrap();
+
+        assert_eq!((rss.raw_format,atom.raw_format,json.raw_format),(FeedFormat::Rss,FeedFormat::Atom,FeedFormat::JsonFeed));
+        for feed in [&rss,&atom,&json] {
+            assert_eq!(feed.channel.title,"Examples");
+            assert_eq!(feed.entries.len(),1);
+            let entry = &feed.entries[0];
+            assert_eq!((entry.item.title.as_str(),entry.item.link.as_str(),entry.item.guid.as_str()),("Hey!","https://examples.com/1","1"));
+            assert_eq!(entry.item.categories[0].name,"News");
+        }
+        assert_eq!(atom.entries[0].item,json.entries[0].item);
+        assert_eq!(rss.entries[0].extensions["dc:subject"],"greetings");
+        #[cfg(feature = "podcast")]
+        assert_eq!(rss.entries[0].item.podcast.as_ref().unwrap().duration,Some(Duration::from_secs(60)));
+        assert_eq!(rss.entries[0].media.thumbnail().unwrap().url,"https://examples.com/1.jpg");
+        assert_eq!(json.entries[0].extensions["_examples"],r#"{"views":3}"#);
+        assert_eq!(json.into_items()[0].author,"MeteorCat");
+    }
+
+    #[test]
+    fn future_rss_feed_vetoed_items(){
+        let xml = r#"<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>
+            <item><title>skipped</title><dc:subject>skipped</dc:subject><itunes:duration>1:00</itunes:duration></item>
+            <item><title>sponsored</title><dc:subject>ads</dc:subject><itunes:duration>2:00</itunes:duration></item>
+            <item><title>Hey!</title><itunes:duration>3:00</itunes:duration></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.on_item_start(|index| index != 0);
+        parser.on_item_complete(|item| item.title != "sponsored");
+
+        let feed = Feed::parse_using(&parser,xml).unwrap();
+        assert_eq!(feed.entries.len(),1);
+        assert_eq!(feed.entries[0].item.title,"Hey!");
+        #[cfg(feature = "podcast")]
+        assert_eq!(feed.entries[0].item.podcast.as_ref().unwrap().duration,Some(Duration::from_secs(180)));
+        assert!(!feed.entries[0].extensions.contains_key("dc:subject"));
+
+        parser.set_xml(xml.to_string());
+        let itunes = parser.parse_itunes().unwrap();
+        assert_eq!(itunes.len(),1);
+        assert_eq!(itunes[0].duration,Some(Duration::from_secs(180)));
+    }
+}
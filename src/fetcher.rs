@@ -0,0 +1,271 @@
+//!
+//! HTTP Backends
+//!
+//! Everything that downloads a feed goes through [`HttpFetcher`], so the
+//! HTTP client is interchangeable: reqwest's blocking client by default and
+//! a lighter hyper client behind the `hyper-backend` feature.
+//!
+
+use std::io::Read;
+use crate::RetryPolicy;
+
+///
+/// Response handed back by an [`HttpFetcher`], the body is read lazily
+///
+pub struct HttpResponse{
+    pub status:u16,
+    /// Header names are lowercase
+    pub headers:Vec<(String,String)>,
+    pub body:Box<dyn Read+Send>,
+}
+
+impl std::fmt::Debug for HttpResponse{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        f.debug_struct("HttpResponse")
+            .field("status",&self.status)
+            .field("headers",&self.headers)
+            .finish()
+    }
+}
+
+impl HttpResponse{
+
+    /// First value of a header, `name` is matched case-insensitively
+    pub fn header(&self,name:&str)->Option<&str>{
+        self.headers.iter()
+            .find(|(key,_)| key.eq_ignore_ascii_case(name))
+            .map(|(_,value)| value.as_str())
+    }
+
+    /// Charset announced in the `Content-Type` header
+    pub fn charset(&self)->Option<String>{
+        self.header("content-type")
+            .and_then(|v| v.split(';').find_map(|p| p.trim().strip_prefix("charset=")))
+            .map(|c| c.trim_matches('"').to_string())
+    }
+
+    /// Turn 4xx and 5xx answers into an error
+    pub fn error_for_status(self)->Result<Self,std::io::Error>{
+        match self.status {
+            400..=599 => Err(std::io::Error::other(format!("Failed by HTTP status {}",self.status))),
+            _ => Ok(self),
+        }
+    }
+
+    ///
+    /// Whole body as utf8, decoded from the announced charset or `default_charset`
+    ///
+    pub fn text(mut self,default_charset:&str)->Result<String,std::io::Error>{
+        let mut body = Vec::new();
+        self.body.read_to_end(&mut body)?;
+        let charset = self.charset().unwrap_or_else(|| default_charset.to_string());
+        Ok(encoding(&charset).decode(&body).0.into_owned())
+    }
+}
+
+/// Encoding of a charset label, unknown labels are read as utf8
+/// like reqwest::blocking::Response::text_with_charset
+pub(crate) fn encoding(charset:&str)->&'static encoding_rs::Encoding{
+    encoding_rs::Encoding::for_label(charset.trim().as_bytes())
+        .unwrap_or(encoding_rs::UTF_8)
+}
+
+///
+/// Headers sent with every request of a parser, see [`RssParser::request`]
+///
+/// Some hosts turn away reqwest's default user agent; the options are sent
+/// by the blocking and the async fetches alike, and by the polls of a
+/// [`FeedWatcher`](crate::FeedWatcher) after its validators, on top of the
+/// headers of the client.
+///
+/// ```no_run
+/// use future_rss::{RequestOptions,RetryPolicy,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     let mut parser = RssParser::new();
+///     parser.request = RequestOptions::new()
+///         .user_agent("Mozilla/5.0 (compatible; reader/1.0)")
+///         .accept_language("zh-CN,en;q=0.8")
+///         .header("Cookie","session=1")
+///         .retry(RetryPolicy::new().max_attempts(5));
+///     let rss = parser.parse_url("https://www.zhihu.com/rss","utf8")?;
+///     println!("{:?}",rss);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`RssParser::request`]: crate::RssParser::request
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct RequestOptions{
+    pub user_agent:Option<String>,
+    pub accept_language:Option<String>,
+    /// Any other header, sent in order after the two above
+    pub headers:Vec<(String,String)>,
+    /// Send failed requests again, see [`RetryPolicy`]
+    pub retry:Option<RetryPolicy>,
+}
+
+impl RequestOptions{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    pub fn user_agent(mut self,user_agent:&str)->Self{
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    pub fn accept_language(mut self,languages:&str)->Self{
+        self.accept_language = Some(languages.to_string());
+        self
+    }
+
+    pub fn header(mut self,name:&str,value:&str)->Self{
+        self.headers.push((name.to_string(),value.to_string()));
+        self
+    }
+
+    pub fn retry(mut self,policy:RetryPolicy)->Self{
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Every header to send, as passed to [`HttpFetcher::fetch`]
+    pub fn header_pairs(&self)->Vec<(&str,&str)>{
+        self.user_agent.iter().map(|agent| ("User-Agent",agent.as_str()))
+            .chain(self.accept_language.iter().map(|languages| ("Accept-Language",languages.as_str())))
+            .chain(self.headers.iter().map(|(name,value)| (name.as_str(),value.as_str())))
+            .collect()
+    }
+}
+
+///
+/// Blocking HTTP GET used to download feeds
+///
+/// ```no_run
+/// use future_rss::{HttpFetcher,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     let client = reqwest::blocking::Client::new();
+///     let mut parser = RssParser::new();
+///     let rss = parser.parse_url_with(&client,"https://www.zhihu.com/rss","utf8")?;
+///     println!("{:?}",rss);
+///     Ok(())
+/// }
+/// ```
+pub trait HttpFetcher:Send+Sync{
+    fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>;
+}
+
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+impl HttpFetcher for reqwest::blocking::Client{
+    fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let mut request = self.get(url);
+        for (name,value) in headers {
+            request = request.header(*name,*value);
+        }
+        let response = request.send().map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(HttpResponse{
+            status:response.status().as_u16(),
+            headers:response.headers()
+                .iter()
+                .filter_map(|(name,value)| value.to_str().ok().map(|v| (name.to_string(),v.to_string())))
+                .collect(),
+            body:Box::new(response),
+        })
+    }
+}
+
+///
+/// Plain hyper client on a private tokio runtime, without reqwest's redirect,
+/// cookie and proxy handling. Redirects are returned as they are.
+///
+#[cfg(feature = "hyper-backend")]
+pub struct HyperFetcher{
+    client:hyper::Client<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>,
+    runtime:tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "hyper-backend")]
+impl HyperFetcher{
+
+    pub fn new()->Result<Self,std::io::Error>{
+        let runtime = tokio::runtime::Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .enable_all()
+            .build()?;
+        Ok(Self{
+            client:hyper::Client::builder().build(hyper_tls::HttpsConnector::new()),
+            runtime,
+        })
+    }
+}
+
+#[cfg(feature = "hyper-backend")]
+impl HttpFetcher for HyperFetcher{
+    fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let mut request = hyper::Request::get(url);
+        for (name,value) in headers {
+            request = request.header(*name,*value);
+        }
+        let request = request.body(hyper::Body::empty()).map_err(std::io::Error::other)?;
+
+        let client = self.client.clone();
+        let (sender,receiver) = std::sync::mpsc::channel();
+        self.runtime.handle().spawn(async move {
+            let response = async {
+                let (parts,body) = client.request(request).await?.into_parts();
+                Ok::<_,hyper::Error>((parts,hyper::body::to_bytes(body).await?))
+            }.await;
+            let _ = sender.send(response);
+        });
+        let (parts,body) = receiver.recv()
+            .map_err(std::io::Error::other)?
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+
+        Ok(HttpResponse{
+            status:parts.status.as_u16(),
+            headers:parts.headers
+                .iter()
+                .filter_map(|(name,value)| value.to_str().ok().map(|v| (name.to_string(),v.to_string())))
+                .collect(),
+            body:Box::new(std::io::Cursor::new(body)),
+        })
+    }
+}
+
+
+#[cfg(all(test,any(feature = "http",feature = "hyper-backend")))]
+mod tests {
+    use crate::HttpFetcher;
+    use crate::mock::{serve,FEED};
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_fetcher_reqwest(){
+        let server = serve(FEED);
+        let client = reqwest::blocking::Client::new();
+        let response = client.fetch(&server.url("/feed"),&[("If-None-Match","\"v1\"")]).unwrap();
+        assert_eq!(response.status,304);
+
+        let response = client.fetch(&server.url("/feed"),&[]).unwrap();
+        assert_eq!(response.header("ETag"),Some("\"v1\""));
+        assert_eq!(response.charset().as_deref(),Some("utf-8"));
+        assert!(response.text("gbk").unwrap().contains("Hey!"));
+    }
+
+    #[cfg(feature = "hyper-backend")]
+    #[test]
+    fn future_rss_fetcher_hyper(){
+        let server = serve(FEED);
+        let fetcher = crate::HyperFetcher::new().unwrap();
+        let response = fetcher.fetch(&server.url("/feed"),&[("If-None-Match","\"v1\"")]).unwrap();
+        assert_eq!(response.status,304);
+
+        let mut parser = crate::RssParser::new();
+        let rss = parser.parse_url_with(&fetcher,&server.url("/feed"),"utf8").unwrap();
+        assert_eq!(rss[0].title,"Hey!");
+    }
+}
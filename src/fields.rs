@@ -0,0 +1,141 @@
+//!
+//! Field Mappings
+//!
+//! Beyond the fixed item fields, any number of named fields are captured
+//! from each item, every one from a list of tags tried in order, so feeds
+//! with extra or oddly named elements need no parser changes.
+//!
+
+use std::collections::BTreeMap;
+use crate::{RssParser,Selector};
+use crate::extension::scan_items;
+
+///
+/// Named field read from the first of its tags an item carries
+///
+/// Tags are element names or [`Selector`] paths such as `link/@href`.
+///
+/// ```
+/// use future_rss::{RssParser,FieldMapping};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut parser = RssParser::new();
+///     parser.fields.push(FieldMapping::new("creator",&["dc:creator","author"]));
+///     parser.fields.push(FieldMapping::new("comments",&["slash:comments"]).required(true));
+///     parser.set_xml(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0"><channel>
+///             <item><author>a@examples.com</author><dc:creator>MeteorCat</dc:creator><slash:comments>3</slash:comments></item>
+///             <item><author>b@examples.com</author></item>
+///         </channel></rss>
+///         "#
+///     ));
+///     let records = parser.parse_fields()?;
+///     assert_eq!(records.len(),1);
+///     assert_eq!(records[0]["creator"],"MeteorCat");
+///     assert_eq!(records[0]["comments"],"3");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct FieldMapping{
+    /// Key of the value in the records
+    pub output_name:String,
+    /// Tags in order of preference
+    pub tags:Vec<String>,
+    /// Items lacking the field are left out
+    pub required:bool,
+}
+
+impl FieldMapping{
+
+    pub fn new(output_name:&str,tags:&[&str])->Self{
+        Self{
+            output_name:output_name.to_string(),
+            tags:tags.iter().map(|tag| tag.to_string()).collect(),
+            required:false,
+        }
+    }
+
+    pub fn required(mut self,required:bool)->Self{
+        self.required = required;
+        self
+    }
+}
+
+///
+/// Records of the parser's field mappings, one per item that has all the
+/// required fields; empty values count as missing
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<BTreeMap<String,String>>,std::io::Error>{
+    let selectors = parser.fields.iter()
+        .map(|field| field.tags.iter().map(|tag| Selector::parse(tag)).collect::<Result<Vec<_>,_>>())
+        .collect::<Result<Vec<_>,_>>()?;
+    // best value per field so far, with the rank of the tag it came from
+    let items = scan_items(parser,xml,"RssParser::parse_fields",|found:&mut Vec<Option<(usize,String)>>,element| {
+        found.resize(selectors.len(),None);
+        for (best,selectors) in found.iter_mut().zip(selectors.iter()) {
+            for (rank,selector) in selectors.iter().enumerate() {
+                match selector.select(&parser.node_tag,element) {
+                    Some(value) if !value.is_empty() && best.as_ref().map_or(true,|(best,_)| rank < *best) => {
+                        *best = Some((rank,value.to_string()));
+                    }
+                    _ => (),
+                }
+            }
+        }
+    })?;
+
+    Ok(items.into_iter()
+        .filter_map(|mut found| {
+            found.resize(selectors.len(),None);
+            let mut record = BTreeMap::new();
+            for (field,value) in parser.fields.iter().zip(found) {
+                match value {
+                    Some((_,value)) => {
+                        record.insert(field.output_name.clone(),value);
+                    }
+                    None if field.required => return None,
+                    None => (),
+                }
+            }
+            Some(record)
+        })
+        .collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,FieldMapping};
+
+    #[test]
+    fn future_rss_field_mapping(){
+        let mut parser = RssParser::new();
+        parser.fields = vec![
+            FieldMapping::new("summary",&["content:encoded","description"]),
+            FieldMapping::new("image",&["media:thumbnail/@url","enclosure/@url"]),
+            FieldMapping::new("title",&["title"]).required(true),
+        ];
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0"><channel>
+                    <item>
+                        <title>1</title><description>short</description><content:encoded><![CDATA[<p>long</p>]]></content:encoded>
+                        <enclosure url="https://examples.com/1.mp3"/><media:thumbnail url="https://examples.com/1.jpg"/>
+                    </item>
+                    <item><title>2</title><description>only</description><content:encoded></content:encoded></item>
+                    <item><description>untitled</description></item>
+                </channel></rss>
+        "#));
+        let records = parser.parse_fields().unwrap();
+        assert_eq!(records.len(),2);
+        assert_eq!(records[0]["summary"],"<p>long</p>");
+        assert_eq!(records[0]["image"],"https://examples.com/1.jpg");
+        assert_eq!(records[1]["summary"],"only");
+        assert!(!records[1].contains_key("image"));
+
+        parser.fields.push(FieldMapping::new("broken",&["a//b"]));
+        assert!(parser.parse_fields().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//!
+//! Item Hooks
+//!
+//! Callbacks run by the parser itself as items start and complete, so items
+//! are normalized or dropped inline instead of in a second pass over the
+//! result.
+//!
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use crate::{RssItem,RawField,RawRssItem};
+
+type StartHook = Arc<dyn Fn(usize)->bool+Send+Sync>;
+type CompleteHook = Arc<dyn Fn(&mut RssItem)->bool+Send+Sync>;
+
+///
+/// Hooks registered with [`RssParser::on_item_start`](crate::RssParser::on_item_start)
+/// and [`RssParser::on_item_complete`](crate::RssParser::on_item_complete),
+/// run in registration order until one vetoes
+///
+#[derive(Clone,Default)]
+pub(crate) struct ItemHooks{
+    start:Vec<StartHook>,
+    complete:Vec<CompleteHook>,
+}
+
+impl std::fmt::Debug for ItemHooks{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        f.debug_struct("ItemHooks")
+            .field("start",&self.start.len())
+            .field("complete",&self.complete.len())
+            .finish()
+    }
+}
+
+impl ItemHooks{
+
+    pub(crate) fn push_start(&mut self,hook:StartHook){
+        self.start.push(hook);
+    }
+
+    pub(crate) fn push_complete(&mut self,hook:CompleteHook){
+        self.complete.push(hook);
+    }
+
+    /// Item number `index` of the document is to be read
+    pub(crate) fn start(&self,index:usize)->bool{
+        self.start.iter().all(|hook| hook(index))
+    }
+
+    /// No hook can veto an item
+    pub(crate) fn is_empty(&self)->bool{
+        self.start.is_empty() && self.complete.is_empty()
+    }
+
+    pub(crate) fn has_complete(&self)->bool{
+        !self.complete.is_empty()
+    }
+
+    ///
+    /// Run the complete hooks on the decoded item, `None` when one vetoes;
+    /// the kept fields are stored decoded from then on
+    ///
+    pub(crate) fn complete<'x>(&self,raw:RawRssItem<'x>)->Result<Option<RawRssItem<'x>>,std::io::Error>{
+        let namespaces = [&raw.title,&raw.link,&raw.author,&raw.description,&raw.content,&raw.guid,&raw.publish,&raw.updated]
+            .map(|field| field.namespace.clone());
+        let mut item = raw.into_owned()?;
+        if !self.complete.iter().all(|hook| hook(&mut item)) {
+            return Ok(None);
+        }
+        let [title,link,author,description,content,guid,publish,updated] = namespaces;
+        let field = |text:String,namespace| {
+            // decoded text must not be unescaped again, cdata is taken as is
+            let mut field = RawField::new(Cow::Owned(text.into_bytes()),true);
+            field.namespace = namespace;
+            field
+        };
+        Ok(Some(RawRssItem{
+            title:field(item.title,title),
+            link:field(item.link,link),
+            author:field(item.author,author),
+            description:field(item.description,description),
+            content:field(item.content,content),
+            guid:field(item.guid,guid),
+            publish:field(item.publish,publish),
+            updated:field(item.updated,updated),
+            enclosures:item.enclosures,
+            categories:item.categories,
+            extensions:item.extensions.into_iter()
+                .map(|(name,values)| (name,values.into_iter().map(|value| field(value,None)).collect()))
+                .collect(),
+            #[cfg(feature = "podcast")]
+            podcast:item.podcast,
+        }))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::RssParser;
+
+    #[test]
+    fn future_rss_item_hooks(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<rss><channel>
+                <item><title>  Tom &amp; Jerry  </title><link>http://examples.com/1</link></item>
+                <item><title>sponsored</title></item>
+                <item><title>skipped</title></item>
+                <item><title>3</title></item>
+            </channel></rss>"#
+        ));
+        parser.on_item_start(|index| index != 2);
+        parser.on_item_complete(|item| {
+            item.title = item.title.trim().to_string();
+            item.link = item.link.replacen("http://","https://",1);
+            true
+        });
+        parser.on_item_complete(|item| item.title != "sponsored");
+
+        let items = parser.parse_vec().unwrap();
+        let titles = items.iter().map(|item| item.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles,["Tom & Jerry","3"]);
+        assert_eq!(items[0].link,"https://examples.com/1");
+        assert_eq!(parser.parse_str(parser.get_xml()).unwrap()[0].title,"Tom & Jerry");
+        assert_eq!(parser.parse_first(1).unwrap().len(),1);
+        assert!(format!("{:?}",parser).contains("ItemHooks { start: 1, complete: 2 }"));
+    }
+}
@@ -0,0 +1,160 @@
+//!
+//! Feeds Wrapped In HTML
+//!
+//! Misconfigured servers answer with the feed inside an HTML page, either
+//! as markup in the body or escaped in a `<pre>` block, or send the plain
+//! feed as `text/html`; [`extract_feed`] digs the feed back out.
+//!
+
+use crate::FeedFormat;
+
+/// Root elements a wrapped feed starts with
+static FEED_ROOTS:[&str;3] = ["rss","feed","rdf:rdf"];
+
+///
+/// Feed found inside an HTML page, with an xml declaration in front;
+/// `None` when `text` is no HTML page or holds no feed
+///
+/// ```
+/// use future_rss::{extract_feed,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let page = r#"<!DOCTYPE html><html><body><pre>
+///         &lt;rss version="2.0"&gt;&lt;item&gt;&lt;title&gt;Hey!&lt;/title&gt;&lt;/item&gt;&lt;/rss&gt;
+///     </pre></body></html>"#;
+///     let feed = extract_feed(page).unwrap();
+///     let items = RssParser::from_str(feed)?.parse_vec()?;
+///     assert_eq!(items[0].title,"Hey!");
+///     Ok(())
+/// }
+/// ```
+pub fn extract_feed(text:&str)->Option<String>{
+    if FeedFormat::detect(text).is_some() || !is_html(text) {
+        return None;
+    }
+    let fragment = FEED_ROOTS.iter()
+        .find_map(|root| element(text,root,"<","</",">"))
+        .map(String::from)
+        .or_else(|| {
+            FEED_ROOTS.iter()
+                .find_map(|root| element(text,root,"&lt;","&lt;/","&gt;"))
+                .map(|escaped| unescape(&strip_tags(escaped)))
+        })?;
+    Some(format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}",fragment))
+}
+
+fn is_html(text:&str)->bool{
+    let head = text.get(..text.len().min(1024)).unwrap_or(text).to_ascii_lowercase();
+    head.contains("<!doctype html") || head.contains("<html") || head.contains("<body")
+}
+
+///
+/// `root` from its start tag to its last end tag, with tags opened by
+/// `open`, closed by `close` and ended by `end`
+///
+fn element<'t>(text:&'t str,root:&str,open:&str,close:&str,end:&str)->Option<&'t str>{
+    let lower = text.to_ascii_lowercase();
+    let start_tag = format!("{}{}",open,root);
+    let start = lower.match_indices(&start_tag)
+        .map(|(i,_)| i)
+        .find(|&i| lower[i + start_tag.len()..].starts_with(|c:char| c.is_ascii_whitespace() || c == '>' || c == '&'))?;
+    let end_tag = format!("{}{}{}",close,root,end);
+    let stop = lower.rfind(&end_tag).filter(|&stop| stop > start)?;
+    text.get(start..stop + end_tag.len())
+}
+
+/// Drop the markup syntax highlighters put around escaped text
+pub(crate) fn strip_tags(text:&str)->String{
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = match rest[start..].find('>') {
+            Some(end) => &rest[start + end + 1..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+pub(crate) fn unescape(text:&str)->String{
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').filter(|&end| end <= 10).map(|end| &rest[1..end]);
+        let decoded = match entity {
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("amp") => Some('&'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some(code) if code.starts_with("#x") || code.starts_with("#X") => u32::from_str_radix(&code[2..],16).ok().and_then(char::from_u32),
+            Some(code) if code.starts_with('#') => code[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match (decoded,entity) {
+            (Some(c),Some(entity)) => {
+                out.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{extract_feed,RssParser,HttpFetcher,HttpResponse};
+
+    #[test]
+    fn future_rss_extract_feed(){
+        let page = r#"<html><head><title>Feed</title></head><body>
+            <div><RSS version="2.0"><channel><item><title>Tom &amp; Jerry</title></item></channel></RSS></div>
+        </body></html>"#;
+        let feed = extract_feed(page).unwrap();
+        assert!(feed.starts_with("<?xml"));
+        assert!(feed.ends_with("</RSS>"));
+
+        let escaped = r#"<!doctype html><pre><span class="tag">&lt;feed xmlns="http://www.w3.org/2005/Atom"&gt;</span>
+            &lt;entry&gt;&lt;title&gt;Tom &amp;amp; Jerry &#x263A;&lt;/title&gt;&lt;/entry&gt;&lt;/feed&gt;</pre>"#;
+        let feed = extract_feed(escaped).unwrap();
+        assert!(feed.contains("<title>Tom &amp; Jerry \u{263A}</title>"));
+
+        assert_eq!(extract_feed("<?xml version=\"1.0\"?><rss></rss>"),None);
+        assert_eq!(extract_feed("<html><body>no feed, rss is great</body></html>"),None);
+
+        let mut parser = RssParser::from_str(String::from(page)).unwrap();
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"Tom & Jerry");
+        parser.set_xml(String::from("<rss><item><title>1</title></item></rss>"));
+        assert!(!parser.unwrap_html());
+    }
+
+    /// Host serving its feed as a web page
+    struct HtmlHost;
+
+    impl HttpFetcher for HtmlHost{
+        fn fetch(&self,_:&str,_:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+            let page = "<html><body><rss><item><title>Hey!</title></item></rss></body></html>";
+            Ok(HttpResponse{
+                status:200,
+                headers:vec![(String::from("content-type"),String::from("text/html; charset=utf-8"))],
+                body:Box::new(std::io::Cursor::new(page.as_bytes())),
+            })
+        }
+    }
+
+    #[test]
+    fn future_rss_extract_feed_response(){
+        let items = RssParser::new().parse_url_with(&HtmlHost,"https://examples.com/rss","utf8").unwrap();
+        assert_eq!(items[0].title,"Hey!");
+    }
+}
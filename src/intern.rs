@@ -0,0 +1,115 @@
+//!
+//! String Interning
+//!
+//! Values that repeat across items, authors above all, are stored once in a
+//! [`StringPool`] and shared by every item through `Arc<str>`.
+//!
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use crate::RssItemRef;
+
+///
+/// Pool of shared strings, reuse one pool across feeds to share their values too
+///
+#[derive(Debug,Clone,Default)]
+pub struct StringPool{
+    strings:HashSet<Arc<str>>,
+}
+
+impl StringPool{
+
+    pub fn new()->Self{
+        Self{
+            strings:HashSet::new()
+        }
+    }
+
+    /// Shared copy of `value`, allocated on first use only
+    pub fn intern(&mut self,value:&str)->Arc<str>{
+        if let Some(shared) = self.strings.get(value) {
+            return shared.clone();
+        }
+        let shared:Arc<str> = Arc::from(value);
+        self.strings.insert(shared.clone());
+        shared
+    }
+
+    pub fn len(&self)->usize{
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.strings.is_empty()
+    }
+
+    /// Forget every pooled value, items keep their own references
+    pub fn clear(&mut self){
+        self.strings.clear();
+    }
+}
+
+///
+/// Rss Item Node with cheaply clonable fields, repeated values point into a [`StringPool`]
+///
+/// ```
+/// use std::sync::Arc;
+/// use future_rss::{RssParser,StringPool};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut parser = RssParser::new();
+///     parser.set_xml(String::from(
+///         "<rss><item><author>MeteorCat</author></item><item><author>MeteorCat</author></item></rss>"
+///     ));
+///     let mut pool = StringPool::new();
+///     let items = parser.parse_shared(&mut pool)?;
+///     assert!(Arc::ptr_eq(&items[0].author,&items[1].author));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct SharedRssItem{
+    pub title: Arc<str>,
+    pub link: Arc<str>,
+    pub author: Arc<str>,
+    pub description: Arc<str>,
+    pub guid: Arc<str>,
+    pub publish: Arc<str>,
+    pub updated: Arc<str>,
+}
+
+impl SharedRssItem{
+
+    /// Move an item into shared storage, the author is taken from `pool`
+    pub fn from_item(item:RssItemRef<'_>,pool:&mut StringPool)->Self{
+        Self{
+            title:Arc::from(item.title.as_ref()),
+            link:Arc::from(item.link.as_ref()),
+            author:pool.intern(&item.author),
+            description:Arc::from(item.description.as_ref()),
+            guid:Arc::from(item.guid.as_ref()),
+            publish:Arc::from(item.publish.as_ref()),
+            updated:Arc::from(item.updated.as_ref()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::StringPool;
+
+    #[test]
+    fn future_rss_string_pool(){
+        let mut pool = StringPool::new();
+        let a = pool.intern("MeteorCat");
+        let b = pool.intern(&String::from("MeteorCat"));
+        assert!(Arc::ptr_eq(&a,&b));
+        pool.intern("Other");
+        assert_eq!(pool.len(),2);
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(&*a,"MeteorCat");
+    }
+}
@@ -0,0 +1,823 @@
+//!
+//! Pull Parser
+//!
+//! Reads xml events only until the next item is complete, so callers decide
+//! how many items are held in memory at once.
+//!
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap,HashMap};
+use std::io::BufRead;
+use std::sync::Arc;
+use std::time::{Duration,Instant};
+use quick_xml::Reader;
+use quick_xml::events::{Event,BytesStart};
+use crate::{RssParser,RawField,RawRssItem,Category,DateParser,RSS_DEFAULT_CATEGORY_TAG};
+use crate::hooks::ItemHooks;
+
+///
+/// Measurements of a single parse
+///
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct ParseMetrics{
+    /// Bytes of xml consumed
+    pub bytes:usize,
+    /// Xml events read, including the ones outside of items
+    pub events:usize,
+    /// Items produced
+    pub items:usize,
+    /// Wall time spent reading the document
+    pub duration:Duration,
+    /// Elements inside items that no configured tag captured, by name with
+    /// their count, enclosures aside; only filled with [`RssParser::report_unknown`]
+    pub unknown_elements:BTreeMap<String,usize>,
+}
+
+///
+/// Error carried by the `OutOfMemory` io error of a parse that went over
+/// [`RssParser::max_parse_bytes`]
+///
+/// ```
+/// use future_rss::{RssParser,BudgetExceeded};
+///
+/// fn main(){
+///     let mut parser = RssParser::new();
+///     parser.max_parse_bytes = Some(16);
+///     parser.set_xml(String::from("<rss><item><title>Hey!</title></item></rss>"));
+///     let error = parser.parse_vec().unwrap_err();
+///     let budget = error.get_ref().and_then(|e| e.downcast_ref::<BudgetExceeded>()).unwrap();
+///     assert_eq!(budget.budget,16);
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub struct BudgetExceeded{
+    /// Configured budget in bytes
+    pub budget:usize,
+    /// Bytes of document and item text held when the parse was aborted
+    pub used:usize,
+}
+
+impl std::fmt::Display for BudgetExceeded{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        write!(f,"Parse budget of {} bytes exceeded ({} bytes used)",self.budget,self.used)
+    }
+}
+
+impl std::error::Error for BudgetExceeded{}
+
+///
+/// Buffers and measurements the parser keeps between runs
+///
+#[derive(Debug,Clone,Default)]
+pub(crate) struct Scratch{
+    pub(crate) buff:Vec<u8>,
+    pub(crate) metrics:ParseMetrics,
+}
+
+///
+/// Item field a tag name resolves to
+///
+#[derive(Debug,Clone,Copy,PartialEq)]
+enum Field{
+    Node,
+    Title,
+    Link,
+    Author,
+    Description,
+    Content,
+    Guid,
+    Publish,
+    Updated,
+    Category,
+    /// One of the `ITUNES_*_TAG`
+    #[cfg(feature = "podcast")]
+    Itunes(&'static str),
+}
+
+///
+/// Configured tag names compiled into one lookup, so every start event
+/// costs a single hash probe instead of a comparison per tag
+///
+struct TagMatcher{
+    /// tags matched by their name as written
+    tags:HashMap<Vec<u8>,Field>,
+    /// `{uri}local` of tags whose prefix or uri is known
+    bound:HashMap<Vec<u8>,Field>,
+    /// prefixed tags matched as written on elements of no namespace
+    undeclared:HashMap<Vec<u8>,Field>,
+    name:Vec<u8>,
+    key:Vec<u8>,
+}
+
+impl TagMatcher{
+
+    fn new(parser:&RssParser)->Self{
+        let mut matcher = Self{
+            tags:HashMap::new(),
+            bound:HashMap::new(),
+            undeclared:HashMap::new(),
+            name:Vec::new(),
+            key:Vec::new(),
+        };
+        // on duplicates the first tag wins, in the order fields were always matched
+        for (tag,field) in [
+            (&parser.node_tag,Field::Node),
+            (&parser.title_tag,Field::Title),
+            (&parser.link_tag,Field::Link),
+            (&parser.author_tag,Field::Author),
+            (&parser.description_tag,Field::Description),
+            (&parser.content_tag,Field::Content),
+            (&parser.guid_tag,Field::Guid),
+            (&parser.publish_tag,Field::Publish),
+            (&parser.updated_tag,Field::Updated),
+        ] {
+            if !tag.is_empty() {
+                matcher.insert(parser,tag,field);
+            }
+        }
+        matcher.insert(parser,RSS_DEFAULT_CATEGORY_TAG,Field::Category);
+        #[cfg(feature = "podcast")]
+        for tag in [
+            crate::ITUNES_AUTHOR_TAG,
+            crate::ITUNES_DURATION_TAG,
+            crate::ITUNES_EPISODE_TAG,
+            crate::ITUNES_SEASON_TAG,
+            crate::ITUNES_IMAGE_TAG,
+            crate::ITUNES_EXPLICIT_TAG,
+        ] {
+            matcher.insert(parser,tag,Field::Itunes(tag));
+        }
+        matcher
+    }
+
+    fn insert(&mut self,parser:&RssParser,tag:&str,field:Field){
+        if let Some((uri,local)) = tag.strip_prefix('{').and_then(|tag| tag.split_once('}')) {
+            self.bound.entry(format!("{{{}}}{}",uri,local.to_ascii_lowercase()).into_bytes()).or_insert(field);
+            return;
+        }
+        let tag = tag.to_ascii_lowercase();
+        match tag.split_once(':').and_then(|(prefix,local)| parser.namespaces.get(prefix).map(|uri| (uri,local))) {
+            Some((uri,local)) => {
+                self.bound.entry(format!("{{{}}}{}",uri,local).into_bytes()).or_insert(field);
+                self.undeclared.entry(tag.into_bytes()).or_insert(field);
+            }
+            None => { self.tags.entry(tag.into_bytes()).or_insert(field); }
+        }
+    }
+
+    /// Field of an element, `ns` is the uri its prefix is bound to
+    fn resolve(&mut self,ns:Option<&[u8]>,name:&[u8])->Option<Field>{
+        self.name.clear();
+        self.name.extend(name.iter().map(u8::to_ascii_lowercase));
+        match ns {
+            Some(uri) if !self.bound.is_empty() => {
+                let start = self.name.iter().position(|&b| b == b':').map_or(0,|colon| colon + 1);
+                let local = &self.name[start..];
+                self.key.clear();
+                self.key.push(b'{');
+                self.key.extend_from_slice(uri);
+                self.key.push(b'}');
+                self.key.extend_from_slice(local);
+                self.bound.get(&self.key).or_else(|| self.tags.get(&self.name)).copied()
+            }
+            Some(_) => self.tags.get(&self.name).copied(),
+            None => self.tags.get(&self.name).or_else(|| self.undeclared.get(&self.name)).copied(),
+        }
+    }
+}
+
+/// Events read between two looks at the clock while a deadline is set
+const DEADLINE_CHECK_EVENTS:usize = 64;
+
+///
+/// Iterator over the items of one document, yielded as soon as their
+/// closing tag is read. Text is borrowed from `source` when the reader
+/// runs over that same string and copied otherwise.
+///
+pub(crate) struct RawItems<'x,B:BufRead>{
+    matcher:TagMatcher,
+    reader:Reader<B>,
+    source:Option<&'x str>,
+    buff:Vec<u8>,
+    ns_buff:Vec<u8>,
+    /// namespace uris seen so far, shared by every field from them
+    namespaces:HashMap<Vec<u8>,Arc<str>>,
+    active:Option<Field>,
+    active_ns:Option<Arc<str>>,
+    current:Option<RawRssItem<'x>>,
+    /// `domain` of the open category, for its text
+    category_domain:Option<String>,
+    /// elements open inside the current item
+    depth:usize,
+    /// name of the uncaptured child of the item whose text is read
+    extension:Option<String>,
+    /// input may start or end in the middle of the document
+    fragment:bool,
+    /// input starts at an item boundary after a broken item
+    resumed:bool,
+    done:bool,
+    events:usize,
+    items:usize,
+    started:Instant,
+    deadline:Option<Instant>,
+    budget:Option<usize>,
+    /// bytes of field text copied or borrowed into items
+    produced:usize,
+    report_unknown:bool,
+    unknown:BTreeMap<String,usize>,
+    /// set when dates are rewritten to UTC
+    dates:Option<DateParser>,
+    hooks:ItemHooks,
+    /// items whose start tag was read, vetoed ones included
+    started_items:usize,
+    /// document index of the item being filled in and of the last one read
+    current_index:usize,
+    read_index:usize,
+    /// byte offset where the last item read ends, the start of the next one
+    /// when that closed it
+    item_end:usize,
+    /// document indices of the items handed out
+    kept:Vec<usize>,
+}
+
+impl<'x,B:BufRead> RawItems<'x,B>{
+
+    pub(crate) fn new(parser:&RssParser,mut reader:Reader<B>,source:Option<&'x str>,mut buff:Vec<u8>)->Self{
+        buff.reserve(parser.event_buffer);
+        reader.trim_text(true);
+        reader.check_end_names(true);
+        reader.check_comments(false);
+        reader.expand_empty_elements(true);
+
+        Self{
+            matcher:TagMatcher::new(parser),
+            reader,
+            source,
+            buff,
+            ns_buff:Vec::new(),
+            namespaces:HashMap::new(),
+            active:None,
+            active_ns:None,
+            current:None,
+            category_domain:None,
+            depth:0,
+            extension:None,
+            fragment:false,
+            resumed:false,
+            done:false,
+            events:0,
+            items:0,
+            started:Instant::now(),
+            deadline:parser.max_parse_duration.map(|limit| Instant::now() + limit),
+            budget:parser.max_parse_bytes,
+            produced:0,
+            report_unknown:parser.report_unknown,
+            unknown:BTreeMap::new(),
+            dates:match (&parser.date_parser,parser.utc_dates) {
+                (Some(dates),_) => Some(dates.clone()),
+                (None,true) => Some(DateParser::new()),
+                (None,false) => None,
+            },
+            hooks:parser.hooks.clone(),
+            started_items:0,
+            current_index:0,
+            read_index:0,
+            item_end:0,
+            kept:Vec::new(),
+        }
+    }
+
+    ///
+    /// Read a slice of a document: closing tags of elements opened before the
+    /// slice are accepted and an item cut off by the end of input is dropped
+    ///
+    pub(crate) fn fragment(mut self)->Self{
+        self.reader.check_end_names(false);
+        self.fragment = true;
+        self
+    }
+
+    ///
+    /// Read from an item boundary in the middle of a document: closing tags
+    /// of elements opened before it are skipped, the others still checked
+    ///
+    pub(crate) fn resumed(mut self)->Self{
+        self.resumed = true;
+        self
+    }
+
+    /// Bytes consumed so far
+    pub(crate) fn position(&self)->usize{
+        self.reader.buffer_position()
+    }
+
+    ///
+    /// Where the last yielded item ends: right after its closing tag, or at
+    /// the start tag of the next item for an unterminated one
+    ///
+    pub(crate) fn item_end(&self)->usize{
+        self.item_end
+    }
+
+    pub(crate) fn metrics(&self)->ParseMetrics{
+        ParseMetrics{
+            bytes:self.position(),
+            events:self.events,
+            items:self.items,
+            duration:self.started.elapsed(),
+            unknown_elements:self.unknown.clone(),
+        }
+    }
+
+    ///
+    /// Index in the document of every item handed out so far, counting the
+    /// vetoed ones, so per item passes over the document can be lined up
+    ///
+    pub(crate) fn kept(&self)->&[usize]{
+        &self.kept
+    }
+
+    /// Hand the event buffer back for reuse
+    pub(crate) fn into_buffer(self)->Vec<u8>{
+        self.buff
+    }
+
+    fn read_next(&mut self)->Option<Result<RawRssItem<'x>,std::io::Error>>{
+        loop{
+            if let Some(deadline) = self.deadline {
+                if self.events % DEADLINE_CHECK_EVENTS == 0 && Instant::now() >= deadline {
+                    return Some(Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "Failed by RssParser::max_parse_duration"
+                    )));
+                }
+            }
+            if let Some(budget) = self.budget {
+                // a document parsed from memory is held in full from the start
+                let used = self.source.map_or(self.reader.buffer_position(),str::len) + self.produced;
+                if used > budget {
+                    return Some(Err(std::io::Error::new(
+                        std::io::ErrorKind::OutOfMemory,
+                        BudgetExceeded{ budget, used }
+                    )));
+                }
+            }
+            let position = self.reader.buffer_position();
+            self.buff.clear();
+            self.events += 1;
+            let node_text = match self.reader.read_namespaced_event(&mut self.buff,&mut self.ns_buff) {
+                // Fetch = <Item></Item>
+                Ok((ns,Event::Start(ref e))) => {
+                    self.active = self.matcher.resolve(ns,e.name());
+                    let namespaces = &mut self.namespaces;
+                    self.active_ns = ns.map(|ns| match namespaces.get(ns) {
+                        Some(uri) => uri.clone(),
+                        None => {
+                            let uri:Arc<str> = String::from_utf8_lossy(ns).into();
+                            namespaces.insert(ns.to_vec(),uri.clone());
+                            uri
+                        }
+                    });
+
+                    let enclosure = self.current.as_ref().and_then(|_| crate::enclosure::from_element(e));
+                    if self.active.is_none() && enclosure.is_none() && self.report_unknown && self.current.is_some() {
+                        let name = String::from_utf8_lossy(e.name());
+                        match self.unknown.get_mut(name.as_ref()) {
+                            Some(count) => *count += 1,
+                            None => { self.unknown.insert(name.into_owned(),1); }
+                        }
+                    }
+                    if let (Some(item),false) = (self.current.as_mut(),self.active == Some(Field::Node)) {
+                        self.depth += 1;
+                        // only direct children, anything nested stays with its parent
+                        if self.depth == 1 {
+                            self.extension = None;
+                            // enclosures have a field of their own
+                            if self.active.is_none() && enclosure.is_none() {
+                                let name = String::from_utf8_lossy(e.name()).into_owned();
+                                let mut value = RawField::default();
+                                value.namespace = self.active_ns.clone();
+                                self.produced += name.len();
+                                item.extensions.entry(name.clone()).or_default().push(value);
+                                self.extension = Some(name);
+                            }
+                        }else if let (Some(_),Some(name)) = (self.active,self.extension.take()) {
+                            // a captured child, Atom's <author><name>, covers its parent
+                            if let Some(values) = item.extensions.get_mut(&name) {
+                                values.pop();
+                                if values.is_empty() {
+                                    item.extensions.remove(&name);
+                                }
+                            }
+                        }
+                    }
+                    if let (Some(item),Some(enclosure)) = (self.current.as_mut(),enclosure) {
+                        self.produced += enclosure.url.len();
+                        item.enclosures.push(enclosure);
+                    }
+                    // Atom names the category in attributes, RSS in the text that follows
+                    if let (Some(Field::Category),Some(item)) = (self.active,self.current.as_mut()) {
+                        let (mut term,mut scheme,mut domain) = (None,None,None);
+                        for attribute in e.attributes().flatten() {
+                            let value = attribute.unescaped_value().ok().map(|value| String::from_utf8_lossy(&value).into_owned());
+                            match attribute.key {
+                                b"term" => term = value,
+                                b"scheme" => scheme = value,
+                                b"domain" => domain = value,
+                                _ => (),
+                            }
+                        }
+                        item.categories.push(Category::new(term.as_deref().unwrap_or(""),scheme.as_deref()));
+                        self.category_domain = domain;
+                    }
+                    // artwork sits in an attribute, there is no text to wait for
+                    #[cfg(feature = "podcast")]
+                    if let (Some(Field::Itunes(tag)),Some(item)) = (self.active,self.current.as_mut()) {
+                        if tag == crate::ITUNES_IMAGE_TAG {
+                            let href = e.attributes().flatten()
+                                .find(|attribute| attribute.key == b"href")
+                                .and_then(|attribute| attribute.unescaped_value().ok().map(|value| String::from_utf8_lossy(&value).into_owned()));
+                            if let Some(href) = href {
+                                self.produced += href.len();
+                                crate::itunes::set_field(item.podcast.get_or_insert_with(Default::default),tag,&href);
+                            }
+                            self.active = None;
+                        }
+                    }
+                    // Atom's <link href/>, the first alternate one is the item's
+                    if let (Some(Field::Link),Some(item)) = (self.active,self.current.as_mut()) {
+                        if let Some(href) = alternate_href(e) {
+                            if item.link.is_empty() && !href.is_empty() {
+                                self.produced += href.len();
+                                item.link = RawField::new(Cow::Owned(href),false);
+                                item.link.namespace = self.active_ns.clone();
+                            }
+                            self.active = None;
+                        }
+                    }
+                    if self.active == Some(Field::Node) {
+                        self.depth = 0;
+                        self.extension = None;
+                        // a vetoed item is never filled in
+                        let keep = self.hooks.start(self.started_items);
+                        let previous_index = std::mem::replace(&mut self.current_index,self.started_items);
+                        self.started_items += 1;
+                        let previous = if keep { self.current.replace(RawRssItem::default()) }else { self.current.take() };
+                        // an unterminated item is complete once the next one starts
+                        if let Some(item) = previous {
+                            self.read_index = previous_index;
+                            self.item_end = position;
+                            return Some(Ok(item));
+                        }
+                    }
+                    None
+                }
+
+                // Fetch = </Item>
+                Ok((ns,Event::End(ref e))) => {
+                    if self.matcher.resolve(ns,e.name()) == Some(Field::Node) {
+                        if let Some(item) = self.current.take() {
+                            self.read_index = self.current_index;
+                            self.item_end = self.reader.buffer_position();
+                            return Some(Ok(item));
+                        }
+                    }else if self.current.is_some() {
+                        self.depth = self.depth.saturating_sub(1);
+                    }
+                    None
+                }
+
+                // text of tags that are not captured is never copied
+                Ok((_,Event::CData(_))) | Ok((_,Event::Text(_))) if self.current.is_none() || self.active.is_none() && (self.extension.is_none() || self.depth != 1) => None,
+
+                // Fetch = <Item><Node><CDATA></Node><Item>
+                Ok((_,Event::CData(ref e))) => {
+                    // content sits right before the closing "]]>"
+                    let end = self.reader.buffer_position().saturating_sub(3);
+                    let start = end.saturating_sub(e.escaped().len());
+                    match self.source.and_then(|xml| xml.get(start..end)) {
+                        Some(text) if text.as_bytes() == e.escaped() => Some(RawField::new(Cow::Borrowed(text.as_bytes()),true)),
+                        _ => Some(RawField::new(Cow::Owned(e.escaped().to_vec()),true)),
+                    }
+                }
+
+                // Fetch = <Item><Node></Node><Item>
+                Ok((_,Event::Text(ref e))) => {
+                    // raw text runs up to the next '<', minus the trimmed whitespace
+                    let end = self.reader.buffer_position();
+                    let raw = self.source.and_then(|xml| xml.get(position..end)).unwrap_or("");
+                    let raw = raw.trim_start_matches([' ','\r','\n','\t']);
+                    match raw.get(..e.escaped().len()) {
+                        Some(text) if text.as_bytes() == e.escaped() => Some(RawField::new(Cow::Borrowed(text.as_bytes()),false)),
+                        _ => Some(RawField::new(Cow::Owned(e.escaped().to_vec()),false)),
+                    }
+                }
+
+                Ok((_,Event::Eof)) => {
+                    let item = self.current.take();
+                    self.read_index = self.current_index;
+                    self.item_end = self.reader.buffer_position();
+                    return if self.fragment { None } else { item.map(Ok) };
+                }
+                Err(quick_xml::Error::EndEventMismatch{ ref expected, .. }) if self.resumed && expected.is_empty() => None,
+                Err(e) => return Some(Err(std::io::Error::other(format!("{:?}",e)))),
+                _ => None,
+            };
+
+            if let (Some(mut node_text),Some(last)) = (node_text,self.current.as_mut()) {
+                self.produced += node_text.as_bytes().len();
+                node_text.namespace = self.active_ns.clone();
+                match self.active {
+                    // text of an uncaptured child, nested elements were skipped above
+                    None => {
+                        let values = self.extension.as_ref().and_then(|name| last.extensions.get_mut(name));
+                        if let Some(value) = values.and_then(|values| values.last_mut()) {
+                            *value = node_text;
+                        }
+                    }
+                    Some(Field::Title) => last.title = node_text,
+                    Some(Field::Link) => last.link = node_text,
+                    Some(Field::Author) => last.author = node_text,
+                    Some(Field::Description) => last.description = node_text,
+                    Some(Field::Content) => last.content = node_text,
+                    Some(Field::Category) => {
+                        let name = node_text.decode().unwrap_or_default();
+                        if let Some(category) = last.categories.last_mut() {
+                            *category = Category::new(&name,self.category_domain.as_deref());
+                        }
+                    }
+                    Some(Field::Guid) => last.guid = node_text,
+                    Some(Field::Publish) => last.publish = node_text,
+                    Some(Field::Updated) => last.updated = node_text,
+                    #[cfg(feature = "podcast")]
+                    Some(Field::Itunes(tag)) => {
+                        let text = node_text.decode().unwrap_or_default();
+                        crate::itunes::set_field(last.podcast.get_or_insert_with(Default::default),tag,&text);
+                    }
+                    Some(Field::Node) => (),
+                }
+            }
+        }
+    }
+}
+
+///
+/// Escaped `href` of a link element that is an alternate, or names none,
+/// `Some` but empty for links to something else
+///
+fn alternate_href(e:&BytesStart)->Option<Vec<u8>>{
+    let mut href = None;
+    let mut alternate = true;
+    for attribute in e.attributes().flatten() {
+        match attribute.key {
+            b"href" => href = Some(attribute.value.into_owned()),
+            b"rel" => alternate = attribute.value.eq_ignore_ascii_case(b"alternate"),
+            _ => (),
+        }
+    }
+    href.map(|href| if alternate { href }else { Vec::new() })
+}
+
+///
+/// Teaser and full body stand in for each other when only one was found,
+/// categories without a name are dropped
+///
+fn fill_content(item:&mut RawRssItem<'_>){
+    item.categories.retain(|category| !category.name.is_empty());
+    if item.content.is_empty() {
+        item.content = item.description.clone();
+    }else if item.description.is_empty() {
+        item.description = item.content.clone();
+    }
+}
+
+/// Rewrite a date to UTC RFC 3339, dates that do not parse are kept
+fn to_utc(dates:&DateParser,field:&mut RawField<'_>){
+    let timestamp = field.decode().ok().and_then(|text| dates.parse(&text));
+    if let Some(timestamp) = timestamp {
+        let namespace = field.namespace.take();
+        *field = RawField::new(Cow::Owned(timestamp.to_rfc3339().into_bytes()),false);
+        field.namespace = namespace;
+    }
+}
+
+impl<'x,B:BufRead> Iterator for RawItems<'x,B>{
+    type Item = Result<RawRssItem<'x>,std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = loop {
+            let mut item = self.read_next();
+            if let Some(Ok(item)) = item.as_mut() {
+                fill_content(item);
+            }
+            if let (Some(dates),Some(Ok(item))) = (&self.dates,item.as_mut()) {
+                to_utc(dates,&mut item.publish);
+                to_utc(dates,&mut item.updated);
+            }
+            match item {
+                Some(Ok(raw)) if self.hooks.has_complete() => match self.hooks.complete(raw) {
+                    Ok(Some(kept)) => break Some(Ok(kept)),
+                    Ok(None) => continue,
+                    // text that fails to decode only costs its own item, like in into_owned
+                    Err(e) => {
+                        self.items += 1;
+                        return Some(Err(e));
+                    }
+                },
+                item => break item,
+            }
+        };
+        // stop after the end of the document or the first error
+        self.done = !matches!(item,Some(Ok(_)));
+        if !self.done {
+            self.items += 1;
+            self.kept.push(self.read_index);
+        }
+        item
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::ErrorKind;
+    use std::time::Duration;
+    use crate::{RssParser,BudgetExceeded,Enclosure};
+
+    #[test]
+    fn future_rss_tag_matcher(){
+        let mut parser = RssParser::new();
+        parser.author_tag = String::from("DC:Creator");
+        parser.set_xml(String::from(
+            r#"<rss><channel><title>Channel</title>
+                <ITEM><Title>Hey!</Title><dc:creator>MeteorCat</dc:creator><author>ignored</author></ITEM>
+                <image><title>Logo</title></image>
+            </channel></rss>"#
+        ));
+        let rss = parser.parse_vec().unwrap();
+        assert_eq!(rss.len(),1);
+        assert_eq!(rss[0].title,"Hey!");
+        assert_eq!(rss[0].author,"MeteorCat");
+    }
+
+    #[test]
+    fn future_rss_tag_namespaces(){
+        let xml = r#"<rss xmlns:dublin="http://purl.org/dc/elements/1.1/" xmlns:dc="urn:not-dublin-core"><channel>
+            <item><title>1</title><dublin:creator>MeteorCat</dublin:creator><dc:creator>ignored</dc:creator></item>
+            <item><title>2</title><creator xmlns="http://purl.org/dc/elements/1.1/">Tom</creator></item>
+            <item><title>3</title><x:id xmlns:x="urn:ids">a-3</x:id></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.author_tag = String::from("dc:creator");
+        parser.guid_tag = String::from("{urn:ids}ID");
+        parser.set_xml(String::from(xml));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!(items.iter().map(|item| item.author.as_str()).collect::<Vec<_>>(),["MeteorCat","Tom",""]);
+        assert_eq!(items[2].guid,"a-3");
+
+        // without a declaration the prefix is taken as written
+        parser.set_xml(String::from("<rss><item><dc:creator>Jerry</dc:creator></item></rss>"));
+        assert_eq!(parser.parse_vec().unwrap()[0].author,"Jerry");
+
+        // an atom feed bound to a prefix
+        parser = RssParser::new();
+        parser.node_tag = String::from("atom:entry");
+        parser.title_tag = String::from("atom:title");
+        parser.set_xml(String::from(
+            r#"<a:feed xmlns:a="http://www.w3.org/2005/Atom"><a:entry><a:title>Hey!</a:title></a:entry></a:feed>"#
+        ));
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"Hey!");
+    }
+
+    #[test]
+    fn future_rss_atom_entries(){
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+            <entry><content>long</content><summary>short</summary><link rel="self" href="a"/></entry>
+            <entry><link href="b"/><link rel="alternate" href="c"/><content>only</content></entry>
+        </feed>"#;
+        let mut parser = RssParser::atom();
+        parser.set_xml(String::from(xml));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!((items[0].description.as_str(),items[0].link.as_str()),("short",""));
+        assert_eq!((items[1].description.as_str(),items[1].link.as_str()),("only","b"));
+
+        parser.content_tag.clear();
+        assert_eq!(parser.parse_vec().unwrap()[1].description,"");
+    }
+
+    #[test]
+    fn future_rss_encoded_content(){
+        let xml = r#"<rss xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>
+            <item><description>Teaser</description><content:encoded><![CDATA[<p>Full <b>story</b></p>]]></content:encoded></item>
+            <item><description>Only a teaser</description></item>
+            <item><content:encoded>Only the body</content:encoded></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(xml));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!((items[0].description.as_str(),items[0].content.as_str()),("Teaser","<p>Full <b>story</b></p>"));
+        assert_eq!((items[1].description.as_str(),items[1].content.as_str()),("Only a teaser","Only a teaser"));
+        assert_eq!((items[2].description.as_str(),items[2].content.as_str()),("Only the body","Only the body"));
+        assert_eq!(parser.parse_borrowed().unwrap()[0].content,"<p>Full <b>story</b></p>");
+    }
+
+    #[test]
+    fn future_rss_item_enclosures(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<rss><channel><enclosure url="https://examples.com/channel.mp3"/>
+                <item><title>1</title><enclosure url="https://examples.com/1.mp3?a=1&amp;b=2" length="1024" type="audio/mpeg"/></item>
+                <item><title>2</title></item>
+            </channel></rss>"#
+        ));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!(items[0].enclosures,[Enclosure::new(Some("https://examples.com/1.mp3?a=1&b=2"),Some("1024"),Some("audio/mpeg"))]);
+        assert!(items[1].enclosures.is_empty());
+        assert!(items[0].extensions.is_empty());
+        assert_eq!(json::parse(&items[0].to_json()).unwrap()["enclosures"][0]["length"],1024);
+        assert!(!items[1].to_json().contains("enclosures"));
+
+        parser = RssParser::atom();
+        parser.set_xml(String::from(
+            r#"<feed><entry><link href="a"/><link rel="enclosure" href="b.ogg" length="1" type="audio/ogg"/></entry></feed>"#
+        ));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!((items[0].link.as_str(),items[0].enclosures[0].url.as_str()),("a","b.ogg"));
+        assert!(items[0].extensions.is_empty());
+    }
+
+    #[test]
+    fn future_rss_item_extensions(){
+        let xml = r#"<rss xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:media="http://search.yahoo.com/mrss/"><channel>
+            <dc:rights>channel</dc:rights>
+            <item>
+                <title>1</title>
+                <dc:subject>Rust</dc:subject><dc:subject><![CDATA[Q&A]]></dc:subject>
+                <media:group><media:title>nested</media:title></media:group>
+                <slash:comments>3</slash:comments>
+                <x:broken>&bogus;</x:broken>
+            </item>
+            <item><title>2</title></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(xml));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!(items[0].extensions.keys().collect::<Vec<_>>(),["dc:subject","media:group","slash:comments","x:broken"]);
+        assert_eq!(items[0].extensions["dc:subject"],["Rust","Q&A"]);
+        assert_eq!(items[0].extensions["media:group"],[""]);
+        assert_eq!(items[0].extensions["x:broken"],["&bogus;"]);
+        assert!(items[1].extensions.is_empty());
+
+        let raw = parser.parse_raw().unwrap();
+        assert_eq!(raw[0].extensions["dc:subject"][0].namespace(),Some("http://purl.org/dc/elements/1.1/"));
+        assert_eq!(json::parse(&items[0].to_json()).unwrap()["extensions"]["slash:comments"][0],"3");
+    }
+
+    #[test]
+    fn future_rss_unknown_elements(){
+        let xml = r#"<rss><channel><ttl>60</ttl>
+            <item><title>1</title><dc:creator>a</dc:creator><media:content url="x"/><enclosure url="y"/></item>
+            <item><title>2</title><dc:creator>b</dc:creator></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(xml));
+        parser.parse_vec().unwrap();
+        assert!(parser.metrics().unknown_elements.is_empty());
+
+        parser.report_unknown = true;
+        parser.parse_vec().unwrap();
+        let unknown = &parser.metrics().unknown_elements;
+        assert_eq!(unknown.len(),2);
+        assert_eq!(unknown["dc:creator"],2);
+        assert_eq!(unknown["media:content"],1);
+    }
+
+    #[test]
+    fn future_rss_parse_deadline(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from("<rss><item><title>Hey!</title></item></rss>"));
+        parser.max_parse_duration = Some(Duration::from_secs(60));
+        assert_eq!(parser.parse_vec().unwrap().len(),1);
+
+        parser.max_parse_duration = Some(Duration::from_secs(0));
+        assert_eq!(parser.parse_vec().unwrap_err().kind(),ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn future_rss_parse_budget(){
+        let xml = "<rss><item><title>Hey!</title></item></rss>";
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(xml));
+        parser.max_parse_bytes = Some(xml.len() + 4);
+        assert_eq!(parser.parse_vec().unwrap().len(),1);
+
+        parser.max_parse_bytes = Some(xml.len() + 3);
+        let error = parser.parse_vec().unwrap_err();
+        assert_eq!(error.kind(),ErrorKind::OutOfMemory);
+        let budget = error.get_ref().and_then(|e| e.downcast_ref::<BudgetExceeded>()).unwrap();
+        assert_eq!(budget.used,xml.len() + 4);
+    }
+}
@@ -0,0 +1,337 @@
+//!
+//! iTunes Podcast Extension
+//!
+//! Podcast feeds carry episode details in the `itunes:` namespace, next to
+//! the regular item fields; [`ItunesItem`] holds them already typed so a
+//! podcast app can show and add up episode lengths and order episodes.
+//!
+
+use std::time::Duration;
+use crate::RssParser;
+use crate::extension::scan_items;
+
+/// &lt;itunes:duration&gt;...&lt;/itunes:duration&gt;
+pub static ITUNES_DURATION_TAG:&str = "itunes:duration";
+
+/// &lt;itunes:episode&gt;...&lt;/itunes:episode&gt;
+pub static ITUNES_EPISODE_TAG:&str = "itunes:episode";
+
+/// &lt;itunes:season&gt;...&lt;/itunes:season&gt;
+pub static ITUNES_SEASON_TAG:&str = "itunes:season";
+
+/// &lt;itunes:author&gt;...&lt;/itunes:author&gt;
+pub static ITUNES_AUTHOR_TAG:&str = "itunes:author";
+
+/// &lt;itunes:image href/&gt;
+pub static ITUNES_IMAGE_TAG:&str = "itunes:image";
+
+/// &lt;itunes:explicit&gt;...&lt;/itunes:explicit&gt;
+pub static ITUNES_EXPLICIT_TAG:&str = "itunes:explicit";
+
+///
+/// iTunes fields of one item, in the order of [`RssParser::parse_vec`]
+///
+/// ```
+/// use std::time::Duration;
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+///             <item><title>Ep. 1</title><itunes:duration>1:02:33</itunes:duration></item>
+///             <item><title>Ep. 2</title><itunes:duration>62:33</itunes:duration></item>
+///         </rss>
+///         "#
+///     ))?;
+///     let total:Duration = parser.parse_itunes()?.iter().filter_map(|item| item.duration).sum();
+///     assert_eq!(total,Duration::from_secs(3753 + 3753));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct ItunesItem{
+    /// Episode length, `None` when missing or not understood
+    pub duration:Option<Duration>,
+    /// `itunes:episode`, else taken from the title, see [`ItunesItem::numbers_from_title`]
+    pub episode:Option<u32>,
+    /// `itunes:season`, else taken from the title
+    pub season:Option<u32>,
+    pub author:Option<String>,
+    /// Episode artwork, the `href` of `itunes:image`
+    pub image:Option<String>,
+    /// `None` when the feed does not say or says something else than
+    /// `true`, `yes`, `explicit`, `false`, `no` or `clean`
+    pub explicit:Option<bool>,
+}
+
+impl ItunesItem{
+
+    pub fn parse_explicit(text:&str)->Option<bool>{
+        match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "explicit" => Some(true),
+            "false" | "no" | "clean" => Some(false),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Read `3600`, `62:33` or `1:02:33`, fractions of a second are kept;
+    /// minutes and seconds below the leading part must stay under 60
+    ///
+    pub fn parse_duration(text:&str)->Option<Duration>{
+        let parts = text.trim().split(':').collect::<Vec<_>>();
+        if parts.len() > 3 {
+            return None;
+        }
+        let (last,leading) = parts.split_last()?;
+        let seconds = match last.split_once('.') {
+            Some((whole,fraction)) if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+                whole_number(whole)? as f64 + format!("0.{}",fraction).parse::<f64>().ok()?
+            }
+            Some(_) => return None,
+            None => whole_number(last)? as f64,
+        };
+        if !leading.is_empty() && seconds >= 60.0 {
+            return None;
+        }
+        let mut total:u64 = 0;
+        for (i,part) in leading.iter().enumerate() {
+            let value = whole_number(part)?;
+            // hours lead h:mm:ss, their minutes stay under an hour
+            if i > 0 && value >= 60 {
+                return None;
+            }
+            total = total.checked_mul(60)?.checked_add(value)?;
+        }
+        Duration::try_from_secs_f64(total.checked_mul(60)? as f64 + seconds).ok()
+    }
+
+    ///
+    /// Season and episode written in a title: `S02E05`, `Season 2`,
+    /// `Episode 123`, `Ep. 123` or `#123`, the first of each wins
+    ///
+    pub fn numbers_from_title(title:&str)->(Option<u32>,Option<u32>){
+        let lower = title.to_ascii_lowercase();
+        let (mut season,mut episode) = (None,None);
+        let mut previous = None;
+        for (i,c) in lower.char_indices() {
+            let inside_word = previous.is_some_and(|previous:char| previous.is_ascii_alphanumeric());
+            previous = Some(c);
+            if inside_word {
+                continue;
+            }
+            let rest = &lower[i..];
+            if let Some(after) = rest.strip_prefix("season") {
+                season = season.or(number_after(after,true).map(|(n,_)| n));
+            }else if let Some(after) = rest.strip_prefix("episode").or_else(|| rest.strip_prefix("ep")) {
+                episode = episode.or(number_after(after,true).map(|(n,_)| n));
+            }else if let Some(after) = rest.strip_prefix('#') {
+                episode = episode.or(number_after(after,false).map(|(n,_)| n));
+            }else if let Some((s,after)) = rest.strip_prefix('s').and_then(|after| number_after(after,false)) {
+                let after = after.trim_start_matches([' ','-','.']);
+                if let Some((e,_)) = after.strip_prefix('e').and_then(|after| number_after(after,false)) {
+                    season = season.or(Some(s));
+                    episode = episode.or(Some(e));
+                }
+            }
+        }
+        (season,episode)
+    }
+}
+
+///
+/// Store the text of the element `tag`, one of the lowercase `ITUNES_*_TAG`,
+/// false for any other tag
+///
+pub(crate) fn set_field(item:&mut ItunesItem,tag:&str,text:&str)->bool{
+    match tag {
+        tag if tag == ITUNES_DURATION_TAG => item.duration = ItunesItem::parse_duration(text),
+        tag if tag == ITUNES_EPISODE_TAG => item.episode = text.trim().parse().ok(),
+        tag if tag == ITUNES_SEASON_TAG => item.season = text.trim().parse().ok(),
+        tag if tag == ITUNES_AUTHOR_TAG => item.author = Some(text.trim().to_string()).filter(|author| !author.is_empty()),
+        tag if tag == ITUNES_IMAGE_TAG => item.image = Some(text.trim().to_string()).filter(|image| !image.is_empty()),
+        tag if tag == ITUNES_EXPLICIT_TAG => item.explicit = ItunesItem::parse_explicit(text),
+        _ => return false,
+    }
+    true
+}
+
+///
+/// Take season and episode the tags left out from `title`
+///
+pub(crate) fn numbers_from(item:&mut ItunesItem,title:&str){
+    if item.season.is_none() || item.episode.is_none() {
+        let (season,episode) = ItunesItem::numbers_from_title(title);
+        item.season = item.season.or(season);
+        item.episode = item.episode.or(episode);
+    }
+}
+
+/// Number at the start of `text`, after spaces, dots and colons when `skip`,
+/// with the text following it
+fn number_after(text:&str,skip:bool)->Option<(u32,&str)>{
+    let text = if skip { text.trim_start_matches([' ','.',':']) } else { text };
+    let end = text.find(|c:char| !c.is_ascii_digit()).unwrap_or(text.len());
+    if end == 0 || text[end..].starts_with(|c:char| c.is_ascii_alphabetic()) && !text[end..].starts_with(['e','E']) {
+        return None;
+    }
+    Some((text[..end].parse().ok()?,&text[end..]))
+}
+
+fn whole_number(text:&str)->Option<u64>{
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+///
+/// iTunes fields of every item of `xml`, numbers the tags leave out are
+/// taken from the title
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<ItunesItem>,std::io::Error>{
+    let title = parser.title_tag.to_ascii_lowercase();
+    let items = scan_items(parser,xml,"RssParser::parse_itunes",|(item,item_title):&mut (ItunesItem,String),element| {
+        if element.name == ITUNES_IMAGE_TAG {
+            set_field(item,ITUNES_IMAGE_TAG,element.attribute("href").unwrap_or(""));
+        }else if !set_field(item,&element.name,&element.text) && element.name == title {
+            *item_title = element.text.clone();
+        }
+    })?;
+    Ok(items.into_iter().map(|(mut item,title)| {
+        numbers_from(&mut item,&title);
+        item
+    }).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{RssParser,ItunesItem};
+
+    #[test]
+    fn future_rss_itunes_duration(){
+        for (text,seconds) in [
+            ("3600",3600.0),
+            ("62:33",3753.0),
+            ("1:02:33",3753.0),
+            (" 00:45 ",45.0),
+            ("90.5",90.5),
+            ("1:00:00.25",3600.25),
+        ] {
+            assert_eq!(ItunesItem::parse_duration(text),Some(Duration::from_secs_f64(seconds)),"{}",text);
+        }
+        for text in ["","1:2:3:4","1:60:00","5:75","-3","1h","12.","1::2","999999999999999999:00:00","99999999999999999999"] {
+            assert_eq!(ItunesItem::parse_duration(text),None,"{}",text);
+        }
+    }
+
+    #[test]
+    fn future_rss_itunes_items(){
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+                    <channel>
+                        <itunes:duration>1</itunes:duration>
+                        <item><title>1</title><itunes:duration>25:00</itunes:duration></item>
+                        <item><title>2</title></item>
+                        <item><title>3</title><itunes:duration><![CDATA[soon]]></itunes:duration></item>
+                    </channel>
+                </rss>
+        "#)).unwrap();
+        let items = parser.parse_itunes().unwrap();
+        assert_eq!(items.len(),parser.parse_borrowed().unwrap().len());
+        assert_eq!(items[0].duration,Some(Duration::from_secs(1500)));
+        assert_eq!(items[1].duration,None);
+        assert_eq!(items[2].duration,None);
+    }
+
+    #[test]
+    fn future_rss_itunes_details(){
+        let xml = r#"<rss xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd"><channel>
+            <itunes:image href="https://examples.com/show.jpg"/>
+            <item>
+                <title>Ep. 3</title>
+                <itunes:author> Tom &amp; Jerry </itunes:author>
+                <itunes:image href="https://examples.com/3.jpg?size=1&amp;v=2"/>
+                <itunes:explicit>Yes</itunes:explicit>
+            </item>
+            <item><title>Bonus</title><itunes:explicit>maybe</itunes:explicit><itunes:author></itunes:author></item>
+        </channel></rss>"#;
+        let parser = RssParser::from_str(String::from(xml)).unwrap();
+        let items = parser.parse_itunes().unwrap();
+        assert_eq!(items[0],ItunesItem{
+            episode:Some(3),
+            author:Some(String::from("Tom & Jerry")),
+            image:Some(String::from("https://examples.com/3.jpg?size=1&v=2")),
+            explicit:Some(true),
+            ..ItunesItem::default()
+        });
+        assert_eq!((items[1].author.as_deref(),items[1].explicit),(None,None));
+        assert_eq!(ItunesItem::parse_explicit(" clean"),Some(false));
+    }
+
+    #[cfg(feature = "podcast")]
+    #[test]
+    fn future_rss_itunes_podcast(){
+        let xml = r#"<rss><channel>
+            <item>
+                <title>S02E05 Async</title>
+                <itunes:duration>1:00</itunes:duration>
+                <itunes:image href="https://examples.com/5.jpg"/>
+                <itunes:episode>6</itunes:episode>
+            </item>
+            <item><title>Ep. 7</title></item>
+            <item><title>Épisode 3</title><itunes:duration>90</itunes:duration></item>
+        </channel></rss>"#;
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(xml));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!(items[0].podcast,Some(ItunesItem{
+            duration:Some(Duration::from_secs(60)),
+            season:Some(2),
+            episode:Some(6),
+            image:Some(String::from("https://examples.com/5.jpg")),
+            ..ItunesItem::default()
+        }));
+        // only items with itunes tags get the extension
+        assert_eq!(items[1].podcast,None);
+        assert_eq!(items[2].podcast.as_ref().and_then(|podcast| podcast.duration),Some(Duration::from_secs(90)));
+        assert_eq!(parser.parse_borrowed().unwrap()[0].podcast,items[0].podcast);
+    }
+
+    #[test]
+    fn future_rss_itunes_numbers(){
+        for (title,numbers) in [
+            ("Rustacean Station S02E05: Async",(Some(2),Some(5))),
+            ("s1 e12 - pilot",(Some(1),Some(12))),
+            ("Season 3, Episode 7",(Some(3),Some(7))),
+            ("Ep. 123 - Interview",(None,Some(123))),
+            ("EP42: news",(None,Some(42))),
+            ("Weekly #99",(None,Some(99))),
+            ("Epic news of 2020",(None,None)),
+            ("Top 5 episodes",(None,None)),
+            ("S3 recap",(None,None)),
+            ("日本 第5回",(None,None)),
+            ("Épisode 3 – café #4",(None,Some(4))),
+            ("第二季 S02E03",(Some(2),Some(3))),
+        ] {
+            assert_eq!(ItunesItem::numbers_from_title(title),numbers,"{}",title);
+        }
+
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0">
+                    <item><title>S01E09 Finale</title><itunes:episode>10</itunes:episode></item>
+                    <item><title>Bonus</title><itunes:season>2</itunes:season><itunes:episode>x</itunes:episode></item>
+                    <item><title>Épisode #3</title><itunes:duration>1:00</itunes:duration></item>
+                </rss>
+        "#)).unwrap();
+        let items = parser.parse_itunes().unwrap();
+        assert_eq!((items[0].season,items[0].episode),(Some(1),Some(10)));
+        assert_eq!((items[1].season,items[1].episode),(Some(2),None));
+        assert_eq!(items[2].episode,Some(3));
+    }
+}
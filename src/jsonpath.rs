@@ -0,0 +1,279 @@
+//!
+//! JSONPath Mappings
+//!
+//! JSON Feed and the JSON APIs that stand in for feeds name their fields
+//! differently; a [`JsonMapping`] locates the items and every item field
+//! with a JSONPath expression, so such endpoints read into [`RssItem`]s.
+//!
+
+use std::convert::TryFrom;
+use json::JsonValue;
+use crate::RssItem;
+use crate::feed::text_of;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+enum Step{
+    Key(String),
+    /// Negative indexes count from the end
+    Index(i64),
+    Wildcard,
+}
+
+///
+/// JSONPath expression: `$` followed by `.key`, `['key']`, `[0]`, `[-1]`,
+/// `.*` or `[*]` steps
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct JsonPath{
+    steps:Vec<Step>,
+}
+
+impl JsonPath{
+
+    /// Compile `path`, `InvalidInput` when it is no supported expression
+    pub fn parse(path:&str)->Result<Self,std::io::Error>{
+        let invalid = |reason:&str| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Failed by JsonPath::parse: {} in {:?}",reason,path)
+        );
+        // a bare key, as in `title.text`, starts at the root too
+        let path = path.trim();
+        let normalized = match path.strip_prefix('$') {
+            Some(rest) => rest.to_string(),
+            None if path.starts_with('[') => path.to_string(),
+            None => format!(".{}",path),
+        };
+        let mut rest = normalized.as_str();
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.','[']).unwrap_or(after.len());
+                steps.push(match &after[..end] {
+                    "" => return Err(invalid("empty key")),
+                    "*" => Step::Wildcard,
+                    key => Step::Key(key.to_string()),
+                });
+                rest = &after[end..];
+            }else if let Some(after) = rest.strip_prefix('[') {
+                let end = match after.chars().next() {
+                    Some(quote) if quote == '\'' || quote == '"' => after[1..].find(quote).map(|end| end + 2),
+                    _ => after.find(']'),
+                }.ok_or_else(|| invalid("unclosed ["))?;
+                let inner = after[..end].trim();
+                steps.push(match inner {
+                    "*" => Step::Wildcard,
+                    quoted if quoted.len() >= 2 && (quoted.starts_with('\'') || quoted.starts_with('"')) => {
+                        Step::Key(quoted[1..quoted.len() - 1].to_string())
+                    }
+                    index => Step::Index(index.parse().map_err(|_| invalid("invalid index"))?),
+                });
+                rest = after[end..].strip_prefix(']').ok_or_else(|| invalid("unclosed ["))?;
+            }else {
+                return Err(invalid("expected . or ["));
+            }
+        }
+        Ok(Self{ steps })
+    }
+
+    /// Values `self` points at inside `root`, in document order
+    pub fn select<'v>(&self,root:&'v JsonValue)->Vec<&'v JsonValue>{
+        let mut values = vec![root];
+        for step in self.steps.iter() {
+            values = values.into_iter()
+                .flat_map(|value| -> Vec<&JsonValue> {
+                    match (step,value) {
+                        (Step::Key(key),JsonValue::Object(object)) => object.get(key).into_iter().collect(),
+                        (Step::Index(index),JsonValue::Array(array)) => {
+                            let index = if *index < 0 { array.len() as i64 + index }else { *index };
+                            usize::try_from(index).ok().and_then(|index| array.get(index)).into_iter().collect()
+                        }
+                        (Step::Wildcard,JsonValue::Array(array)) => array.iter().collect(),
+                        (Step::Wildcard,JsonValue::Object(object)) => object.iter().map(|(_,value)| value).collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        values
+    }
+}
+
+impl std::str::FromStr for JsonPath{
+    type Err = std::io::Error;
+
+    fn from_str(path:&str)->Result<Self,Self::Err>{
+        Self::parse(path)
+    }
+}
+
+///
+/// Where the items and their fields sit in a JSON document
+///
+/// `items` is evaluated on the document, the field paths on each item;
+/// a field takes the first of its paths that yields a string or number.
+/// The default reads JSON Feed 1.0 and 1.1.
+///
+/// ```
+/// use future_rss::JsonMapping;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mapping = JsonMapping::new("$.data.children[*].data")?
+///         .field("title","$.title")?
+///         .field("link","$.url")?
+///         .field("author","$.author")?
+///         .field("publish","$.created_utc")?;
+///     let items = mapping.parse(r#"{"data":{"children":[
+///         {"kind":"t3","data":{"title":"Hey!","url":"https://examples.com/1","author":"MeteorCat","created_utc":1590678000}}
+///     ]}}"#)?;
+///     assert_eq!(items[0].title,"Hey!");
+///     assert_eq!(items[0].publish,"1590678000");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct JsonMapping{
+    pub items:JsonPath,
+    pub title:Vec<JsonPath>,
+    pub link:Vec<JsonPath>,
+    pub author:Vec<JsonPath>,
+    pub description:Vec<JsonPath>,
+    pub content:Vec<JsonPath>,
+    pub guid:Vec<JsonPath>,
+    pub publish:Vec<JsonPath>,
+    pub updated:Vec<JsonPath>,
+}
+
+impl Default for JsonMapping{
+    fn default() -> Self {
+        let paths = |paths:&[&str]| paths.iter().map(|path| JsonPath::parse(path).expect("valid JSON Feed path")).collect();
+        Self{
+            items:JsonPath::parse("$.items[*]").expect("valid JSON Feed path"),
+            title:paths(&["$.title"]),
+            link:paths(&["$.url","$.external_url"]),
+            author:paths(&["$.authors[0].name","$.author.name"]),
+            description:paths(&["$.content_html","$.content_text","$.summary"]),
+            content:paths(&["$.content_html","$.content_text"]),
+            guid:paths(&["$.id"]),
+            publish:paths(&["$.date_published"]),
+            updated:paths(&["$.date_modified"]),
+        }
+    }
+}
+
+impl JsonMapping{
+
+    /// Mapping with items at `items` and no field paths yet
+    pub fn new(items:&str)->Result<Self,std::io::Error>{
+        Ok(Self{
+            items:JsonPath::parse(items)?,
+            title:Vec::new(),
+            link:Vec::new(),
+            author:Vec::new(),
+            description:Vec::new(),
+            content:Vec::new(),
+            guid:Vec::new(),
+            publish:Vec::new(),
+            updated:Vec::new(),
+        })
+    }
+
+    ///
+    /// Add `path` to the paths of `field`, one of the [`RssItem`] field
+    /// names; `InvalidInput` for other names or a broken path
+    ///
+    pub fn field(mut self,field:&str,path:&str)->Result<Self,std::io::Error>{
+        let paths = match field {
+            "title" => &mut self.title,
+            "link" => &mut self.link,
+            "author" => &mut self.author,
+            "description" => &mut self.description,
+            "content" => &mut self.content,
+            "guid" => &mut self.guid,
+            "publish" => &mut self.publish,
+            "updated" => &mut self.updated,
+            _ => return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Failed by JsonMapping::field: unknown field {}",field)
+            )),
+        };
+        paths.push(JsonPath::parse(path)?);
+        Ok(self)
+    }
+
+    /// Items of the JSON document `text`
+    pub fn parse(&self,text:&str)->Result<Vec<RssItem>,std::io::Error>{
+        let root = json::parse(text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by JsonMapping::parse: {}",e)))?;
+        Ok(self.items.select(&root).into_iter().map(|item| self.item(item)).collect())
+    }
+
+    fn item(&self,item:&JsonValue)->RssItem{
+        let first = |paths:&[JsonPath]| paths.iter()
+            .flat_map(|path| path.select(item))
+            .map(text_of)
+            .find(|text| !text.is_empty())
+            .unwrap_or_default();
+        let description = first(&self.description);
+        let content = match first(&self.content) {
+            content if content.is_empty() => description.clone(),
+            content => content,
+        };
+        RssItem{
+            title:first(&self.title),
+            link:first(&self.link),
+            author:first(&self.author),
+            description,
+            content,
+            guid:first(&self.guid),
+            publish:first(&self.publish),
+            updated:first(&self.updated),
+            ..RssItem::default()
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonMapping,JsonPath};
+
+    #[test]
+    fn future_rss_json_path(){
+        let root = json::parse(r#"{"a":{"b c":[1,2,{"d":"x"}]},"e":[{"f":1},{"f":2}]}"#).unwrap();
+        let select = |path:&str| JsonPath::parse(path).unwrap().select(&root).into_iter().map(|v| v.dump()).collect::<Vec<_>>();
+        assert_eq!(select("$.a['b c'][0]"),["1"]);
+        assert_eq!(select("$.a[\"b c\"][-1].d"),["\"x\""]);
+        assert_eq!(select("$.e[*].f"),["1","2"]);
+        assert_eq!(select("$.*.f"),Vec::<String>::new());
+        assert_eq!(select("e[1].f"),["2"]);
+        assert_eq!(select("$"),[root.dump()]);
+        assert!(select("$.missing[0]").is_empty());
+        for broken in ["$.","$[","$[x]","$..a","$a"] {
+            assert!(JsonPath::parse(broken).is_err(),"{}",broken);
+        }
+    }
+
+    #[test]
+    fn future_rss_json_mapping(){
+        let items = JsonMapping::default().parse(r#"{
+            "version": "https://jsonfeed.org/version/1",
+            "items": [
+                { "id": 1, "title": "Hey!", "external_url": "https://examples.com/1", "author": { "name": "MeteorCat" },
+                  "content_text": "hello.world!", "date_published": "2020-05-28T15:00:00Z" },
+                { "id": "2", "authors": [{ "name": "Tom" }], "content_html": "<p>hi</p>", "summary": "hi" }
+            ]
+        }"#).unwrap();
+        assert_eq!(items.len(),2);
+        assert_eq!((items[0].guid.as_str(),items[0].link.as_str(),items[0].author.as_str()),("1","https://examples.com/1","MeteorCat"));
+        assert_eq!(items[0].description,"hello.world!");
+        assert_eq!((items[1].author.as_str(),items[1].description.as_str()),("Tom","<p>hi</p>"));
+
+        let mapping = JsonMapping::new("$.results[*]").unwrap()
+            .field("title","$.headline.main").unwrap()
+            .field("title","$.name").unwrap();
+        let items = mapping.parse(r#"{"results":[{"headline":{"main":""},"name":"fallback"}]}"#).unwrap();
+        assert_eq!(items[0].title,"fallback");
+        assert!(JsonMapping::new("$").unwrap().field("body","$.x").is_err());
+        assert!(mapping.parse("<rss/>").is_err());
+    }
+}
@@ -15,35 +15,245 @@
 //! ```
 
 use std::io::prelude::*;
+use std::io::BufRead;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize,Ordering};
 use std::fs::File;
 use quick_xml::Reader;
-use quick_xml::events::Event;
+use crate::items::{RawItems,Scratch};
+#[cfg(feature = "stream")]
+use crate::stream::Sender;
 use json::{object,array};
 
+/// `tracing::debug!` when the `tracing` feature is enabled, nothing otherwise
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature="tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+/// Enter a `tracing::debug_span!` until the end of the enclosing block
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature="tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+mod diff;
+mod cache;
+#[cfg(all(feature = "redis",not(target_arch = "wasm32")))]
+mod redis;
+#[cfg(all(feature = "mqtt",not(target_arch = "wasm32")))]
+mod mqtt;
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+mod watcher;
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+mod batch;
+mod schedule;
+mod decode;
+mod raw;
+mod items;
+#[cfg(feature = "stream")]
+mod stream;
+mod intern;
+mod append;
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+mod pipeline;
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+mod notify;
+mod cancel;
+mod runtime;
+mod fetcher;
+mod enrich;
+mod robots;
+mod normalize;
+mod date;
+mod author;
+mod extension;
+mod itunes;
+mod category;
+mod enclosure;
+mod links;
+mod media;
+mod cloud;
+mod channel;
+mod feed;
+mod recover;
+mod html;
+mod query;
+mod select;
+mod jsonpath;
+mod fields;
+mod hooks;
+mod transform;
+mod retry;
+mod config;
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+mod network;
+#[cfg(all(feature = "tls-pinning",not(target_arch = "wasm32")))]
+mod pinning;
+#[cfg(feature="metrics")]
+mod metrics;
+#[cfg(all(feature = "mmap",unix))]
+mod mmap;
+#[cfg(any(test,all(feature = "test-util",not(target_arch = "wasm32"))))]
+mod mock;
+pub use crate::diff::{FeedDiff,FeedDelta,FeedEvent};
+pub use crate::cache::{CacheBackend,SeenStore,MemoryCache,CACHE_KEY_PREFIX};
+#[cfg(all(feature = "redis",not(target_arch = "wasm32")))]
+pub use crate::redis::{RedisCache,RedisSeenStore,REDIS_DEFAULT_PORT,REDIS_DEFAULT_TIMEOUT,REDIS_MAX_BULK_LENGTH};
+#[cfg(all(feature = "mqtt",not(target_arch = "wasm32")))]
+pub use crate::mqtt::{MqttSink,MQTT_DEFAULT_PORT,MQTT_DEFAULT_TOPIC,MQTT_DEFAULT_TIMEOUT,MQTT_MAX_REMAINING_LENGTH};
+#[cfg(all(feature = "test-util",not(target_arch = "wasm32")))]
+pub use crate::mock::{MockServer,Fixture,Malformed,fixture_items};
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+pub use crate::watcher::{FeedWatcher,PollOutcome};
+pub use crate::schedule::{AdaptiveSchedule,SCHEDULE_HISTORY_LEN,SCHEDULE_DEFAULT_MIN,SCHEDULE_DEFAULT_MAX,SCHEDULE_DEFAULT_INITIAL};
+#[cfg(all(feature = "mmap",unix))]
+pub use crate::mmap::MappedFile;
+pub use crate::decode::{DecodeReader,DECODE_DEFAULT_CHUNK};
+pub use crate::raw::{RawField,RawRssItem};
+pub use crate::items::{ParseMetrics,BudgetExceeded};
+#[cfg(feature = "stream")]
+pub use crate::stream::ItemStream;
+#[cfg(all(feature = "http",feature = "stream",not(target_arch = "wasm32")))]
+pub use crate::stream::EventStream;
+pub use crate::intern::{StringPool,SharedRssItem};
+pub use crate::append::AppendParser;
+pub use crate::cancel::CancelToken;
+pub use crate::fetcher::{HttpFetcher,HttpResponse,RequestOptions};
+pub use crate::enrich::{PageMeta,ENRICH_DEFAULT_MAX_BYTES};
+#[cfg(all(feature = "tls-pinning",not(target_arch = "wasm32")))]
+pub use crate::pinning::PinnedFetcher;
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+pub use crate::network::{NetworkSettings,ProxyMode,ENV_USER_AGENT,ENV_TIMEOUT,ENV_CONNECT_TIMEOUT,NETWORK_DEFAULT_TIMEOUT};
+pub use crate::config::{Config,FeedConfig};
+pub use crate::normalize::{UrlNormalizer,URL_TRACKING_PARAMS};
+pub use crate::date::{Timestamp,DateParser};
+pub use crate::author::Author;
+pub use crate::category::{Category,RSS_DEFAULT_CATEGORY_TAG};
+pub use crate::enclosure::{Enclosure,EnclosureIssue,MediaType,RSS_DEFAULT_ENCLOSURE_TAG};
+pub use crate::links::{ItemLink,LinkRole,WFW_COMMENT_RSS_TAG};
+pub use crate::media::{MediaExtension,MediaGroup,MediaContent,MediaThumbnail,MEDIA_CONTENT_TAG,MEDIA_THUMBNAIL_TAG,MEDIA_GROUP_TAG};
+pub use crate::cloud::{RssCloud,RSS_DEFAULT_CLOUD_TAG};
+pub use crate::channel::Channel;
+pub use crate::feed::{Feed,Entry,FeedFormat};
+pub use crate::recover::{RecoveredItems,ItemError};
+pub use crate::html::extract_feed;
+pub use crate::query::Query;
+pub use crate::select::Selector;
+pub use crate::jsonpath::{JsonPath,JsonMapping};
+pub use crate::fields::FieldMapping;
+pub use crate::transform::Transformer;
+pub use crate::retry::{RetryPolicy,RETRY_DEFAULT_ATTEMPTS,RETRY_DEFAULT_BACKOFF,RETRY_DEFAULT_MAX_BACKOFF};
+pub use crate::itunes::{ItunesItem,ITUNES_AUTHOR_TAG,ITUNES_DURATION_TAG,ITUNES_EPISODE_TAG,ITUNES_EXPLICIT_TAG,ITUNES_IMAGE_TAG,ITUNES_SEASON_TAG};
+pub use crate::robots::{RobotsTxt,PoliteFetcher,ROBOTS_DEFAULT_USER_AGENT,ROBOTS_MAX_CRAWL_DELAY,ROBOTS_DEFAULT_FAILURE_RETRY};
+#[cfg(feature = "hyper-backend")]
+pub use crate::fetcher::HyperFetcher;
+#[cfg(feature="metrics")]
+pub use crate::metrics::{metrics,AggregatorMetrics,METRICS_PARSE_BUCKETS};
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+pub use crate::pipeline::{Pipeline,PipelineReport,RssItemSink,PIPELINE_DEFAULT_WORKERS,PIPELINE_DEFAULT_CAPACITY};
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+pub use crate::notify::{WebhookNotifier,Webhook,TELEGRAM_DEFAULT_API,DISCORD_MAX_CONTENT};
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+pub use crate::batch::{BatchRefresh,RefreshSummary,BATCH_DEFAULT_CONCURRENCY,BATCH_DEFAULT_PER_HOST};
+
 /// &lt;item&gt;&lt;/item&gt;
-pub static RSS_DEFAULT_NODE_TAG:&'static str = "item";
+pub static RSS_DEFAULT_NODE_TAG:&str = "item";
 
 /// &lt;title&gt;...&lt;/title&gt;
-pub static RSS_DEFAULT_TITLE_TAG:&'static str = "title";
+pub static RSS_DEFAULT_TITLE_TAG:&str = "title";
 
 /// &lt;link&gt;...&lt;/link&gt;
-pub static RSS_DEFAULT_LINK_TAG:&'static str = "link";
+pub static RSS_DEFAULT_LINK_TAG:&str = "link";
 
 /// &lt;author&gt;...&lt;/author&gt;
-pub static RSS_DEFAULT_AUTHOR_TAG:&'static str = "author";
+pub static RSS_DEFAULT_AUTHOR_TAG:&str = "author";
 
 /// &lt;description&gt;...&lt;/description&gt;
-pub static RSS_DEFAULT_DESC_TAG:&'static str = "description";
+pub static RSS_DEFAULT_DESC_TAG:&str = "description";
+
+/// &lt;content:encoded&gt;...&lt;/content:encoded&gt;
+pub static RSS_DEFAULT_CONTENT_TAG:&str = "content:encoded";
 
 /// &lt;guid&gt;...&lt;/guid&gt;
-pub static RSS_DEFAULT_GUID_TAG:&'static str = "guid";
+pub static RSS_DEFAULT_GUID_TAG:&str = "guid";
 
 /// &lt;pubDate&gt;...&lt;/pubDate&gt;
-pub static RSS_DEFAULT_PUBLISH_TAG:&'static str = "pubDate";
+pub static RSS_DEFAULT_PUBLISH_TAG:&str = "pubDate";
+
+/// &lt;atom:updated&gt;...&lt;/atom:updated&gt;, Atom feeds use `updated`
+pub static RSS_DEFAULT_UPDATED_TAG:&str = "atom:updated";
+
+/// Bytes read from a response or file at a time
+pub static RSS_DEFAULT_READ_BUFFER:usize = 8 * 1024;
+
+/// Bytes reserved up front for a single xml event
+pub static RSS_DEFAULT_EVENT_BUFFER:usize = 1024;
+
+/// Prefixes feeds conventionally use, with the namespace uri each stands for
+pub static XML_DEFAULT_NAMESPACES:[(&str,&str);9] = [
+    ("atom","http://www.w3.org/2005/Atom"),
+    ("content","http://purl.org/rss/1.0/modules/content/"),
+    ("dc","http://purl.org/dc/elements/1.1/"),
+    ("dcterms","http://purl.org/dc/terms/"),
+    ("itunes","http://www.itunes.com/dtds/podcast-1.0.dtd"),
+    ("media","http://search.yahoo.com/mrss/"),
+    ("rdf","http://www.w3.org/1999/02/22-rdf-syntax-ns#"),
+    ("slash","http://purl.org/rss/1.0/modules/slash/"),
+    ("wfw","http://wellformedweb.org/CommentAPI/"),
+];
 
 /// Check &lt;xml&gt; and &gt;rss&lt;
-pub static XML_DEFAULT_TAG:&'static str = "xml";
-pub static RSS_DEFAULT_TAG:&'static str = "rss";
+pub static XML_DEFAULT_TAG:&str = "xml";
+pub static RSS_DEFAULT_TAG:&str = "rss";
+
+/// &lt;urlset&gt;&lt;/urlset&gt;, root of a sitemap
+pub static SITEMAP_DEFAULT_TAG:&str = "urlset";
+
+/// &lt;url&gt;&lt;/url&gt;
+pub static SITEMAP_DEFAULT_NODE_TAG:&str = "url";
+
+/// &lt;loc&gt;...&lt;/loc&gt;
+pub static SITEMAP_DEFAULT_LINK_TAG:&str = "loc";
+
+/// &lt;lastmod&gt;...&lt;/lastmod&gt;
+pub static SITEMAP_DEFAULT_PUBLISH_TAG:&str = "lastmod";
+
+/// &lt;feed&gt;&lt;/feed&gt;, root of an Atom feed
+pub static ATOM_DEFAULT_TAG:&str = "feed";
+
+/// &lt;entry&gt;&lt;/entry&gt;
+pub static ATOM_DEFAULT_NODE_TAG:&str = "entry";
+
+/// &lt;name&gt;...&lt;/name&gt; of the entry's &lt;author&gt;
+pub static ATOM_DEFAULT_AUTHOR_TAG:&str = "name";
+
+/// &lt;summary&gt;...&lt;/summary&gt;
+pub static ATOM_DEFAULT_DESC_TAG:&str = "summary";
+
+/// &lt;content&gt;...&lt;/content&gt;, the description of entries without a summary
+pub static ATOM_DEFAULT_CONTENT_TAG:&str = "content";
+
+/// &lt;dc:creator&gt;...&lt;/dc:creator&gt; of RSS 1.0 items
+pub static RDF_DEFAULT_AUTHOR_TAG:&str = "dc:creator";
+
+/// &lt;dc:date&gt;...&lt;/dc:date&gt; of RSS 1.0 items
+pub static RDF_DEFAULT_PUBLISH_TAG:&str = "dc:date";
+
+/// &lt;id&gt;...&lt;/id&gt;
+pub static ATOM_DEFAULT_GUID_TAG:&str = "id";
+
+/// &lt;published&gt;...&lt;/published&gt;
+pub static ATOM_DEFAULT_PUBLISH_TAG:&str = "published";
+
+/// &lt;updated&gt;...&lt;/updated&gt;
+pub static ATOM_DEFAULT_UPDATED_TAG:&str = "updated";
 
 ///
 /// Rss Item Node
@@ -55,15 +265,29 @@ pub static RSS_DEFAULT_TAG:&'static str = "rss";
 ///     println!("{:?}",item);
 /// }
 /// ```
-#[derive(Debug)]
+#[derive(Debug,Clone,PartialEq,Default)]
 #[allow(dead_code)]
 pub struct RssItem{
     pub title: String,
     pub link: String,
     pub author: String,
     pub description: String,
+    /// Full body such as `<content:encoded>`, the description when the item has none
+    pub content: String,
     pub guid: String,
     pub publish: String,
+    /// Last edit, empty when the feed only tells when the item was published
+    pub updated: String,
+    /// Attached files, `<enclosure/>` or Atom's `<link rel="enclosure"/>`
+    pub enclosures: Vec<Enclosure>,
+    /// Every `<category>`, RSS `domain` or Atom `term` and `scheme` included
+    pub categories: Vec<Category>,
+    /// Text of the item's other elements by name, e.g. `dc:subject`, see
+    /// [`RawRssItem::extensions`]
+    pub extensions: BTreeMap<String,Vec<String>>,
+    /// `itunes:` fields, `None` for items without any
+    #[cfg(feature = "podcast")]
+    pub podcast: Option<ItunesItem>,
 }
 
 ///
@@ -138,30 +362,196 @@ pub struct RssItem{
 ///     Ok(())
 /// }
 /// ```
-#[derive(Debug)]
+#[derive(Debug,Clone)]
 #[allow(dead_code)]
 pub struct RssParser{
     xml:String,
+    scratch:Scratch,
     pub node_tag:String,
     pub title_tag:String,
     pub link_tag:String,
     pub author_tag:String,
     pub description_tag:String,
+    /// Full body of an item, read into [`RssItem::content`] and into the
+    /// description of items without one; empty for none
+    pub content_tag:String,
     pub guid_tag:String,
     pub publish_tag:String,
+    pub updated_tag:String,
+    /// Namespace uri of each lowercase prefix used in the tags above, see
+    /// [`XML_DEFAULT_NAMESPACES`]. A tag such as `dc:creator` then matches
+    /// its element whatever prefix the feed binds the namespace to, and
+    /// `{uri}name` matches by uri alone; prefixes a feed never declares
+    /// are still matched as written
+    pub namespaces:BTreeMap<String,String>,
+    /// Read buffer size for fetched bodies, see [`RSS_DEFAULT_READ_BUFFER`]
+    pub read_buffer:usize,
+    /// Event buffer size reserved before parsing, see [`RSS_DEFAULT_EVENT_BUFFER`]
+    pub event_buffer:usize,
+    /// Abort parsing with a `TimedOut` error once it runs longer than this
+    pub max_parse_duration:Option<std::time::Duration>,
+    /// Abort parsing with a [`BudgetExceeded`] error once the document plus
+    /// the text of the produced items go over this many bytes
+    pub max_parse_bytes:Option<usize>,
+    /// Count the elements inside items that no tag captures, see
+    /// [`ParseMetrics::unknown_elements`]
+    pub report_unknown:bool,
+    /// Rewrite every publish and updated date [`Timestamp`] understands to UTC RFC 3339,
+    /// so items of feeds in different zones sort and store alike
+    pub utc_dates:bool,
+    /// Formats and zone default for rewriting dates to UTC, implies
+    /// [`RssParser::utc_dates`]
+    pub date_parser:Option<DateParser>,
+    /// Make [`RssParser::parse_json`] return `{"channel":{...},"items":[...]}`
+    /// instead of the bare item array, see [`Channel`]
+    pub json_channel:bool,
+    /// Named paths read by [`RssParser::parse_extras`], see [`RssParser::select`]
+    pub selectors:Vec<(String,Selector)>,
+    /// Extra fields read by [`RssParser::parse_fields`]
+    pub fields:Vec<FieldMapping>,
+    /// User agent and headers of every request the parser sends
+    pub request:RequestOptions,
+    hooks:hooks::ItemHooks,
 }
 
 
-impl Default for RssItem{
-    fn default() -> Self {
-        Self{
-            title:String::new(),
-            link:String::new(),
-            author:String::new(),
-            description:String::new(),
-            guid:String::new(),
-            publish:String::new()
+///
+/// Rss Item Node borrowing its text from the parsed xml
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0">
+///             <item><title>Hey!</title><author>Tom &amp; Jerry</author></item>
+///         </rss>
+///         "#
+///     ))?;
+///     let items = parser.parse_borrowed()?;
+///     assert_eq!(items[0].title,"Hey!");
+///     assert_eq!(items[0].author,"Tom & Jerry");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct RssItemRef<'a>{
+    pub title: Cow<'a,str>,
+    pub link: Cow<'a,str>,
+    pub author: Cow<'a,str>,
+    pub description: Cow<'a,str>,
+    pub content: Cow<'a,str>,
+    pub guid: Cow<'a,str>,
+    pub publish: Cow<'a,str>,
+    pub updated: Cow<'a,str>,
+    pub enclosures: Vec<Enclosure>,
+    pub categories: Vec<Category>,
+    pub extensions: BTreeMap<String,Vec<Cow<'a,str>>>,
+    #[cfg(feature = "podcast")]
+    pub podcast: Option<ItunesItem>,
+}
+
+impl RssItemRef<'_>{
+    pub fn into_owned(self)->RssItem{
+        RssItem{
+            title:self.title.into_owned(),
+            link:self.link.into_owned(),
+            author:self.author.into_owned(),
+            description:self.description.into_owned(),
+            content:self.content.into_owned(),
+            guid:self.guid.into_owned(),
+            publish:self.publish.into_owned(),
+            updated:self.updated.into_owned(),
+            enclosures:self.enclosures,
+            categories:self.categories,
+            extensions:self.extensions.into_iter()
+                .map(|(name,values)| (name,values.into_iter().map(Cow::into_owned).collect()))
+                .collect(),
+            #[cfg(feature = "podcast")]
+            podcast:self.podcast,
+        }
+    }
+}
+
+impl RssItem{
+
+    ///
+    /// Identity used to recognise the same item across fetches:
+    /// guid, falling back to link and then title
+    ///
+    pub fn key(&self)->&str{
+        if !self.guid.is_empty() {
+            &self.guid
+        }else if !self.link.is_empty() {
+            &self.link
+        }else {
+            &self.title
+        }
+    }
+
+    /// Publish date in UTC, `None` when its format is not understood
+    pub fn published_at(&self)->Option<Timestamp>{
+        Timestamp::parse(&self.publish)
+    }
+
+    /// Last edit in UTC, `None` when missing or not understood
+    pub fn updated_at(&self)->Option<Timestamp>{
+        Timestamp::parse(&self.updated)
+    }
+
+    /// Address and display name of the author, see [`Author::parse`]
+    pub fn author_details(&self)->Author{
+        Author::parse(&self.author)
+    }
+
+    ///
+    /// Item as a json object, with the keys used by [`RssParser::parse_json`]
+    ///
+    pub fn to_json(&self)->String{
+        self.json().dump()
+    }
+
+    fn json(&self)->json::JsonValue{
+        let mut json = object!{
+            "title": self.title.as_str(),
+            "link": self.link.as_str(),
+            "author": self.author.as_str(),
+            "description": self.description.as_str(),
+            "guid": self.guid.as_str(),
+            "publish": self.publish.as_str(),
+            "updated": self.updated.as_str(),
+        };
+        // keys beyond the original seven only show up when set
+        if !self.content.is_empty() {
+            json["content"] = self.content.as_str().into();
         }
+        if !self.enclosures.is_empty() {
+            json["enclosures"] = self.enclosures.iter().map(|enclosure| object!{
+                "url": enclosure.url.as_str(),
+                "length": enclosure.length,
+                "type": enclosure.media_type.as_ref().map(MediaType::to_string),
+            }).collect::<Vec<_>>().into();
+        }
+        if !self.categories.is_empty() {
+            json["categories"] = self.categories.iter().map(|category| object!{
+                "name": category.name.as_str(),
+                "domain": category.domain.as_deref(),
+            }).collect::<Vec<_>>().into();
+        }
+        if !self.extensions.is_empty() {
+            json["extensions"] = self.extensions.iter()
+                .map(|(name,values)| (name.as_str(),values.clone()))
+                .collect::<BTreeMap<_,_>>()
+                .into();
+        }
+        json
+    }
+}
+
+impl Default for RssParser{
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -176,48 +566,296 @@ impl RssParser{
         if !self.xml.contains(XML_DEFAULT_TAG) && !self.xml.contains(&XML_DEFAULT_TAG.to_uppercase()) {
             return false;
         }
-        if !self.xml.contains(RSS_DEFAULT_TAG) && !self.xml.contains(&RSS_DEFAULT_TAG.to_uppercase()) {
-            return false;
+        [RSS_DEFAULT_TAG,SITEMAP_DEFAULT_TAG,ATOM_DEFAULT_TAG].iter()
+            .any(|tag| self.xml.contains(tag) || self.xml.contains(&tag.to_uppercase()))
+    }
+
+    ///
+    /// Replace an HTML page wrapping the feed by the feed itself, see
+    /// [`extract_feed`]; `false` when the xml is left as it was
+    ///
+    pub fn unwrap_html(&mut self)->bool{
+        match extract_feed(&self.xml) {
+            Some(feed) => {
+                self.xml = feed;
+                true
+            }
+            None => false
         }
-        return true;
     }
 
 
     ///
     /// Request Rss by Web
     ///
+    /// The request blocks the calling thread and panics inside an async
+    /// runtime, use [`RssParser::request_xml_async`] there
+    ///
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
     pub fn request_xml(&mut self,url:&str,charset:&str)->Result<String,reqwest::Error>{
-        Ok(reqwest::blocking::get(url)?
-            .text_with_charset(charset)?)
+        let client = network::default_client();
+        let send = || {
+            let mut request = client.get(url);
+            for (name,value) in self.request.header_pairs() {
+                request = request.header(name,value);
+            }
+            request.send()
+        };
+        retry::with_retry(self.request.retry.as_ref(),send,|response| response.status().as_u16())?
+            .text_with_charset(charset)
+    }
+
+    ///
+    /// Request Rss by Web without blocking, on reqwest's async client: the
+    /// future has to be polled inside a tokio runtime
+    ///
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn request_xml_async(&mut self,url:&str,charset:&str)->Result<String,reqwest::Error>{
+        self.request_xml_async_with(&network::default_async_client(),url,charset).await
+    }
+
+    ///
+    /// [`RssParser::request_xml`] through another HTTP backend, e.g. a
+    /// `reqwest::blocking::Client` with its own proxy, TLS options, pool
+    /// and default headers
+    ///
+    pub fn request_xml_with(&mut self,fetcher:&dyn HttpFetcher,url:&str,charset:&str)->Result<String,std::io::Error>{
+        let headers = self.request.header_pairs();
+        retry::with_retry(self.request.retry.as_ref(),|| fetcher.fetch(url,&headers),|response| response.status)?
+            .text(charset)
+    }
+
+    ///
+    /// [`RssParser::request_xml_async`] on a client built by the caller
+    ///
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn request_xml_async_with(&mut self,client:&reqwest::Client,url:&str,charset:&str)->Result<String,reqwest::Error>{
+        let send = || {
+            let mut request = client.get(url);
+            for (name,value) in self.request.header_pairs() {
+                request = request.header(name,value);
+            }
+            request.send()
+        };
+        retry::with_retry_async(self.request.retry.as_ref(),send,|response| response.status().as_u16())
+            .await?
+            .text_with_charset(charset)
+            .await
     }
 
     ///
     /// Request RSS by File
+    ///
+    /// Compressed files (gzip / zstd, recognised by their magic bytes) are
+    /// not decompressed, the crate carries no decoder for either: they are
+    /// rejected with `InvalidData` instead of failing on invalid utf8. Hand a
+    /// decoder such as `flate2::read::GzDecoder` to
+    /// [`RssParser::parse_from_reader`] instead
+    ///
+    /// The file is read off the async task, see [`RssParser::parse_vec_async`]
+    /// for the runtime used
     pub async fn request_file(&mut self,filename:&str)->Result<String,std::io::Error>{
-        let mut f = File::open(filename)?;
-        let mut body = String::new();
-        f.read_to_string(&mut body)?;
-        Ok(body)
+        let filename = filename.to_string();
+        runtime::spawn_blocking(move || {
+            let mut f = File::open(filename)?;
+            let mut body = Vec::new();
+            f.read_to_end(&mut body)?;
+            if let Some(format) = compression_format(&body) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Failed by RssParser::request_file: {} compressed input, decompress it first",format)
+                ));
+            }
+            String::from_utf8(body).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,e))
+        }).await?
     }
 
 
     pub fn new()->Self{
         Self{
             xml:String::new(),
+            scratch:Scratch::default(),
             node_tag:String::from(RSS_DEFAULT_NODE_TAG),
             title_tag:String::from(RSS_DEFAULT_TITLE_TAG),
             link_tag:String::from(RSS_DEFAULT_LINK_TAG),
             author_tag:String::from(RSS_DEFAULT_AUTHOR_TAG),
             description_tag:String::from(RSS_DEFAULT_DESC_TAG),
+            content_tag:String::from(RSS_DEFAULT_CONTENT_TAG),
             guid_tag:String::from(RSS_DEFAULT_GUID_TAG),
-            publish_tag:String::from(RSS_DEFAULT_PUBLISH_TAG)
+            publish_tag:String::from(RSS_DEFAULT_PUBLISH_TAG),
+            updated_tag:String::from(RSS_DEFAULT_UPDATED_TAG),
+            namespaces:XML_DEFAULT_NAMESPACES.iter().map(|(prefix,uri)| (prefix.to_string(),uri.to_string())).collect(),
+            read_buffer:RSS_DEFAULT_READ_BUFFER,
+            event_buffer:RSS_DEFAULT_EVENT_BUFFER,
+            max_parse_duration:None,
+            max_parse_bytes:None,
+            report_unknown:false,
+            utc_dates:false,
+            date_parser:None,
+            json_channel:false,
+            selectors:Vec::new(),
+            fields:Vec::new(),
+            request:RequestOptions::default(),
+            hooks:hooks::ItemHooks::default(),
+        }
+    }
+
+    ///
+    /// Parser for sitemap.xml, every &lt;url&gt; becomes an item with
+    /// its &lt;loc&gt; as link and &lt;lastmod&gt; as publish
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let mut parser = RssParser::sitemap();
+    ///     parser.set_xml(String::from(
+    ///         r#"<?xml version="1.0" encoding="UTF-8"?>
+    ///         <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    ///             <url><loc>https://examples.com/a</loc><lastmod>2020-05-28</lastmod></url>
+    ///         </urlset>"#
+    ///     ));
+    ///     let items = parser.parse_vec()?;
+    ///     assert_eq!(items[0].link,"https://examples.com/a");
+    ///     assert_eq!(items[0].publish,"2020-05-28");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn sitemap()->Self{
+        Self{
+            node_tag:String::from(SITEMAP_DEFAULT_NODE_TAG),
+            link_tag:String::from(SITEMAP_DEFAULT_LINK_TAG),
+            publish_tag:String::from(SITEMAP_DEFAULT_PUBLISH_TAG),
+            ..Self::new()
+        }
+    }
+
+    ///
+    /// Parser for Atom feeds, every &lt;entry&gt; becomes an item
+    ///
+    /// The link is the `href` of the first alternate &lt;link/&gt; and the
+    /// description the &lt;summary&gt;, or the &lt;content&gt; without one.
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let mut parser = RssParser::atom();
+    ///     parser.set_xml(String::from(
+    ///        r#"<?xml version="1.0" encoding="utf-8"?>
+    ///         <feed xmlns="http://www.w3.org/2005/Atom">
+    ///             <entry>
+    ///                 <title>Hey!</title>
+    ///                 <link rel="edit" href="https://examples.com/edit/1"/>
+    ///                 <link href="https://examples.com/1?a=1&amp;b=2"/>
+    ///                 <id>urn:uuid:1</id>
+    ///                 <updated>2020-05-28T15:00:00Z</updated>
+    ///                 <content type="html">&lt;p&gt;hello.world!&lt;/p&gt;</content>
+    ///             </entry>
+    ///         </feed>
+    ///         "#
+    ///     ));
+    ///     let items = parser.parse_vec()?;
+    ///     assert_eq!(items[0].link,"https://examples.com/1?a=1&b=2");
+    ///     assert_eq!(items[0].description,"<p>hello.world!</p>");
+    ///     assert_eq!(items[0].updated,"2020-05-28T15:00:00Z");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn atom()->Self{
+        Self{
+            node_tag:String::from(ATOM_DEFAULT_NODE_TAG),
+            author_tag:String::from(ATOM_DEFAULT_AUTHOR_TAG),
+            description_tag:String::from(ATOM_DEFAULT_DESC_TAG),
+            content_tag:String::from(ATOM_DEFAULT_CONTENT_TAG),
+            guid_tag:String::from(ATOM_DEFAULT_GUID_TAG),
+            publish_tag:String::from(ATOM_DEFAULT_PUBLISH_TAG),
+            updated_tag:String::from(ATOM_DEFAULT_UPDATED_TAG),
+            ..Self::new()
         }
     }
 
+    ///
+    /// Parser for RSS 1.0 (RDF) feeds, whose items carry Dublin Core
+    /// &lt;dc:creator&gt; and &lt;dc:date&gt;
+    ///
+    pub fn rdf()->Self{
+        Self{
+            author_tag:String::from(RDF_DEFAULT_AUTHOR_TAG),
+            publish_tag:String::from(RDF_DEFAULT_PUBLISH_TAG),
+            ..Self::new()
+        }
+    }
+
+    ///
+    /// Set the item tags for the dialect of the xml from its root element:
+    /// those of [`RssParser::new`] for RSS 0.9x and 2.0, [`RssParser::rdf`]
+    /// for RSS 1.0 and [`RssParser::atom`] for Atom; nothing changes for
+    /// other documents
+    ///
+    /// `from_str`, `from_url` and `from_file` detect the format themselves,
+    /// so does [`FeedWatcher`] while its parser keeps the default tags.
+    ///
+    /// ```
+    /// use future_rss::{RssParser,FeedFormat};
+    ///
+    /// fn main(){
+    ///     let mut parser = RssParser::new();
+    ///     parser.set_xml(String::from(
+    ///        r#"<?xml version="1.0"?>
+    ///         <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/" xmlns:dc="http://purl.org/dc/elements/1.1/">
+    ///             <channel rdf:about="https://examples.com/"><title>Examples</title></channel>
+    ///             <item rdf:about="https://examples.com/1"><title>Hey!</title><dc:creator>MeteorCat</dc:creator></item>
+    ///         </rdf:RDF>
+    ///         "#
+    ///     ));
+    ///     assert_eq!(parser.detect_format(),Some(FeedFormat::Rss));
+    ///     assert_eq!(parser.parse_vec().unwrap()[0].author,"MeteorCat");
+    /// }
+    /// ```
+    pub fn detect_format(&mut self)->Option<FeedFormat>{
+        let format = FeedFormat::detect(&self.xml)?;
+        let dialect = match format {
+            FeedFormat::Atom => Self::atom(),
+            FeedFormat::Rss if crate::feed::root_element(&self.xml).as_deref() == Some("rdf:rdf") => Self::rdf(),
+            FeedFormat::Rss => Self::new(),
+            FeedFormat::JsonFeed => return Some(format),
+        };
+        self.node_tag = dialect.node_tag;
+        self.title_tag = dialect.title_tag;
+        self.link_tag = dialect.link_tag;
+        self.author_tag = dialect.author_tag;
+        self.description_tag = dialect.description_tag;
+        self.content_tag = dialect.content_tag;
+        self.guid_tag = dialect.guid_tag;
+        self.publish_tag = dialect.publish_tag;
+        self.updated_tag = dialect.updated_tag;
+        Some(format)
+    }
+
+    /// Item tags are still those of [`RssParser::new`]
+    #[cfg_attr(not(all(feature = "http",not(target_arch = "wasm32"))),allow(dead_code))]
+    pub(crate) fn has_default_tags(&self)->bool{
+        let default = Self::new();
+        [
+            (&self.node_tag,&default.node_tag),
+            (&self.title_tag,&default.title_tag),
+            (&self.link_tag,&default.link_tag),
+            (&self.author_tag,&default.author_tag),
+            (&self.description_tag,&default.description_tag),
+            (&self.content_tag,&default.content_tag),
+            (&self.guid_tag,&default.guid_tag),
+            (&self.publish_tag,&default.publish_tag),
+            (&self.updated_tag,&default.updated_tag),
+        ].iter().all(|(tag,default)| tag == default)
+    }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(xml:String)->Result<Self,std::io::Error>{
         let mut parser = Self::new();
         parser.xml = xml;
+        parser.unwrap_html();
+        parser.detect_format();
         if !parser.check_xml() {
             Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -230,26 +868,152 @@ impl RssParser{
 
 
 
+    ///
+    /// Parser over a body fetched by the host, e.g. the `ArrayBuffer` of a
+    /// browser `fetch` on wasm32, decoded from `charset` into utf8
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let body = b"<?xml version=\"1.0\"?><rss><item><title>Hey!</title></item></rss>";
+    ///     let mut parser = RssParser::from_bytes(body,"utf8")?;
+    ///     assert_eq!(parser.parse_vec()?[0].title,"Hey!");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_bytes(body:&[u8],charset:&str)->Result<Self,std::io::Error>{
+        let (xml,_,_) = fetcher::encoding(charset).decode(body);
+        Self::from_str(xml.into_owned())
+    }
+
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
     pub fn from_url(url:&str,charset:&str)->Result<Self,std::io::Error>{
         let mut parser = Self::new();
         match parser.request_xml(url,charset) {
             Ok(body) => {
                 parser.xml = body;
+                parser.unwrap_html();
+                parser.detect_format();
                 if !parser.check_xml() {
                     Err(std::io::Error::from(std::io::ErrorKind::InvalidData))
                 }else {
                     Ok(parser)
                 }
             }
-            Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,e.to_string()))
+            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData,e.to_string()))
         }
     }
 
+    ///
+    /// [`RssParser::from_url`] without blocking, see [`RssParser::request_xml_async`]
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// async fn latest(address:&str)->Result<(),std::io::Error>{
+    ///     let mut parser = RssParser::from_url_async(address,"utf8").await?;
+    ///     println!("{:?}",parser.parse_vec()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn from_url_async(url:&str,charset:&str)->Result<Self,std::io::Error>{
+        Self::from_url_async_with(&network::default_async_client(),url,charset).await
+    }
+
+    ///
+    /// Fetch many feeds at once, at most `concurrency` requests at a time
+    /// and at least one; each url gets the result [`RssParser::from_url_async`]
+    /// would give, in the order of `urls`
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// async fn aggregate()->Result<(),std::io::Error>{
+    ///     let urls = ["https://www.zhihu.com/rss","https://sspai.com/feed"];
+    ///     for (url,parser) in urls.iter().zip(RssParser::from_urls(&urls,"utf8",8).await) {
+    ///         match parser {
+    ///             Ok(mut parser) => println!("{}: {:?}",url,parser.parse_vec()?),
+    ///             Err(e) => println!("{}: {}",url,e),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn from_urls(urls:&[&str],charset:&str,concurrency:usize)->Vec<Result<Self,std::io::Error>>{
+        Self::from_urls_with_cancel(urls,charset,concurrency,&CancelToken::new()).await
+    }
+
+    ///
+    /// [`RssParser::from_urls`] until `token` is cancelled: requests in
+    /// flight are dropped then, and they as well as the urls not fetched
+    /// yet fail with `Interrupted`
+    ///
+    /// ```no_run
+    /// use future_rss::{CancelToken,RssParser};
+    ///
+    /// async fn aggregate(token:CancelToken){
+    ///     let urls = ["https://www.zhihu.com/rss","https://sspai.com/feed"];
+    ///     let parsers = RssParser::from_urls_with_cancel(&urls,"utf8",8,&token).await;
+    ///     println!("{} fetched",parsers.iter().filter(|parser| parser.is_ok()).count());
+    /// }
+    /// ```
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn from_urls_with_cancel(urls:&[&str],charset:&str,concurrency:usize,token:&CancelToken)->Vec<Result<Self,std::io::Error>>{
+        let client = network::default_async_client();
+        let fetches = urls.iter().map(|url| Self::from_url_async_with_cancel(&client,url,charset,token)).collect();
+        runtime::join_limited(fetches,concurrency).await
+    }
+
+    ///
+    /// [`RssParser::from_url`] through another HTTP backend, see
+    /// [`RssParser::request_xml_with`]
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let client = reqwest::blocking::Client::builder()
+    ///         .proxy(reqwest::Proxy::all("http://proxy.internal:3128")?)
+    ///         .build()?;
+    ///     let mut parser = RssParser::from_url_with(&client,"https://www.zhihu.com/rss","utf8")?;
+    ///     println!("{:?}",parser.parse_vec()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_url_with(fetcher:&dyn HttpFetcher,url:&str,charset:&str)->Result<Self,std::io::Error>{
+        Self::from_str(Self::new().request_xml_with(fetcher,url,charset)?)
+    }
+
+    ///
+    /// [`RssParser::from_url_async`] on a client built by the caller
+    ///
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn from_url_async_with(client:&reqwest::Client,url:&str,charset:&str)->Result<Self,std::io::Error>{
+        match Self::new().request_xml_async_with(client,url,charset).await {
+            Ok(body) => Self::from_str(body),
+            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData,e.to_string()))
+        }
+    }
+
+    ///
+    /// [`RssParser::from_url_async_with`] until `token` is cancelled, the
+    /// request is dropped then, retries and their waits included
+    ///
+    #[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+    pub async fn from_url_async_with_cancel(client:&reqwest::Client,url:&str,charset:&str,token:&CancelToken)->Result<Self,std::io::Error>{
+        token.run(Self::from_url_async_with(client,url,charset)).await?
+    }
+
     pub async fn from_file(filename:&str)->Result<Self,std::io::Error>{
         let mut parser = Self::new();
         let body = parser.request_file(filename).await?;
 
         parser.xml = body;
+        parser.unwrap_html();
+        parser.detect_format();
         if !parser.check_xml() {
             Err(std::io::Error::from(std::io::ErrorKind::InvalidData))
         }else {
@@ -257,97 +1021,660 @@ impl RssParser{
         }
     }
 
+    ///
+    /// Parse into owned items, the event buffer is kept on the parser
+    /// and reused by the next call
+    ///
     pub fn parse_vec(&mut self)->Result<Vec<RssItem>,std::io::Error>{
-        let mut reader = Reader::from_str(self.xml.as_str());
+        self.with_scratch(|parser,scratch| {
+            let xml = parser.xml.as_str();
+            parser.parse_with(Reader::from_str(xml),Some(xml),scratch,None)
+                .and_then(|nodes| nodes.into_iter().map(RawRssItem::into_owned).collect())
+        })
+    }
 
-        reader.trim_text(true);
-        reader.check_end_names(true);
-        reader.check_comments(false);
-        reader.expand_empty_elements(true);
+    ///
+    /// [`RssParser::parse_vec`] off the async task, so a large feed doesn't
+    /// stall the executor. With the `rt-tokio` feature the work goes to tokio's
+    /// blocking pool, otherwise to a thread of its own and any executor works.
+    /// The document is moved away while it is parsed: if the future is
+    /// dropped early the parser is left without xml.
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// async fn latest(body:String)->Result<(),std::io::Error>{
+    ///     let mut parser = RssParser::new();
+    ///     parser.set_xml(body);
+    ///     let items = parser.parse_vec_async().await?;
+    ///     println!("{:?}",items);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn parse_vec_async(&mut self)->Result<Vec<RssItem>,std::io::Error>{
+        let xml = std::mem::take(&mut self.xml);
+        let mut parser = self.clone();
+        parser.xml = xml;
 
+        let (parser,nodes) = runtime::spawn_blocking(move || {
+            let nodes = parser.parse_vec();
+            (parser,nodes)
+        }).await?;
 
-        let mut buff = Vec::new();
-        let mut nodes = Vec::new();
-        let mut active = String::new();
+        self.xml = parser.xml;
+        self.scratch = parser.scratch;
+        nodes
+    }
 
-        loop{
-            match reader.read_event(&mut buff) {
-                // Fetch = <Item></Item>
-                Ok(Event::Start(ref e)) => {
-                    active = std::str::from_utf8(e.name())
-                        .expect("Failed By Parse <Item>")
-                        .to_string();
+    ///
+    /// Parse without copying field text: fields borrow from the xml buffer
+    /// and only text that needs unescaping is allocated
+    ///
+    pub fn parse_borrowed(&self)->Result<Vec<RssItemRef<'_>>,std::io::Error>{
+        self.parse_str(self.xml.as_str())
+    }
 
-                    if self.node_tag.eq_ignore_ascii_case(&active) {
-                        nodes.push(RssItem::default());
-                    }
-                }
+    ///
+    /// Parse a document owned by the caller instead of the one set by [`RssParser::set_xml`],
+    /// items borrow from `xml` the same way as [`RssParser::parse_borrowed`]
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let body = r#"<rss version="2.0"><item><title>Hey!</title></item></rss>"#;
+    ///     let parser = RssParser::new();
+    ///     let items = parser.parse_str(body)?;
+    ///     assert_eq!(items[0].title,"Hey!");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse_str<'x>(&self,xml:&'x str)->Result<Vec<RssItemRef<'x>>,std::io::Error>{
+        self.parse_with(Reader::from_str(xml),Some(xml),&mut Scratch::default(),None)?
+            .into_iter()
+            .map(RawRssItem::decode)
+            .collect()
+    }
 
-                // Fetch = <Item><Node><CDATA></Node><Item>
-                Ok(Event::CData(ref e)) => {
-                    let node_text = std::str::from_utf8(e.escaped())
-                        .expect("Failed by Parse <CData>");
-
-                    if let Some(last) = nodes.last_mut() {
-                        match active {
-                            _ if self.title_tag.eq_ignore_ascii_case(&active) => { last.title = node_text.to_string() },
-                            _ if self.link_tag.eq_ignore_ascii_case(&active) => { last.link = node_text.to_string() },
-                            _ if self.author_tag.eq_ignore_ascii_case(&active) => { last.author = node_text.to_string() },
-                            _ if self.description_tag.eq_ignore_ascii_case(&active) => { last.description = node_text.to_string() },
-                            _ if self.guid_tag.eq_ignore_ascii_case(&active) => { last.guid = node_text.to_string() },
-                            _ if self.publish_tag.eq_ignore_ascii_case(&active) => { last.publish = node_text.to_string() },
-                            _ => (),
-                        }
-                    }
-                }
+    ///
+    /// Parse without decoding anything, fields are unescaped when read
+    ///
+    pub fn parse_raw(&self)->Result<Vec<RawRssItem<'_>>,std::io::Error>{
+        let xml = self.xml.as_str();
+        self.parse_with(Reader::from_str(xml),Some(xml),&mut Scratch::default(),None)
+    }
+
+    ///
+    /// Parse like [`RssParser::parse_vec`], but resume at the next item after
+    /// a malformed one and hand back every failure next to the items read
+    ///
+    pub fn parse_recover(&self)->RecoveredItems{
+        crate::recover::parse_recover(self,&self.xml)
+    }
+
+    ///
+    /// iTunes podcast fields of every item, in the order of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_itunes(&self)->Result<Vec<ItunesItem>,std::io::Error>{
+        crate::itunes::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Categories of every item, in the order of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_categories(&self)->Result<Vec<Vec<Category>>,std::io::Error>{
+        crate::category::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Enclosures of every item with their length and type checked, in the
+    /// order of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_enclosures(&self)->Result<Vec<Vec<Enclosure>>,std::io::Error>{
+        crate::enclosure::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Links of every item tagged with their role, comments and related
+    /// pages included, in the order of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_links(&self)->Result<Vec<Vec<ItemLink>>,std::io::Error>{
+        crate::links::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Media RSS contents, thumbnails and groups of every item, in the order
+    /// of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_media(&self)->Result<Vec<MediaExtension>,std::io::Error>{
+        crate::media::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Register the path `path` under `name` for [`RssParser::parse_extras`],
+    /// see [`Selector`]
+    ///
+    pub fn select(&mut self,name:&str,path:&str)->Result<(),std::io::Error>{
+        self.selectors.push((name.to_string(),Selector::parse(path)?));
+        Ok(())
+    }
+
+    ///
+    /// Values of the registered selectors for every item, by name, in the
+    /// order of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_extras(&self)->Result<Vec<BTreeMap<String,String>>,std::io::Error>{
+        crate::select::parse_items(self,&self.xml)
+    }
+
+    ///
+    /// Run `hook` with the number of every item of the document, counted from 0,
+    /// as its start tag is read; returning false skips the item unread.
+    /// Runs once per pass like [`RssParser::on_item_complete`]
+    ///
+    pub fn on_item_start<F>(&mut self,hook:F)
+        where F:Fn(usize)->bool+Send+Sync+'static
+    {
+        self.hooks.push_start(std::sync::Arc::new(hook));
+    }
+
+    ///
+    /// Run `hook` on every complete item before it is handed out, the hook
+    /// may change it or return false to drop it; applies to every parse
+    /// method
+    ///
+    /// Side passes such as [`RssParser::parse_categories`] read the document
+    /// once more with the hooks to learn which items are kept, so their
+    /// results still line up with [`RssParser::parse_vec`]. Hooks therefore
+    /// run once per pass over the same items and must be idempotent: give
+    /// the same answer, and make the same change, every time
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let mut parser = RssParser::new();
+    ///     parser.on_item_complete(|item| {
+    ///         item.title = item.title.trim().to_string();
+    ///         !item.link.is_empty()
+    ///     });
+    ///     parser.set_xml(String::from(
+    ///         "<rss><item><title> Hey! </title><link>https://examples.com/1</link></item><item><title>No link</title></item></rss>"
+    ///     ));
+    ///     let items = parser.parse_vec()?;
+    ///     assert_eq!(items.len(),1);
+    ///     assert_eq!(items[0].title,"Hey!");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn on_item_complete<F>(&mut self,hook:F)
+        where F:Fn(&mut RssItem)->bool+Send+Sync+'static
+    {
+        self.hooks.push_complete(std::sync::Arc::new(hook));
+    }
+
+    ///
+    /// Run `transformer` on the `field` of every item as it is parsed, after
+    /// the transformers attached before; `InvalidInput` when `field` is no
+    /// [`RssItem`] field name
+    ///
+    pub fn transform(&mut self,field:&str,transformer:Transformer)->Result<(),std::io::Error>{
+        let mut probe = RssItem::default();
+        if crate::transform::field_mut(&mut probe,field).is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Failed by RssParser::transform: unknown field {}",field)
+            ));
+        }
+        let field = field.to_string();
+        self.on_item_complete(move |item| {
+            if let Some(value) = crate::transform::field_mut(item,&field) {
+                *value = transformer.apply(value);
+            }
+            true
+        });
+        Ok(())
+    }
+
+    ///
+    /// Values of the [`FieldMapping`]s for every item, by output name;
+    /// items missing a required field are left out
+    ///
+    pub fn parse_fields(&self)->Result<Vec<BTreeMap<String,String>>,std::io::Error>{
+        crate::fields::parse_items(self,&self.xml)
+    }
 
-                // Fetch = <Item><Node></Node><Item>
-                Ok(Event::Text(ref e)) => {
-                    let node_text = e
-                        .unescape_and_decode(&reader)
-                        .expect("Failed by Parse <Node>");
-
-                    if let Some(last) = nodes.last_mut() {
-                        match active {
-                            _ if self.title_tag.eq_ignore_ascii_case(&active) => { last.title = node_text.to_string() },
-                            _ if self.link_tag.eq_ignore_ascii_case(&active) => { last.link = node_text.to_string() },
-                            _ if self.author_tag.eq_ignore_ascii_case(&active) => { last.author = node_text.to_string() },
-                            _ if self.description_tag.eq_ignore_ascii_case(&active) => { last.description = node_text.to_string() },
-                            _ if self.guid_tag.eq_ignore_ascii_case(&active) => { last.guid = node_text.to_string() },
-                            _ if self.publish_tag.eq_ignore_ascii_case(&active) => { last.publish = node_text.to_string() },
-                            _ => (),
-                        }
-                    }
+    ///
+    /// rssCloud server announced by the channel, see [`RssCloud`]
+    ///
+    pub fn parse_cloud(&self)->Result<Option<RssCloud>,std::io::Error>{
+        crate::cloud::parse_cloud(&self.xml)
+    }
+
+    ///
+    /// Title, link, description, language, last build date and image of
+    /// the feed, see [`Channel`]
+    ///
+    pub fn parse_channel(&self)->Result<Channel,std::io::Error>{
+        crate::channel::parse_channel(&self.xml)
+    }
+
+    ///
+    /// Channel together with the items of [`RssParser::parse_vec`]
+    ///
+    pub fn parse_feed(&mut self)->Result<(Channel,Vec<RssItem>),std::io::Error>{
+        let channel = self.parse_channel()?;
+        Ok((channel,self.parse_vec()?))
+    }
+
+    ///
+    /// Parse into items with shared storage, repeated values are taken from `pool`
+    ///
+    pub fn parse_shared(&mut self,pool:&mut StringPool)->Result<Vec<SharedRssItem>,std::io::Error>{
+        self.with_scratch(|parser,scratch| {
+            let xml = parser.xml.as_str();
+            parser.parse_with(Reader::from_str(xml),Some(xml),scratch,None)?
+                .into_iter()
+                .map(|item| item.decode().map(|item| SharedRssItem::from_item(item,pool)))
+                .collect()
+        })
+    }
+
+    ///
+    /// Measurements of the last parse run through `&mut self`
+    /// ([`RssParser::parse_vec`], [`RssParser::parse_first`], [`RssParser::parse_url`], ...)
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let mut parser = RssParser::new();
+    ///     parser.set_xml(String::from("<rss><item><title>Hey!</title></item></rss>"));
+    ///     parser.parse_vec()?;
+    ///     let metrics = parser.metrics();
+    ///     assert_eq!(metrics.items,1);
+    ///     println!("{} bytes, {} events in {:?}",metrics.bytes,metrics.events,metrics.duration);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn metrics(&self)->&ParseMetrics{
+        &self.scratch.metrics
+    }
+
+    ///
+    /// Run `parse` with the buffers kept on the parser
+    ///
+    fn with_scratch<T,F>(&mut self,parse:F)->T
+        where F:FnOnce(&Self,&mut Scratch)->T
+    {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        let result = parse(self,&mut scratch);
+
+        scratch.buff.clear();
+        self.scratch = scratch;
+        result
+    }
+
+    ///
+    /// Collect items from any buffered input, text is borrowed from `source`
+    /// when the reader runs over that same string and copied otherwise.
+    /// Stops after `limit` complete items when given.
+    ///
+    fn parse_with<'x,B:BufRead>(&self,reader:Reader<B>,source:Option<&'x str>,scratch:&mut Scratch,limit:Option<usize>)->Result<Vec<RawRssItem<'x>>,std::io::Error>{
+        let mut items = RawItems::new(self,reader,source,std::mem::take(&mut scratch.buff));
+        let nodes = items.by_ref()
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+
+        scratch.metrics = items.metrics();
+        scratch.buff = items.into_buffer();
+        #[cfg(feature="metrics")]
+        crate::metrics().record_parse(scratch.metrics.duration);
+        trace_event!(
+            items=scratch.metrics.items,
+            bytes=scratch.metrics.bytes,
+            duration_us=scratch.metrics.duration.as_micros() as u64,
+            "parse finished"
+        );
+        nodes
+    }
+
+    ///
+    /// Items of `xml` next to the index in the document of each, vetoed
+    /// items included, to line up passes that read every item again
+    ///
+    pub(crate) fn parse_indexed<'x>(&self,xml:&'x str)->Result<(Vec<RawRssItem<'x>>,Vec<usize>),std::io::Error>{
+        let mut items = RawItems::new(self,Reader::from_str(xml),Some(xml),Vec::new());
+        let nodes = items.by_ref().collect::<Result<Vec<_>,_>>()?;
+        Ok((nodes,items.kept().to_vec()))
+    }
+
+    /// Same tags and limits without the item hooks or a document
+    pub(crate) fn without_hooks(&self)->Self{
+        Self{
+            xml:String::new(),
+            hooks:hooks::ItemHooks::default(),
+            ..self.clone()
+        }
+    }
+
+    ///
+    /// Parse only the first `n` items of the document set by [`RssParser::set_xml`],
+    /// the rest of the document is never read
+    ///
+    pub fn parse_first(&mut self,n:usize)->Result<Vec<RssItem>,std::io::Error>{
+        self.with_scratch(|parser,scratch| {
+            let xml = parser.xml.as_str();
+            parser.parse_with(Reader::from_str(xml),Some(xml),scratch,Some(n))
+                .and_then(|nodes| nodes.into_iter().map(RawRssItem::into_owned).collect())
+        })
+    }
+
+    ///
+    /// Parse a file while reading it, with std I/O only so it also works on
+    /// wasm32-wasi; compressed files are rejected like in [`RssParser::request_file`],
+    /// a decoder goes in front of [`RssParser::parse_from_reader`]
+    ///
+    pub fn parse_file<P:AsRef<std::path::Path>>(&mut self,path:P)->Result<Vec<RssItem>,std::io::Error>{
+        let mut file = std::io::BufReader::with_capacity(self.read_buffer.max(1),File::open(path)?);
+        if let Some(format) = compression_format(file.fill_buf()?) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed by RssParser::parse_file: {} compressed input, decompress it first",format)
+            ));
+        }
+        self.parse_from_reader(file)
+    }
+
+    ///
+    /// Parse any reader while reading it: the document is never held whole,
+    /// only the read buffer and the items parsed so far
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let body = std::io::Cursor::new("<rss><item><title>Hey!</title></item></rss>");
+    ///     let mut parser = RssParser::new();
+    ///     assert_eq!(parser.parse_from_reader(body)?[0].title,"Hey!");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse_from_reader<R:BufRead>(&mut self,reader:R)->Result<Vec<RssItem>,std::io::Error>{
+        self.with_scratch(|parser,scratch| {
+            parser.parse_with(Reader::from_reader(reader),None,scratch,None)
+                .and_then(|nodes| nodes.into_iter().map(RawRssItem::into_owned).collect())
+        })
+    }
+
+    ///
+    /// [`RssParser::parse_from_reader`] one item at a time, so memory stays
+    /// bounded by a single item whatever the size of the feed; iteration
+    /// ends after the first error
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>>{
+    ///     let file = std::io::BufReader::new(std::fs::File::open("podcast.xml")?);
+    ///     for item in RssParser::new().reader_items(file) {
+    ///         println!("{}",item?.title);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn reader_items<R:BufRead>(&self,reader:R)->impl Iterator<Item=Result<RssItem,std::io::Error>>{
+        RawItems::new(self,Reader::from_reader(reader),None,Vec::new())
+            .map(|item| item.and_then(RawRssItem::into_owned))
+    }
+
+    ///
+    /// Fetch and parse a feed, consuming xml events straight from the response body
+    ///
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn parse_url(&mut self,url:&str,charset:&str)->Result<Vec<RssItem>,std::io::Error>{
+        self.fetch_items(&network::default_client(),url,charset,None)
+    }
+
+    ///
+    /// [`RssParser::parse_url`] through another HTTP backend
+    ///
+    pub fn parse_url_with(&mut self,fetcher:&dyn HttpFetcher,url:&str,charset:&str)->Result<Vec<RssItem>,std::io::Error>{
+        self.fetch_items(fetcher,url,charset,None)
+    }
+
+    ///
+    /// Fetch a feed and stop downloading as soon as `n` items were parsed
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>> {
+    ///     let mut parser = RssParser::new();
+    ///     parser.author_tag = String::from("dc:creator");
+    ///     let latest = parser.parse_url_first("https://www.zhihu.com/rss","utf8",1)?;
+    ///     println!("{:?}",latest);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn parse_url_first(&mut self,url:&str,charset:&str,n:usize)->Result<Vec<RssItem>,std::io::Error>{
+        self.fetch_items(&network::default_client(),url,charset,Some(n))
+    }
+
+    fn fetch_items(&mut self,fetcher:&dyn HttpFetcher,url:&str,charset:&str,limit:Option<usize>)->Result<Vec<RssItem>,std::io::Error>{
+        let body = open_url(fetcher,url,charset,self)?;
+        self.with_scratch(|parser,scratch| {
+            parser.parse_with(Reader::from_reader(body),None,scratch,limit)
+                .and_then(|nodes| nodes.into_iter().map(RawRssItem::into_owned).collect())
+        })
+    }
+
+    ///
+    /// Fetch and parse a feed on a background thread, items are handed over
+    /// through a queue of `capacity` entries; the thread stops reading the
+    /// response while the queue is full
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// async fn store()->Result<(),std::io::Error>{
+    ///     let mut parser = RssParser::new();
+    ///     parser.author_tag = String::from("dc:creator");
+    ///     let mut items = parser.item_stream("https://www.zhihu.com/rss","utf8",16);
+    ///     // with futures::StreamExt
+    ///     // while let Some(item) = items.next().await { db.insert(item?).await; }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "http",feature = "stream",not(target_arch = "wasm32")))]
+    pub fn item_stream(&self,url:&str,charset:&str,capacity:usize)->ItemStream{
+        let parser = self.clone();
+        let url = url.to_string();
+        let charset = charset.to_string();
+        ItemStream::spawn(capacity,move |sender| {
+            let body = open_url(&network::default_client(),&url,&charset,&parser)?;
+            parser.send_items(body,sender);
+            Ok(())
+        })
+    }
+
+    ///
+    /// Incrementally parse any reader on a background thread,
+    /// the stream yields items as soon as their end tag is read
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// async fn titles(file:std::fs::File)->Result<(),std::io::Error>{
+    ///     let parser = RssParser::new();
+    ///     let items = parser.reader_stream(std::io::BufReader::new(file),16);
+    ///     // with futures::StreamExt
+    ///     // let titles = items.map(|item| item.map(|i| i.title)).take(10).collect::<Vec<_>>().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub fn reader_stream<R:BufRead+Send+'static>(&self,reader:R,capacity:usize)->ItemStream{
+        let parser = self.clone();
+        ItemStream::spawn(capacity,move |sender| {
+            parser.send_items(reader,sender);
+            Ok(())
+        })
+    }
+
+    ///
+    /// Fetch and parse a feed on a background thread for synchronous consumers,
+    /// at most `capacity` items wait in the channel before the thread blocks;
+    /// dropping the receiver stops the thread
+    ///
+    /// ```no_run
+    /// use future_rss::RssParser;
+    ///
+    /// fn main()->Result<(),Box<dyn std::error::Error>> {
+    ///     let parser = RssParser::new();
+    ///     for item in parser.item_channel("https://www.zhihu.com/rss","utf8",16) {
+    ///         println!("{}",item?.title);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub fn item_channel(&self,url:&str,charset:&str,capacity:usize)->std::sync::mpsc::Receiver<Result<RssItem,std::io::Error>>{
+        let (sender,receiver) = std::sync::mpsc::sync_channel(capacity);
+        let parser = self.clone();
+        let url = url.to_string();
+        let charset = charset.to_string();
+        std::thread::spawn(move || {
+            let body = match open_url(&network::default_client(),&url,&charset,&parser) {
+                Ok(body) => body,
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            };
+            for item in RawItems::new(&parser,Reader::from_reader(body),None,Vec::new()) {
+                if sender.send(item.and_then(RawRssItem::into_owned)).is_err() {
+                    break;
                 }
+            }
+        });
+        receiver
+    }
 
-                Ok(Event::Eof) => break,
-                Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::Other,format!("{:?}",e))),
-                _ =>(),
+    #[cfg(feature = "stream")]
+    fn send_items<R:BufRead>(&self,reader:R,sender:&Sender<Result<RssItem,std::io::Error>>){
+        for item in RawItems::new(self,Reader::from_reader(reader),None,Vec::new()) {
+            if !sender.send(item.and_then(RawRssItem::into_owned)) {
+                break;
             }
-            buff.clear();
         }
+    }
+
+    ///
+    /// Parse raw bytes such as a `bytes::Bytes` body, which must be utf8
+    ///
+    pub fn parse_bytes<'x>(&self,xml:&'x [u8])->Result<Vec<RssItemRef<'x>>,std::io::Error>{
+        let xml = std::str::from_utf8(xml)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,e))?;
+        self.parse_str(xml)
+    }
+
+    ///
+    /// Parse independent documents in parallel on scoped threads,
+    /// one result per document in input order
+    ///
+    /// ```
+    /// use future_rss::RssParser;
+    ///
+    /// fn main(){
+    ///     let docs = vec![
+    ///         "<rss><item><title>One</title></item></rss>",
+    ///         "<rss><item><title>Two</title></item></rss>",
+    ///     ];
+    ///     let parser = RssParser::new();
+    ///     let results = parser.parse_many(&docs);
+    ///     assert_eq!(results[1].as_ref().unwrap()[0].title,"Two");
+    /// }
+    /// ```
+    pub fn parse_many<S:AsRef<str>+Sync>(&self,docs:&[S])->Vec<Result<Vec<RssItem>,std::io::Error>>{
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(docs.len());
+        let next = AtomicUsize::new(0);
+
+        let mut results:Vec<_> = std::thread::scope(|scope| {
+            let work = || {
+                let mut done = Vec::new();
+                loop {
+                    let index = next.fetch_add(1,Ordering::Relaxed);
+                    let doc = match docs.get(index) {
+                        Some(doc) => doc,
+                        None => break done,
+                    };
+                    let items = self.parse_str(doc.as_ref())
+                        .map(|items| items.into_iter().map(RssItemRef::into_owned).collect());
+                    done.push((index,items));
+                }
+            };
+            // targets without threads (wasm32-wasi) refuse to spawn,
+            // the calling thread takes part either way
+            let workers:Vec<_> = (1..threads)
+                .map_while(|_| std::thread::Builder::new().spawn_scoped(scope,work).ok())
+                .collect();
+            let mut results = work();
+            for worker in workers {
+                results.extend(worker.join().expect("Failed by RssParser::parse_many"));
+            }
+            results
+        });
+
+        results.sort_by_key(|(index,_)| *index);
+        results.into_iter().map(|(_,items)| items).collect()
+    }
 
-        Ok(nodes)
+    ///
+    /// Memory-map a local file and parse it without reading it into a `String`,
+    /// see [`MappedFile`] to keep borrowed items around
+    ///
+    /// # Safety
+    ///
+    /// Same contract as [`MappedFile::open`]: nothing may write to or truncate
+    /// the file while it is parsed.
+    ///
+    #[cfg(all(feature = "mmap",unix))]
+    pub unsafe fn parse_file_mmap<P:AsRef<std::path::Path>>(&self,path:P)->Result<Vec<RssItem>,std::io::Error>{
+        let mapped = MappedFile::open(path)?;
+        Ok(self.parse_bytes(&mapped)?
+            .into_iter()
+            .map(RssItemRef::into_owned)
+            .collect())
     }
 
     pub fn parse_json(&mut self)->Result<String,std::io::Error>{
         let item = self.parse_vec()?;
         let mut json = array![];
-        for node in item.into_iter() {
-            let data = object!{
-                "title": node.title,
-                "link": node.link,
-                "author": node.author,
-                "description": node.description,
-                "guid": node.guid,
-                "publish": node.publish,
-            };
-            json.push(data).expect("Failed by Parse Json")
+        for node in item.iter() {
+            json.push(node.json()).expect("Failed by Parse Json")
         }
 
+        if self.json_channel {
+            let channel = self.parse_channel()?;
+            return Ok(object!{ "channel": channel.json(), "items": json }.dump());
+        }
         Ok(json.dump())
     }
 
+    ///
+    /// [`RssParser::parse_json`] as a delta against the items of an earlier
+    /// parse, see [`FeedDelta::to_json`]
+    ///
+    pub fn parse_json_delta(&mut self,previous:&[RssItem])->Result<String,std::io::Error>{
+        let items = self.parse_vec()?;
+        Ok(FeedDelta::between(previous,&items).to_json())
+    }
+
 
     pub fn set_xml(&mut self,xml:String){
         self.xml = xml;
@@ -359,23 +1686,70 @@ impl RssParser{
 }
 
 
+///
+/// Name of the compression format `body` starts with, if any
+///
+fn compression_format(body:&[u8])->Option<&'static str>{
+    if body.starts_with(&[0x1f,0x8b]) {
+        Some("gzip")
+    }else if body.starts_with(&[0x28,0xb5,0x2f,0xfd]) {
+        Some("zstd")
+    }else {
+        None
+    }
+}
+
+///
+/// Open a feed response as utf8, the charset announced by the server wins
+/// over the caller's default
+///
+fn open_url(fetcher:&dyn HttpFetcher,url:&str,charset:&str,parser:&RssParser)->Result<Box<dyn BufRead+Send>,std::io::Error>{
+    trace_event!(url,"fetch started");
+    let headers = parser.request.header_pairs();
+    let response = retry::with_retry(parser.request.retry.as_ref(),|| fetcher.fetch(url,&headers),|response| response.status)?
+        .error_for_status()?;
+    let read_buffer = parser.read_buffer;
+    trace_event!(url,status=response.status,"fetch finished");
+
+    let html = response.header("content-type").is_some_and(|kind| kind.to_ascii_lowercase().contains("html"));
+    let encoding = fetcher::encoding(&response.charset().unwrap_or_else(|| charset.to_string()));
+    let mut reader:Box<dyn BufRead+Send> = if encoding == encoding_rs::UTF_8 {
+        Box::new(std::io::BufReader::with_capacity(read_buffer.max(1),response.body))
+    }else {
+        Box::new(DecodeReader::with_chunk(response.body,encoding,read_buffer))
+    };
+    // an html page has to be read whole to find the feed inside
+    if html {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let text = extract_feed(&text).unwrap_or(text);
+        reader = Box::new(std::io::Cursor::new(text.into_bytes()));
+    }
+    Ok(reader)
+}
+
 
 
 #[cfg(test)]
 mod tests {
-    use crate::RssParser;
+    use std::borrow::Cow;
+    use crate::{RssParser,FeedFormat,RSS_DEFAULT_CONTENT_TAG};
 
+    #[cfg(feature = "http")]
     #[test]
+    #[ignore = "fetches https://www.zhihu.com/rss, run with --ignored when online"]
     fn future_rss_works()->Result<(),Box<dyn std::error::Error>> {
         let address = "https://www.zhihu.com/rss";
         let mut parser = RssParser::from_url(address,"utf8")?;
         parser.author_tag = String::from("dc:creator");
         let rss = parser.parse_vec()?;
-        assert!(rss.len()>0);
+        assert!(!rss.is_empty());
         Ok(())
     }
 
+    #[cfg(feature = "http")]
     #[test]
+    #[ignore = "fetches https://www.zhihu.com/rss, run with --ignored when online"]
     fn future_rss_to_json(){
         let address = "https://www.zhihu.com/rss";
         let mut parser = RssParser::from_url(address,"utf8").unwrap();
@@ -401,6 +1775,355 @@ mod tests {
                 </rss>
         "#));
         let rss = parser.parse_vec().unwrap();
-        assert!(rss.len()>0);
+        assert!(!rss.is_empty());
+    }
+
+    #[test]
+    fn future_rss_borrowed(){
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0">
+                    <item>
+                        <title><![CDATA[Hey & <b>you</b>]]></title>
+                        <link>examples.com</link>
+                        <description>1 &lt; 2</description>
+                    </item>
+                </rss>
+        "#)).unwrap();
+        let rss = parser.parse_borrowed().unwrap();
+        assert!(matches!(rss[0].title,Cow::Borrowed("Hey & <b>you</b>")));
+        assert!(matches!(rss[0].link,Cow::Borrowed("examples.com")));
+        assert!(matches!(rss[0].description,Cow::Owned(ref d) if d == "1 < 2"));
+    }
+
+    #[test]
+    fn future_rss_parse_bytes(){
+        let parser = RssParser::new();
+        let body = b"<rss><item><title>Hey!</title></item><item><title>Bye</title></item></rss>".to_vec();
+        let rss = parser.parse_bytes(&body).unwrap();
+        assert_eq!(rss.len(),2);
+        assert_eq!(rss[1].title,"Bye");
+        assert!(parser.parse_bytes(b"<rss><item><title>\xff</title></item></rss>").is_err());
+    }
+
+    #[test]
+    fn future_rss_reuse_buffers(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from("<rss><item><title>Hey!</title></item></rss>"));
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"Hey!");
+        let capacity = parser.scratch.buff.capacity();
+        assert!(capacity > 0 && parser.scratch.buff.is_empty());
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"Hey!");
+        assert_eq!(parser.scratch.buff.capacity(),capacity);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_buffer_sizes(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let mut parser = RssParser::new();
+        parser.read_buffer = 16;
+        parser.event_buffer = 16 * 1024;
+        assert_eq!(parser.parse_url(&server.url("/feed"),"utf8").unwrap()[0].title,"Hey!");
+        assert!(parser.scratch.buff.capacity() >= 16 * 1024);
+    }
+
+    #[test]
+    fn future_rss_sitemap(){
+        let mut parser = RssParser::sitemap();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+                <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+                    <url>
+                        <loc>https://examples.com/a</loc>
+                        <lastmod>2020-05-28T15:00:00+00:00</lastmod>
+                        <changefreq>daily</changefreq>
+                    </url>
+                    <url><loc>https://examples.com/b</loc></url>
+                </urlset>
+        "#));
+        assert!(parser.check_xml());
+        let rss = parser.parse_vec().unwrap();
+        assert_eq!(rss.len(),2);
+        assert_eq!(rss[0].link,"https://examples.com/a");
+        assert_eq!(rss[0].publish,"2020-05-28T15:00:00+00:00");
+        assert_eq!(rss[1].key(),"https://examples.com/b");
+        assert!(rss[1].publish.is_empty());
+    }
+
+    #[test]
+    fn future_rss_utc_dates(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0">
+                    <item><title>1</title><pubDate>Thu, 28 May 2020 23:00:00 +0800</pubDate></item>
+                    <item><title>2</title><pubDate>2020-05-28T12:00:00-04:00</pubDate></item>
+                    <item><title>3</title><pubDate>last week</pubDate></item>
+                </rss>
+        "#));
+        let rss = parser.parse_vec().unwrap();
+        assert_eq!(rss[0].publish,"Thu, 28 May 2020 23:00:00 +0800");
+        assert!(rss[0].published_at() < rss[1].published_at());
+        assert_eq!(rss[2].published_at(),None);
+
+        parser.utc_dates = true;
+        let rss = parser.parse_vec().unwrap();
+        assert_eq!(rss[0].publish,"2020-05-28T15:00:00Z");
+        assert_eq!(rss[1].publish,"2020-05-28T16:00:00Z");
+        assert_eq!(rss[2].publish,"last week");
+        assert_eq!(parser.parse_borrowed().unwrap()[0].publish,"2020-05-28T15:00:00Z");
+        let json = json::parse(&parser.parse_json().unwrap()).unwrap();
+        assert_eq!(json[1]["publish"],"2020-05-28T16:00:00Z");
+    }
+
+    #[test]
+    fn future_rss_updated(){
+        let mut parser = RssParser::new();
+        parser.node_tag = String::from("entry");
+        parser.publish_tag = String::from("published");
+        parser.updated_tag = String::from("updated");
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+                <feed xmlns="http://www.w3.org/2005/Atom">
+                    <updated>2020-06-01T00:00:00Z</updated>
+                    <entry>
+                        <title>Hey!</title>
+                        <published>2020-05-28T15:00:00+08:00</published>
+                        <updated>2020-05-29T09:30:00+08:00</updated>
+                    </entry>
+                    <entry><title>Hello</title></entry>
+                </feed>
+        "#));
+        parser.utc_dates = true;
+        let rss = parser.parse_vec().unwrap();
+        assert_eq!(rss[0].publish,"2020-05-28T07:00:00Z");
+        assert_eq!(rss[0].updated,"2020-05-29T01:30:00Z");
+        assert!(rss[0].updated_at() > rss[0].published_at());
+        assert!(rss[1].updated.is_empty());
+        assert_eq!(json::parse(&rss[0].to_json()).unwrap()["updated"],"2020-05-29T01:30:00Z");
+    }
+
+    #[test]
+    fn future_rss_parse_first(){
+        let mut parser = RssParser::new();
+        // the document is broken after the second item
+        parser.set_xml(String::from("<rss><item><title>1</title></item><item><title>2</title></item><item><title></link>"));
+        assert!(parser.parse_vec().is_err());
+        let rss = parser.parse_first(2).unwrap();
+        assert_eq!(rss.len(),2);
+        assert_eq!(rss[1].title,"2");
+        assert!(parser.parse_first(0).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_parse_url_first(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let mut parser = RssParser::new();
+        let rss = parser.parse_url_first(&server.url("/feed"),"utf8",1).unwrap();
+        assert_eq!(rss.len(),1);
+        assert_eq!(rss[0].author,"MeteorCat");
+        assert_eq!(parser.parse_url(&server.url("/feed"),"gbk").unwrap(),rss);
+    }
+
+    #[test]
+    fn future_rss_parse_vec_async(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from("<rss><item><title>Hey!</title></item></rss>"));
+        #[cfg(feature = "rt-tokio")]
+        let rss = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .build()
+            .unwrap()
+            .block_on(parser.parse_vec_async())
+            .unwrap();
+        #[cfg(not(feature = "rt-tokio"))]
+        let rss = crate::runtime::tests::block_on(parser.parse_vec_async()).unwrap();
+        assert_eq!(rss[0].title,"Hey!");
+        assert!(!parser.get_xml().is_empty());
+        assert_eq!(parser.metrics().items,1);
+    }
+
+    #[cfg(all(feature = "http-async",feature = "rt-tokio"))]
+    #[test]
+    fn future_rss_from_url_async(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let mut runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap();
+        let mut parser = runtime.block_on(RssParser::from_url_async(&server.url("/feed"),"utf8")).unwrap();
+        assert_eq!(parser.parse_vec().unwrap()[0].author,"MeteorCat");
+        assert!(runtime.block_on(RssParser::from_url_async("http://127.0.0.1:1/feed","utf8")).is_err());
+
+        let client = crate::NetworkSettings::default().user_agent("reader/1.0").async_client().unwrap();
+        let mut parser = runtime.block_on(RssParser::from_url_async_with(&client,&server.url("/feed"),"utf8")).unwrap();
+        assert_eq!(parser.parse_vec().unwrap()[0].title,"Hey!");
+
+        parser.request = crate::RequestOptions::new().user_agent("reader/2.0");
+        runtime.block_on(parser.request_xml_async_with(&client,&server.url("/feed"),"utf8")).unwrap();
+        assert!(server.received().last().unwrap().contains("\r\nuser-agent: reader/2.0\r\n"));
+    }
+
+    #[cfg(all(feature = "http-async",feature = "rt-tokio"))]
+    #[test]
+    fn future_rss_from_urls(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        server.set_delay(std::time::Duration::from_millis(20));
+        let feed = server.url("/feed");
+        let urls = [feed.as_str(),"http://127.0.0.1:1/feed",feed.as_str(),feed.as_str()];
+        let results = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(RssParser::from_urls(&urls,"utf8",2));
+        assert_eq!(results.iter().map(Result::is_ok).collect::<Vec<_>>(),[true,false,true,true]);
+        assert_eq!(server.peak(),2);
+    }
+
+    #[cfg(all(feature = "http-async",feature = "rt-tokio"))]
+    #[test]
+    fn future_rss_from_urls_with_cancel(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        server.set_delay(std::time::Duration::from_secs(2));
+        let feed = server.url("/feed");
+        let urls = [feed.as_str(),feed.as_str(),feed.as_str()];
+        let token = crate::CancelToken::new();
+        let handle = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            handle.cancel();
+        });
+        let started = std::time::Instant::now();
+        let results = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(RssParser::from_urls_with_cancel(&urls,"utf8",2,&token));
+        // the two requests in flight are dropped, the third never starts
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        assert!(results.iter().all(|result| result.as_ref().unwrap_err().kind() == std::io::ErrorKind::Interrupted));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_from_url_with(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let client = crate::NetworkSettings::default().user_agent("reader/1.0").client().unwrap();
+        let mut parser = RssParser::from_url_with(&client,&server.url("/feed"),"utf8").unwrap();
+        assert_eq!(parser.parse_vec().unwrap()[0].author,"MeteorCat");
+        assert!(RssParser::from_url_with(&client,"http://127.0.0.1:1/feed","utf8").is_err());
+        assert_eq!(parser.request_xml_with(&client,&server.url("/feed"),"utf8").unwrap(),crate::mock::FEED);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_request_options(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let mut parser = RssParser::new();
+        parser.request = crate::RequestOptions::new()
+            .user_agent("reader/1.0")
+            .accept_language("zh-CN")
+            .header("X-Token","1");
+        let client = reqwest::blocking::Client::new();
+        parser.request_xml(&server.url("/feed"),"utf8").unwrap();
+        parser.request_xml_with(&client,&server.url("/feed"),"utf8").unwrap();
+        for head in server.received().iter().map(|head| head.to_lowercase()) {
+            assert!(head.contains("\r\nuser-agent: reader/1.0\r\n"));
+            assert!(head.contains("\r\naccept-language: zh-cn\r\n"));
+            assert!(head.contains("\r\nx-token: 1\r\n"));
+        }
+        assert_eq!(parser.parse_url(&server.url("/feed"),"utf8").unwrap()[0].title,"Hey!");
+    }
+
+    #[test]
+    fn future_rss_parse_from_reader(){
+        // a reader handing out a few bytes at a time, as a slow socket would
+        let body = std::io::BufReader::with_capacity(7,crate::mock::FEED.as_bytes());
+        let mut parser = RssParser::new();
+        let rss = parser.parse_from_reader(body).unwrap();
+        assert_eq!(rss,RssParser::from_str(String::from(crate::mock::FEED)).unwrap().parse_vec().unwrap());
+        assert_eq!(parser.metrics().items,1);
+        assert!(parser.get_xml().is_empty());
+
+        let items = parser.reader_items("<rss><item><title>1</title></item><item><title>2</title></item><item><title></link>".as_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(items.len(),3);
+        assert_eq!(items[1].as_ref().unwrap().title,"2");
+        assert!(items[2].is_err());
+    }
+
+    #[test]
+    fn future_rss_compressed_file(){
+        assert_eq!(crate::compression_format(&[0x1f,0x8b,0x08,0x00]),Some("gzip"));
+        assert_eq!(crate::compression_format(&[0x28,0xb5,0x2f,0xfd,0x00]),Some("zstd"));
+        assert_eq!(crate::compression_format(b"<?xml"),None);
+    }
+
+    #[test]
+    fn future_rss_parse_file(){
+        let path = std::env::temp_dir().join(format!("future_rss_parse_file_{}.xml",std::process::id()));
+        std::fs::write(&path,crate::mock::FEED).unwrap();
+        let mut parser = RssParser::new();
+        let rss = parser.parse_file(&path);
+        std::fs::write(&path,[0x1f,0x8b,0x08,0x00]).unwrap();
+        let compressed = parser.parse_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(rss.unwrap()[0].title,"Hey!");
+        assert_eq!(compressed.unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_item_channel(){
+        let server = crate::mock::serve(crate::mock::FEED);
+        let parser = RssParser::new();
+        let items = parser.item_channel(&server.url("/feed"),"utf8",1)
+            .into_iter()
+            .collect::<Result<Vec<_>,_>>()
+            .unwrap();
+        assert_eq!(items.len(),1);
+        assert_eq!(items[0].title,"Hey!");
+
+        let mut items = parser.item_channel("http://127.0.0.1:1/feed","utf8",1).into_iter();
+        assert!(items.next().unwrap().is_err());
+        assert!(items.next().is_none());
+    }
+
+    #[test]
+    fn future_rss_parse_many(){
+        let parser = RssParser::new();
+        let mut docs:Vec<String> = (0..32)
+            .map(|i| format!("<rss><item><title>{}</title></item></rss>",i))
+            .collect();
+        docs.push(String::from("<rss><item></rss>"));
+        let results = parser.parse_many(&docs);
+        assert_eq!(results.len(),33);
+        for (i,result) in results[..32].iter().enumerate() {
+            assert_eq!(result.as_ref().unwrap()[0].title,i.to_string());
+        }
+        assert!(results[32].is_err());
+    }
+
+    #[test]
+    fn future_rss_detect_format(){
+        let mut atom = RssParser::from_str(String::from(
+            r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom"><entry><title>Hey!</title><link href="x"/></entry></feed>"#
+        )).unwrap();
+        assert_eq!(atom.node_tag,"entry");
+        assert_eq!(atom.parse_vec().unwrap()[0].link,"x");
+
+        let mut parser = RssParser::atom();
+        parser.set_xml(String::from("<?xml version=\"1.0\"?><rss version=\"0.91\"><channel><item><title>1</title></item></channel></rss>"));
+        assert_eq!(parser.detect_format(),Some(FeedFormat::Rss));
+        assert_eq!((parser.node_tag.as_str(),parser.content_tag.as_str()),("item",RSS_DEFAULT_CONTENT_TAG));
+        parser.set_xml(String::from("<urlset><url><loc>x</loc></url></urlset>"));
+        assert_eq!(parser.detect_format(),None);
+        assert_eq!(parser.node_tag,"item");
     }
 }
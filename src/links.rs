@@ -0,0 +1,167 @@
+//!
+//! Item Links
+//!
+//! Besides its `<link>`, an item points at its comments page, a feed of its
+//! comments, enclosures and related pages; [`ItemLink`] keeps each with the
+//! role it plays so readers can offer "view comments" or "related" actions.
+//! Atom's `<link rel href/>` maps its `rel` onto the same roles.
+//!
+
+use crate::RssParser;
+use crate::extension::scan_items;
+
+/// &lt;wfw:commentRss&gt;...&lt;/wfw:commentRss&gt;
+pub static WFW_COMMENT_RSS_TAG:&str = "wfw:commentRss";
+
+///
+/// What a link of an item points at
+///
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub enum LinkRole{
+    /// The item itself, `<link>` or Atom's `rel="alternate"`
+    Alternate,
+    /// Page with the comments, `<comments>`
+    Comments,
+    /// Feed of the comments, `<wfw:commentRss>` or Atom's `rel="replies"`
+    CommentFeed,
+    /// Attached file, `<enclosure>`
+    Enclosure,
+    /// Related page, Atom's `rel="related"`
+    Related,
+    /// Any other Atom `rel`, lowercase
+    Other(String),
+}
+
+impl LinkRole{
+
+    /// Role of an Atom `rel`, `alternate` when it is missing
+    pub fn from_rel(rel:Option<&str>)->Self{
+        match rel.map(|rel| rel.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("alternate") => LinkRole::Alternate,
+            Some("replies") => LinkRole::CommentFeed,
+            Some("enclosure") => LinkRole::Enclosure,
+            Some("related") => LinkRole::Related,
+            Some(rel) => LinkRole::Other(rel.to_string()),
+        }
+    }
+}
+
+///
+/// Link of an item with its role
+///
+/// ```
+/// use future_rss::{RssParser,LinkRole};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0" xmlns:wfw="http://wellformedweb.org/CommentAPI/">
+///             <item>
+///                 <link>https://examples.com/1</link>
+///                 <comments>https://examples.com/1#comments</comments>
+///                 <wfw:commentRss>https://examples.com/1/feed</wfw:commentRss>
+///             </item>
+///         </rss>
+///         "#
+///     ))?;
+///     let links = parser.parse_links()?;
+///     let comments = links[0].iter().find(|link| link.role == LinkRole::Comments).unwrap();
+///     assert_eq!(comments.href,"https://examples.com/1#comments");
+///     assert_eq!(links[0].len(),3);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ItemLink{
+    pub role:LinkRole,
+    pub href:String,
+    /// MIME type the link announces, as found
+    pub media_type:Option<String>,
+    pub title:Option<String>,
+}
+
+impl ItemLink{
+
+    pub fn new(role:LinkRole,href:&str)->Self{
+        Self{ role, href:href.trim().to_string(), media_type:None, title:None }
+    }
+}
+
+///
+/// Links of every item of `xml` in document order, elements nested in
+/// others such as Atom's `<source>` are not the item's own
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<Vec<ItemLink>>,std::io::Error>{
+    let comment_rss = WFW_COMMENT_RSS_TAG.to_ascii_lowercase();
+    scan_items(parser,xml,"RssParser::parse_links",|links:&mut Vec<ItemLink>,element| {
+        if !element.parents.is_empty() {
+            return;
+        }
+        let optional = |key:&str| element.attribute(key).map(str::trim).filter(|value| !value.is_empty()).map(String::from);
+        let link = match element.name.as_str() {
+            "link" => match element.attribute("href") {
+                Some(href) => ItemLink{
+                    media_type:optional("type"),
+                    title:optional("title"),
+                    ..ItemLink::new(LinkRole::from_rel(element.attribute("rel")),href)
+                },
+                None => ItemLink::new(LinkRole::Alternate,&element.text),
+            },
+            "comments" => ItemLink::new(LinkRole::Comments,&element.text),
+            name if name == comment_rss => ItemLink::new(LinkRole::CommentFeed,&element.text),
+            "enclosure" => match element.attribute("url") {
+                Some(url) => ItemLink{ media_type:optional("type"), ..ItemLink::new(LinkRole::Enclosure,url) },
+                None => return,
+            },
+            _ => return,
+        };
+        if !link.href.is_empty() {
+            links.push(link);
+        }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,ItemLink,LinkRole};
+
+    #[test]
+    fn future_rss_item_links(){
+        let mut parser = RssParser::atom();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+                <feed xmlns="http://www.w3.org/2005/Atom">
+                    <entry>
+                        <link href="https://examples.com/1"/>
+                        <link rel="related" href="https://examples.com/0" title="Part 0"/>
+                        <link rel="replies" type="application/atom+xml" href="https://examples.com/1/comments"/>
+                        <link rel="enclosure" type="audio/mpeg" href="https://examples.com/1.mp3"/>
+                        <link rel="Edit" href="https://examples.com/1/edit"/>
+                        <source><link href="https://elsewhere.com/"/></source>
+                    </entry>
+                </feed>
+        "#));
+        let links = parser.parse_links().unwrap();
+        assert_eq!(links[0].iter().map(|link| link.role.clone()).collect::<Vec<_>>(),[
+            LinkRole::Alternate,
+            LinkRole::Related,
+            LinkRole::CommentFeed,
+            LinkRole::Enclosure,
+            LinkRole::Other(String::from("edit")),
+        ]);
+        assert_eq!(links[0][1].title.as_deref(),Some("Part 0"));
+        assert_eq!(links[0][3].media_type.as_deref(),Some("audio/mpeg"));
+
+        parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<rss><channel>
+                <item><link> https://examples.com/2 </link><comments></comments><enclosure url="https://examples.com/2.ogg" type="audio/ogg"/></item>
+            </channel></rss>"#
+        ));
+        assert_eq!(parser.parse_links().unwrap(),[vec![
+            ItemLink::new(LinkRole::Alternate,"https://examples.com/2"),
+            ItemLink{ media_type:Some(String::from("audio/ogg")),..ItemLink::new(LinkRole::Enclosure,"https://examples.com/2.ogg") },
+        ]]);
+    }
+}
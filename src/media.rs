@@ -0,0 +1,210 @@
+//!
+//! Media RSS Extension
+//!
+//! Video sites and news feeds describe their media with the `media:`
+//! namespace: `<media:content>` for each rendition of a file, with its size,
+//! type and dimensions, `<media:thumbnail>` for preview images and
+//! `<media:group>` to bundle renditions of the same media. [`MediaExtension`]
+//! holds them typed and keeps the groups apart.
+//!
+
+use std::time::Duration;
+use crate::{RssParser,MediaType};
+use crate::extension::{scan_items,Element};
+
+/// &lt;media:content url/&gt;
+pub static MEDIA_CONTENT_TAG:&str = "media:content";
+
+/// &lt;media:thumbnail url/&gt;
+pub static MEDIA_THUMBNAIL_TAG:&str = "media:thumbnail";
+
+/// &lt;media:group&gt;...&lt;/media:group&gt;
+pub static MEDIA_GROUP_TAG:&str = "media:group";
+
+///
+/// One rendition of a media object, attributes that are missing or do not
+/// parse are `None`
+///
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct MediaContent{
+    pub url:String,
+    /// `fileSize` in bytes
+    pub file_size:Option<u64>,
+    pub media_type:Option<MediaType>,
+    /// `image`, `audio`, `video`, `document` or `executable`, lowercase
+    pub medium:Option<String>,
+    pub width:Option<u32>,
+    pub height:Option<u32>,
+    pub duration:Option<Duration>,
+    /// Rendition to pick in its group, `isDefault="true"`
+    pub is_default:bool,
+}
+
+///
+/// Preview image
+///
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct MediaThumbnail{
+    pub url:String,
+    pub width:Option<u32>,
+    pub height:Option<u32>,
+}
+
+///
+/// Renditions of the same media of a `<media:group>`
+///
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct MediaGroup{
+    pub contents:Vec<MediaContent>,
+    pub thumbnails:Vec<MediaThumbnail>,
+}
+
+///
+/// Media RSS elements of one item, in the order of [`RssParser::parse_vec`]
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let parser = RssParser::from_str(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+///             <item>
+///                 <title>Launch</title>
+///                 <media:group>
+///                     <media:content url="https://examples.com/480.mp4" type="video/mp4" height="480"/>
+///                     <media:content url="https://examples.com/1080.mp4" type="video/mp4" height="1080" isDefault="true"/>
+///                     <media:thumbnail url="https://examples.com/launch.jpg" width="320" height="180"/>
+///                 </media:group>
+///             </item>
+///         </rss>
+///         "#
+///     ))?;
+///     let media = parser.parse_media()?;
+///     assert_eq!(media[0].groups[0].contents.len(),2);
+///     assert_eq!(media[0].default_content().unwrap().height,Some(1080));
+///     assert_eq!(media[0].thumbnail().unwrap().url,"https://examples.com/launch.jpg");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct MediaExtension{
+    /// Contents outside of any group
+    pub contents:Vec<MediaContent>,
+    /// Thumbnails outside of any group
+    pub thumbnails:Vec<MediaThumbnail>,
+    pub groups:Vec<MediaGroup>,
+}
+
+impl MediaExtension{
+
+    pub fn is_empty(&self)->bool{
+        self.contents.is_empty() && self.thumbnails.is_empty() && self.groups.is_empty()
+    }
+
+    /// Every content, the ones outside of groups first
+    pub fn all_contents(&self)->impl Iterator<Item=&MediaContent>{
+        self.contents.iter().chain(self.groups.iter().flat_map(|group| group.contents.iter()))
+    }
+
+    /// Content marked `isDefault`, else the first one
+    pub fn default_content(&self)->Option<&MediaContent>{
+        self.all_contents().find(|content| content.is_default).or_else(|| self.all_contents().next())
+    }
+
+    /// First thumbnail, the ones outside of groups first
+    pub fn thumbnail(&self)->Option<&MediaThumbnail>{
+        self.thumbnails.iter().chain(self.groups.iter().flat_map(|group| group.thumbnails.iter())).next()
+    }
+}
+
+fn number<T:std::str::FromStr>(element:&Element,key:&str)->Option<T>{
+    element.attribute(key).and_then(|value| value.trim().parse().ok())
+}
+
+fn url(element:&Element)->Option<String>{
+    element.attribute("url").map(str::trim).filter(|url| !url.is_empty()).map(String::from)
+}
+
+///
+/// Media elements of every item of `xml`, thumbnails of a content count
+/// for the group or item around it
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<MediaExtension>,std::io::Error>{
+    // children are read before their parent: a group's elements wait in `open`
+    let items = scan_items(parser,xml,"RssParser::parse_media",|(media,open):&mut (MediaExtension,MediaGroup),element| {
+        let grouped = element.parents.iter().any(|parent| parent == MEDIA_GROUP_TAG);
+        match element.name.as_str() {
+            name if name == MEDIA_CONTENT_TAG => {
+                let url = match url(element) { Some(url) => url, None => return };
+                let content = MediaContent{
+                    url,
+                    file_size:number(element,"filesize"),
+                    media_type:element.attribute("type").and_then(MediaType::parse),
+                    medium:element.attribute("medium").map(|medium| medium.trim().to_ascii_lowercase()).filter(|medium| !medium.is_empty()),
+                    width:number(element,"width"),
+                    height:number(element,"height"),
+                    duration:number::<f64>(element,"duration").and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()),
+                    is_default:element.attribute("isdefault").is_some_and(|value| value.trim().eq_ignore_ascii_case("true")),
+                };
+                if grouped { open.contents.push(content) }else { media.contents.push(content) }
+            }
+            name if name == MEDIA_THUMBNAIL_TAG => {
+                let url = match url(element) { Some(url) => url, None => return };
+                let thumbnail = MediaThumbnail{ url, width:number(element,"width"), height:number(element,"height") };
+                if grouped { open.thumbnails.push(thumbnail) }else { media.thumbnails.push(thumbnail) }
+            }
+            name if name == MEDIA_GROUP_TAG => media.groups.push(std::mem::take(open)),
+            _ => (),
+        }
+    })?;
+    Ok(items.into_iter().map(|(media,_)| media).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::{RssParser,MediaContent,MediaThumbnail};
+
+    #[test]
+    fn future_rss_media_items(){
+        let parser = RssParser::from_str(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+                    <channel>
+                        <media:thumbnail url="https://examples.com/channel.jpg"/>
+                        <item>
+                            <title>1</title>
+                            <media:content url="https://examples.com/1.mp3?a=1&amp;b=2" fileSize="1024" type="audio/mpeg" medium="Audio" duration="61.5">
+                                <media:thumbnail url="https://examples.com/1.jpg" width="wide"/>
+                            </media:content>
+                            <media:group>
+                                <media:content url="https://examples.com/1.ogg" fileSize="big" type="ogg"/>
+                                <media:content/>
+                            </media:group>
+                            <media:group><media:thumbnail url="https://examples.com/1b.jpg" height="90"/></media:group>
+                        </item>
+                        <item><title>2</title></item>
+                    </channel>
+                </rss>
+        "#)).unwrap();
+        let media = parser.parse_media().unwrap();
+        assert_eq!(media.len(),2);
+        assert_eq!(media[0].contents,[MediaContent{
+            url:String::from("https://examples.com/1.mp3?a=1&b=2"),
+            file_size:Some(1024),
+            media_type:crate::MediaType::parse("audio/mpeg"),
+            medium:Some(String::from("audio")),
+            duration:Some(Duration::from_millis(61500)),
+            ..MediaContent::default()
+        }]);
+        assert_eq!(media[0].thumbnails,[MediaThumbnail{ url:String::from("https://examples.com/1.jpg"),..MediaThumbnail::default() }]);
+        assert_eq!(media[0].groups.len(),2);
+        assert_eq!((media[0].groups[0].contents[0].file_size,media[0].groups[0].contents[0].media_type.as_ref()),(None,None));
+        assert_eq!(media[0].groups[1].thumbnails[0].height,Some(90));
+        assert_eq!(media[0].all_contents().count(),2);
+        assert_eq!(media[0].default_content().unwrap().file_size,Some(1024));
+        assert!(media[1].is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+//!
+//! Prometheus Metrics
+//!
+//! Every poll and parse of the crate is counted in a process-wide
+//! [`AggregatorMetrics`], rendered in the Prometheus text format so a
+//! service only has to serve [`AggregatorMetrics::render`] on its scrape
+//! endpoint.
+//!
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64,Ordering};
+use std::time::Duration;
+
+/// Upper bounds in seconds of the parse latency histogram buckets
+pub static METRICS_PARSE_BUCKETS:[f64;8] = [0.001,0.005,0.01,0.05,0.1,0.5,1.0,5.0];
+
+static GLOBAL:AggregatorMetrics = AggregatorMetrics::new();
+
+///
+/// Aggregator counters, see [`metrics`] for the instance the crate records into
+///
+/// ```
+/// use future_rss::metrics;
+///
+/// fn main(){
+///     let text = metrics().render();
+///     assert!(text.contains("future_rss_feeds_refreshed_total"));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AggregatorMetrics{
+    refreshed:AtomicU64,
+    items:AtomicU64,
+    errors:AtomicU64,
+    not_modified:AtomicU64,
+    parse_buckets:[AtomicU64;8],
+    parse_count:AtomicU64,
+    parse_sum_us:AtomicU64,
+}
+
+///
+/// Metrics recorded by every watcher and parser of the process
+///
+pub fn metrics()->&'static AggregatorMetrics{
+    &GLOBAL
+}
+
+impl Default for AggregatorMetrics{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AggregatorMetrics{
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    pub const fn new()->Self{
+        const ZERO:AtomicU64 = AtomicU64::new(0);
+        Self{
+            refreshed:ZERO,
+            items:ZERO,
+            errors:ZERO,
+            not_modified:ZERO,
+            parse_buckets:[ZERO;8],
+            parse_count:ZERO,
+            parse_sum_us:ZERO,
+        }
+    }
+
+    /// Feed downloaded and parsed, `items` new or updated entries found
+    pub fn record_fetched(&self,items:usize){
+        self.refreshed.fetch_add(1,Ordering::Relaxed);
+        self.items.fetch_add(items as u64,Ordering::Relaxed);
+    }
+
+    /// Server answered 304
+    pub fn record_not_modified(&self){
+        self.refreshed.fetch_add(1,Ordering::Relaxed);
+        self.not_modified.fetch_add(1,Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self){
+        self.refreshed.fetch_add(1,Ordering::Relaxed);
+        self.errors.fetch_add(1,Ordering::Relaxed);
+    }
+
+    pub fn record_parse(&self,duration:Duration){
+        let seconds = duration.as_secs_f64();
+        for (bound,bucket) in METRICS_PARSE_BUCKETS.iter().zip(self.parse_buckets.iter()) {
+            if seconds <= *bound {
+                bucket.fetch_add(1,Ordering::Relaxed);
+            }
+        }
+        self.parse_count.fetch_add(1,Ordering::Relaxed);
+        self.parse_sum_us.fetch_add(duration.as_micros() as u64,Ordering::Relaxed);
+    }
+
+    /// Share of polls answered with 304, 0 before the first poll
+    pub fn not_modified_ratio(&self)->f64{
+        let refreshed = self.refreshed.load(Ordering::Relaxed);
+        match refreshed {
+            0 => 0.0,
+            _ => self.not_modified.load(Ordering::Relaxed) as f64 / refreshed as f64,
+        }
+    }
+
+    ///
+    /// Prometheus text exposition of every metric
+    ///
+    pub fn render(&self)->String{
+        let mut out = String::new();
+        let counters = [
+            ("future_rss_feeds_refreshed_total","Feeds polled",&self.refreshed),
+            ("future_rss_items_discovered_total","New and updated items found",&self.items),
+            ("future_rss_fetch_errors_total","Polls that failed",&self.errors),
+            ("future_rss_not_modified_total","Polls answered with 304",&self.not_modified),
+        ];
+        for (name,help,value) in counters.iter() {
+            let _ = write!(out,"# HELP {} {}\n# TYPE {} counter\n{} {}\n",
+                name,help,name,name,value.load(Ordering::Relaxed));
+        }
+
+        let name = "future_rss_not_modified_ratio";
+        let _ = write!(out,"# HELP {} Share of polls answered with 304\n# TYPE {} gauge\n{} {}\n",
+            name,name,name,self.not_modified_ratio());
+
+        let name = "future_rss_parse_duration_seconds";
+        let _ = write!(out,"# HELP {} Time spent parsing one document\n# TYPE {} histogram\n",name,name);
+        for (bound,bucket) in METRICS_PARSE_BUCKETS.iter().zip(self.parse_buckets.iter()) {
+            let _ = writeln!(out,"{}_bucket{{le=\"{}\"}} {}",name,bound,bucket.load(Ordering::Relaxed));
+        }
+        let count = self.parse_count.load(Ordering::Relaxed);
+        let sum = self.parse_sum_us.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+        let _ = write!(out,"{}_bucket{{le=\"+Inf\"}} {}\n{}_sum {}\n{}_count {}\n",name,count,name,sum,name,count);
+        out
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::AggregatorMetrics;
+
+    #[test]
+    fn future_rss_metrics_render(){
+        let metrics = AggregatorMetrics::new();
+        metrics.record_fetched(3);
+        metrics.record_not_modified();
+        metrics.record_parse(Duration::from_millis(2));
+
+        let text = metrics.render();
+        assert!(text.contains("future_rss_feeds_refreshed_total 2\n"));
+        assert!(text.contains("future_rss_items_discovered_total 3\n"));
+        assert!(text.contains("future_rss_not_modified_ratio 0.5\n"));
+        assert!(text.contains("future_rss_parse_duration_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("future_rss_parse_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("future_rss_parse_duration_seconds_count 1\n"));
+    }
+}
@@ -0,0 +1,106 @@
+//!
+//! Memory-Mapped Feed Files
+//!
+//! Maps a file read-only so multi-hundred-MB archives are parsed straight from
+//! the page cache instead of being copied into a `String` first.
+//!
+
+use std::fs::File;
+use std::ops::Deref;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+///
+/// Read-only mapping of a whole file, unmapped on drop
+///
+/// ```no_run
+/// use future_rss::{MappedFile,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     // nothing writes to or truncates the archive while it is mapped
+///     let mapped = unsafe { MappedFile::open("archive.xml")? };
+///     let items = RssParser::new().parse_bytes(&mapped)?;
+///     println!("{}",items.len());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MappedFile{
+    ptr:*mut libc::c_void,
+    len:usize,
+}
+
+// the mapping is private and read-only, sharing it between threads is safe
+unsafe impl Send for MappedFile{}
+unsafe impl Sync for MappedFile{}
+
+impl MappedFile{
+
+    ///
+    /// Map the file at `path`
+    ///
+    /// # Safety
+    ///
+    /// The bytes are read straight from the file for as long as the mapping
+    /// lives: the caller must make sure no process writes to or truncates the
+    /// file until it is dropped, or the slices handed out change under their
+    /// borrowers and reading past a truncated end raises `SIGBUS`.
+    ///
+    pub unsafe fn open<P:AsRef<Path>>(path:P)->Result<Self,std::io::Error>{
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            // zero length mappings are rejected by mmap
+            return Ok(Self{ ptr:std::ptr::null_mut(), len:0 });
+        }
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ,
+            libc::MAP_PRIVATE,
+            file.as_raw_fd(),
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self{ ptr, len })
+    }
+}
+
+impl Deref for MappedFile{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8,self.len) }
+    }
+}
+
+impl Drop for MappedFile{
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe { libc::munmap(self.ptr,self.len); }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::prelude::*;
+    use crate::RssParser;
+
+    #[test]
+    fn future_rss_mmap()->Result<(),Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("future_rss_mmap_{}.xml",std::process::id()));
+        std::fs::File::create(&path)?
+            .write_all(b"<rss><item><title>Hey!</title></item></rss>")?;
+        let rss = unsafe { RssParser::new().parse_file_mmap(&path) };
+        std::fs::remove_file(&path)?;
+        assert_eq!(rss?[0].title,"Hey!");
+        Ok(())
+    }
+}
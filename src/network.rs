@@ -0,0 +1,263 @@
+//!
+//! Network Settings
+//!
+//! Every client the crate creates on its own comes from [`NetworkSettings`]:
+//! the standard `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables and the
+//! `FUTURE_RSS_*` ones below are honoured, so containers can be tuned without
+//! code changes, while values set in code win over the environment.
+//!
+
+use std::time::Duration;
+#[cfg(feature = "http")]
+use reqwest::blocking::Client;
+
+/// User-agent of every request
+pub static ENV_USER_AGENT:&str = "FUTURE_RSS_USER_AGENT";
+
+/// Whole request timeout in seconds
+pub static ENV_TIMEOUT:&str = "FUTURE_RSS_TIMEOUT";
+
+/// Connection timeout in seconds
+pub static ENV_CONNECT_TIMEOUT:&str = "FUTURE_RSS_CONNECT_TIMEOUT";
+
+/// Whole request timeout when neither the code nor [`ENV_TIMEOUT`] sets one,
+/// the one reqwest's blocking client uses by default
+pub static NETWORK_DEFAULT_TIMEOUT:Duration = Duration::from_secs(30);
+
+///
+/// Where requests go
+///
+#[derive(Debug,Clone,PartialEq)]
+pub enum ProxyMode{
+    /// `HTTP_PROXY` / `HTTPS_PROXY`, minus the hosts of `NO_PROXY`
+    Env,
+    /// Straight to the server, whatever the environment says
+    Direct,
+    /// Through this proxy, minus the hosts of [`NetworkSettings::no_proxy`]
+    Url(String),
+}
+
+///
+/// Client settings read from the environment, overridable in code
+///
+/// ```
+/// use std::time::Duration;
+/// use future_rss::{NetworkSettings,ProxyMode};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let client = NetworkSettings::from_env()
+///         .timeout(Duration::from_secs(30))
+///         .proxy(ProxyMode::Url(String::from("http://proxy.internal:3128")))
+///         .client()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct NetworkSettings{
+    pub user_agent:Option<String>,
+    /// Bound on the whole request, reading the body included,
+    /// [`NETWORK_DEFAULT_TIMEOUT`] when not set
+    pub timeout:Option<Duration>,
+    /// Bound on opening the connection, so an unreachable host fails
+    /// before [`NetworkSettings::timeout`]
+    pub connect_timeout:Option<Duration>,
+    pub proxy:ProxyMode,
+    /// Hosts reached without the proxy: `example.com` also covers its
+    /// subdomains, `*` covers everything
+    pub no_proxy:Vec<String>,
+}
+
+impl Default for NetworkSettings{
+    fn default() -> Self {
+        Self{
+            user_agent:None,
+            timeout:None,
+            connect_timeout:None,
+            proxy:ProxyMode::Env,
+            no_proxy:Vec::new(),
+        }
+    }
+}
+
+impl NetworkSettings{
+
+    /// Settings from [`ENV_USER_AGENT`], [`ENV_TIMEOUT`], [`ENV_CONNECT_TIMEOUT`]
+    /// and `NO_PROXY`
+    pub fn from_env()->Self{
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup<F:Fn(&str)->Option<String>>(lookup:F)->Self{
+        let value = |name:&str| lookup(name).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let seconds = |name:&str| value(name)
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(|v| Duration::try_from_secs_f64(v).ok());
+        Self{
+            user_agent:value(ENV_USER_AGENT),
+            timeout:seconds(ENV_TIMEOUT),
+            connect_timeout:seconds(ENV_CONNECT_TIMEOUT),
+            proxy:ProxyMode::Env,
+            no_proxy:value("NO_PROXY").or_else(|| value("no_proxy"))
+                .map(|v| v.split(',').map(|h| h.trim().to_string()).filter(|h| !h.is_empty()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn user_agent(mut self,user_agent:&str)->Self{
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    pub fn timeout(mut self,timeout:Duration)->Self{
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self,timeout:Duration)->Self{
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self,proxy:ProxyMode)->Self{
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn no_proxy(mut self,hosts:&[&str])->Self{
+        self.no_proxy = hosts.iter().map(|h| h.to_string()).collect();
+        self
+    }
+
+    /// Whole request timeout the clients get, never unbounded
+    pub fn request_timeout(&self)->Duration{
+        self.timeout.unwrap_or(NETWORK_DEFAULT_TIMEOUT)
+    }
+
+    /// Host is listed in [`NetworkSettings::no_proxy`]
+    pub fn bypasses_proxy(&self,host:&str)->bool{
+        bypasses(&self.no_proxy,host)
+    }
+
+    #[cfg(feature = "http")]
+    pub fn client(&self)->Result<Client,std::io::Error>{
+        self.client_builder()?.build().map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    /// Builder of [`NetworkSettings::client`], for clients that need more on top
+    #[cfg(feature = "http")]
+    pub(crate) fn client_builder(&self)->Result<reqwest::blocking::ClientBuilder,std::io::Error>{
+        let mut builder = Client::builder().timeout(self.request_timeout()).connect_timeout(self.connect_timeout);
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        builder = match &self.proxy {
+            ProxyMode::Env => builder,
+            ProxyMode::Direct => builder.no_proxy(),
+            ProxyMode::Url(url) => builder.proxy(self.custom_proxy(url)?),
+        };
+        Ok(builder)
+    }
+
+    ///
+    /// Async client with the same settings, it has to be polled inside a
+    /// tokio runtime
+    ///
+    pub fn async_client(&self)->Result<reqwest::Client,std::io::Error>{
+        let mut builder = reqwest::Client::builder().timeout(self.request_timeout());
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(agent) = &self.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        builder = match &self.proxy {
+            ProxyMode::Env => builder,
+            ProxyMode::Direct => builder.no_proxy(),
+            ProxyMode::Url(url) => builder.proxy(self.custom_proxy(url)?),
+        };
+        builder.build().map_err(|e| std::io::Error::other(e.to_string()))
+    }
+
+    fn custom_proxy(&self,url:&str)->Result<reqwest::Proxy,std::io::Error>{
+        let scheme = reqwest::Url::parse(url).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput,e.to_string()))?;
+        let no_proxy = self.no_proxy.clone();
+        Ok(reqwest::Proxy::custom(move |target| match target.host_str() {
+            Some(host) if bypasses(&no_proxy,host) => None,
+            _ => Some(scheme.clone()),
+        }))
+    }
+}
+
+fn bypasses(no_proxy:&[String],host:&str)->bool{
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+    no_proxy.iter().any(|entry| {
+        let entry = entry.trim_start_matches('.').to_ascii_lowercase();
+        entry == "*" || host == entry || host.ends_with(&format!(".{}",entry))
+    })
+}
+
+///
+/// Client used where the caller passes none, falls back to reqwest's
+/// defaults when the environment holds unusable values
+///
+#[cfg(feature = "http")]
+pub(crate) fn default_client()->Client{
+    NetworkSettings::from_env().client().unwrap_or_default()
+}
+
+///
+/// Async counterpart of [`default_client`]
+///
+pub(crate) fn default_async_client()->reqwest::Client{
+    NetworkSettings::from_env().async_client().unwrap_or_default()
+}
+
+
+#[cfg(all(test,feature = "http"))]
+mod tests {
+    use std::time::Duration;
+    use crate::{NetworkSettings,ProxyMode,HttpFetcher,NETWORK_DEFAULT_TIMEOUT};
+    use crate::mock::{serve,FEED};
+
+    #[test]
+    fn future_rss_network_env(){
+        let settings = NetworkSettings::from_lookup(|name| match name {
+            "FUTURE_RSS_USER_AGENT" => Some(String::from("reader/1.0")),
+            "FUTURE_RSS_TIMEOUT" => Some(String::from("2.5")),
+            "FUTURE_RSS_CONNECT_TIMEOUT" => Some(String::from("0.5")),
+            "no_proxy" => Some(String::from("localhost, .internal,")),
+            _ => None,
+        });
+        assert_eq!(settings.user_agent.as_deref(),Some("reader/1.0"));
+        assert_eq!(settings.timeout,Some(Duration::from_millis(2500)));
+        assert_eq!(settings.connect_timeout,Some(Duration::from_millis(500)));
+        assert!(settings.bypasses_proxy("LOCALHOST"));
+        assert!(settings.bypasses_proxy("feeds.internal"));
+        assert!(!settings.bypasses_proxy("example.com"));
+        assert_eq!(NetworkSettings::from_lookup(|_| None).proxy,ProxyMode::Env);
+        assert_eq!(NetworkSettings::from_lookup(|_| None).request_timeout(),NETWORK_DEFAULT_TIMEOUT);
+        assert_eq!(settings.request_timeout(),Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn future_rss_network_timeout(){
+        // the clients hold the request timeout of the settings
+        let server = serve(FEED);
+        server.set_delay(Duration::from_millis(500));
+        let client = NetworkSettings::default().timeout(Duration::from_millis(100)).client().unwrap();
+        assert!(client.fetch(&server.url("/feed"),&[]).is_err());
+    }
+
+    #[test]
+    fn future_rss_network_proxy(){
+        // the test server answers every request, proxied ones included
+        let proxy = serve(FEED);
+        let settings = NetworkSettings::default()
+            .user_agent("reader/1.0")
+            .proxy(ProxyMode::Url(proxy.url("")))
+            .no_proxy(&["direct.invalid"]);
+        let client = settings.client().unwrap();
+        assert_eq!(client.fetch("http://feeds.invalid/rss",&[]).unwrap().status,200);
+        assert!(client.fetch("http://direct.invalid/rss",&[]).is_err());
+    }
+}
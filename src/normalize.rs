@@ -0,0 +1,180 @@
+//!
+//! URL Normalization
+//!
+//! The same article is often linked as `HTTP://Example.com:80/a%7eb` by one
+//! feed and `http://example.com/a~b` by another; [`UrlNormalizer`] rewrites
+//! both to one canonical form so deduplication by link holds across sources.
+//!
+
+use url::Url;
+use crate::RssItem;
+
+/// Query parameters added by campaign and click trackers
+pub static URL_TRACKING_PARAMS:&[&str] = &[
+    "utm_source","utm_medium","utm_campaign","utm_term","utm_content","utm_id","utm_name",
+    "gclid","dclid","fbclid","msclkid","yclid","igshid","mc_cid","mc_eid","ref_src","_hsenc","_hsmi",
+];
+
+///
+/// URL Normalizer
+///
+/// Lowercases scheme and host, encodes internationalized domains as punycode,
+/// drops default ports and dot segments, and rewrites percent-encoding to
+/// uppercase hex with unreserved characters decoded.
+///
+/// ```
+/// use future_rss::UrlNormalizer;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let normalizer = UrlNormalizer::new().strip_tracking(true);
+///     let url = normalizer.normalize("HTTPS://Bücher.example:443/a/../%7euser?id=1&utm_source=rss")?;
+///     assert_eq!(url,"https://xn--bcher-kva.example/~user?id=1");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,Default)]
+pub struct UrlNormalizer{
+    strip_tracking:bool,
+    strip_fragment:bool,
+    sort_query:bool,
+}
+
+impl UrlNormalizer{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    /// Drop the parameters of [`URL_TRACKING_PARAMS`] and every `utm_*`
+    pub fn strip_tracking(mut self,strip:bool)->Self{
+        self.strip_tracking = strip;
+        self
+    }
+
+    /// Drop the `#fragment`
+    pub fn strip_fragment(mut self,strip:bool)->Self{
+        self.strip_fragment = strip;
+        self
+    }
+
+    /// Order query parameters by name, for sites that build them in any order
+    pub fn sort_query(mut self,sort:bool)->Self{
+        self.sort_query = sort;
+        self
+    }
+
+    ///
+    /// Canonical form of an absolute url, `InvalidInput` when it does not parse
+    ///
+    pub fn normalize(&self,url:&str)->Result<String,std::io::Error>{
+        let mut parsed = Url::parse(url.trim())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput,format!("Failed by UrlNormalizer: {}: {}",url,e)))?;
+        if parsed.cannot_be_a_base() {
+            return Ok(parsed.into());
+        }
+
+        let path = normalize_percent(parsed.path());
+        parsed.set_path(&path);
+
+        let query = parsed.query().map(|query| {
+            let mut pairs = query.split('&')
+                .filter(|pair| !pair.is_empty())
+                .filter(|pair| !self.strip_tracking || !is_tracking(pair.split('=').next().unwrap_or("")))
+                .map(normalize_percent)
+                .collect::<Vec<_>>();
+            if self.sort_query {
+                pairs.sort_by(|a,b| a.split('=').next().cmp(&b.split('=').next()));
+            }
+            pairs.join("&")
+        });
+        parsed.set_query(query.as_deref().filter(|q| !q.is_empty()));
+
+        if self.strip_fragment {
+            parsed.set_fragment(None);
+        }else if let Some(fragment) = parsed.fragment().map(normalize_percent) {
+            parsed.set_fragment(Some(&fragment));
+        }
+        Ok(parsed.into())
+    }
+
+    ///
+    /// Normalize the link of `item`, and its guid when that is a url too;
+    /// values that are not absolute urls are left as they are
+    ///
+    pub fn apply(&self,item:&mut RssItem){
+        for value in [&mut item.link,&mut item.guid] {
+            if let Ok(normalized) = self.normalize(value) {
+                *value = normalized;
+            }
+        }
+    }
+}
+
+fn is_tracking(name:&str)->bool{
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || URL_TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// Uppercase the hex of every escape and decode the unreserved characters
+fn normalize_percent(value:&str)->String{
+    let bytes = value.as_bytes();
+    let mut out = String::with_capacity(value.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = match bytes.get(i..i + 3) {
+            Some([b'%',hi,lo]) if hi.is_ascii_hexdigit() && lo.is_ascii_hexdigit() => {
+                u8::from_str_radix(&value[i + 1..i + 3],16).ok()
+            }
+            _ => None,
+        };
+        match escape {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                out.push(byte as char);
+                i += 3;
+            }
+            Some(byte) => {
+                out.push_str(&format!("%{:02X}",byte));
+                i += 3;
+            }
+            None => {
+                let c = value[i..].chars().next().unwrap_or_default();
+                out.push(c);
+                i += c.len_utf8().max(1);
+            }
+        }
+    }
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{UrlNormalizer,RssItem};
+
+    #[test]
+    fn future_rss_normalize_url(){
+        let plain = UrlNormalizer::new();
+        assert_eq!(plain.normalize("HTTP://Example.COM:80/a/./b/../c%2f%7E?q=%e4#Top").unwrap(),"http://example.com/a/c%2F~?q=%E4#Top");
+        assert_eq!(plain.normalize("https://例え.jp/記事").unwrap(),"https://xn--r8jz45g.jp/%E8%A8%98%E4%BA%8B");
+        assert_eq!(plain.normalize("https://example.com:8443").unwrap(),"https://example.com:8443/");
+        assert_eq!(plain.normalize("https://example.com/?utm_source=x").unwrap(),"https://example.com/?utm_source=x");
+        assert!(plain.normalize("/relative").is_err());
+
+        let strict = UrlNormalizer::new().strip_tracking(true).strip_fragment(true).sort_query(true);
+        assert_eq!(strict.normalize("https://example.com/?utm_source=x&UTM_Medium=y&fbclid=1").unwrap(),"https://example.com/");
+        assert_eq!(strict.normalize("https://example.com/?b=2&utm_term=t&a=1#x").unwrap(),"https://example.com/?a=1&b=2");
+        assert_eq!(
+            strict.normalize("http://EXAMPLE.com:80/x?id=1&utm_campaign=rss").unwrap(),
+            strict.normalize("http://example.com/x?id=1").unwrap()
+        );
+
+        let mut item = RssItem{
+            link:String::from("HTTPS://Example.com:443/a?utm_source=rss"),
+            guid:String::from("tag:example.com,2020:1"),
+            ..RssItem::default()
+        };
+        strict.apply(&mut item);
+        assert_eq!(item.link,"https://example.com/a");
+        assert_eq!(item.guid,"tag:example.com,2020:1");
+    }
+}
@@ -0,0 +1,194 @@
+//!
+//! Chat Notifiers
+//!
+//! [`WebhookNotifier`] is an [`RssItemSink`] posting every new item to a
+//! Slack or Discord incoming webhook, or to a chat through the Telegram bot
+//! API, so a [`Pipeline`](crate::Pipeline) can notify without extra code.
+//!
+
+use json::object;
+use reqwest::blocking::Client;
+use crate::{RssItem,FeedEvent,RssItemSink};
+
+/// Telegram bot API endpoint, the bot token is appended
+pub static TELEGRAM_DEFAULT_API:&str = "https://api.telegram.org";
+
+/// Longest message accepted by Discord, longer ones are cut
+pub static DISCORD_MAX_CONTENT:usize = 2000;
+
+///
+/// Where a [`WebhookNotifier`] posts to
+///
+#[derive(Debug,Clone,PartialEq)]
+pub enum Webhook{
+    /// Incoming webhook url
+    Slack(String),
+    /// Channel webhook url
+    Discord(String),
+    /// Bot token and the chat receiving the messages
+    Telegram{ token:String, chat_id:String },
+}
+
+///
+/// Webhook Notifier
+///
+/// ```no_run
+/// use future_rss::{Pipeline,FeedWatcher,WebhookNotifier};
+///
+/// fn main(){
+///     let mut feeds = vec![FeedWatcher::new("https://www.zhihu.com/rss","utf8")];
+///     let notifier = WebhookNotifier::slack("https://hooks.slack.com/services/T000/B000/XXXX");
+///     let report = Pipeline::new().run(&mut feeds,&notifier);
+///     println!("{:?}",report);
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct WebhookNotifier{
+    webhook:Webhook,
+    client:Client,
+    api:String,
+    updates:bool,
+}
+
+impl WebhookNotifier{
+
+    pub fn new(webhook:Webhook)->Self{
+        Self{
+            webhook,
+            client:crate::network::default_client(),
+            api:String::from(TELEGRAM_DEFAULT_API),
+            updates:false,
+        }
+    }
+
+    pub fn slack(url:&str)->Self{
+        Self::new(Webhook::Slack(url.to_string()))
+    }
+
+    pub fn discord(url:&str)->Self{
+        Self::new(Webhook::Discord(url.to_string()))
+    }
+
+    pub fn telegram(token:&str,chat_id:&str)->Self{
+        Self::new(Webhook::Telegram{ token:token.to_string(), chat_id:chat_id.to_string() })
+    }
+
+    /// Client used for the posts
+    pub fn client(mut self,client:Client)->Self{
+        self.client = client;
+        self
+    }
+
+    /// Telegram bot API endpoint, for self-hosted bot API servers
+    pub fn telegram_api(mut self,api:&str)->Self{
+        self.api = api.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Also notify items whose content changed, only new ones by default
+    pub fn updates(mut self,updates:bool)->Self{
+        self.updates = updates;
+        self
+    }
+
+    ///
+    /// Url and json body of the post announcing `item`
+    ///
+    fn request(&self,feed:&str,item:&RssItem)->(String,String){
+        let text = message(feed,item);
+        match &self.webhook {
+            Webhook::Slack(url) => (url.clone(),object!{ "text": text }.dump()),
+            Webhook::Discord(url) => {
+                let content = text.chars().take(DISCORD_MAX_CONTENT).collect::<String>();
+                (url.clone(),object!{ "content": content }.dump())
+            }
+            Webhook::Telegram{ token, chat_id } => (
+                format!("{}/bot{}/sendMessage",self.api,token),
+                object!{ "chat_id": chat_id.as_str(), "text": text }.dump(),
+            ),
+        }
+    }
+}
+
+/// Title and link of the item, the feed url when the item has no title
+fn message(feed:&str,item:&RssItem)->String{
+    let title = match item.title.trim() {
+        "" => feed,
+        title => title,
+    };
+    match item.link.trim() {
+        "" => title.to_string(),
+        link => format!("{}\n{}",title,link),
+    }
+}
+
+impl RssItemSink for WebhookNotifier{
+    fn store(&self,feed:&str,event:FeedEvent)->Result<(),std::io::Error>{
+        if let FeedEvent::Updated(_) = event {
+            if !self.updates {
+                return Ok(());
+            }
+        }
+        let (url,body) = self.request(feed,event.item());
+        self.client.post(&url)
+            .header("Content-Type","application/json")
+            .body(body)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| std::io::Error::other(describe(&e)))?;
+        Ok(())
+    }
+}
+
+///
+/// Message of a failed post without its url, webhook urls and the Telegram
+/// bot token in them are secrets that must not end up in logs
+///
+fn describe(e:&reqwest::Error)->String{
+    match (e.status(),std::error::Error::source(e)) {
+        (Some(status),_) => format!("Failed by WebhookNotifier: server answered {}",status),
+        (None,Some(source)) => format!("Failed by WebhookNotifier: {}",source),
+        (None,None) => String::from("Failed by WebhookNotifier: request failed"),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssItem,FeedEvent,RssItemSink,WebhookNotifier};
+
+    fn item()->RssItem{
+        RssItem{
+            title:String::from("Hey!"),
+            link:String::from("https://examples.com/1"),
+            ..RssItem::default()
+        }
+    }
+
+    #[test]
+    fn future_rss_webhook_request(){
+        let (url,body) = WebhookNotifier::slack("https://hooks.slack.com/x").request("feed",&item());
+        assert_eq!(url,"https://hooks.slack.com/x");
+        assert_eq!(body,r#"{"text":"Hey!\nhttps://examples.com/1"}"#);
+
+        let long = RssItem{ title:"a".repeat(3000), ..RssItem::default() };
+        let (_,body) = WebhookNotifier::discord("https://discord.com/x").request("feed",&long);
+        assert_eq!(json::parse(&body).unwrap()["content"].as_str().unwrap().len(),2000);
+
+        let notifier = WebhookNotifier::telegram("123:abc","42").telegram_api("http://localhost:8081/");
+        let (url,body) = notifier.request("https://examples.com/rss",&RssItem::default());
+        assert_eq!(url,"http://localhost:8081/bot123:abc/sendMessage");
+        assert_eq!(body,r#"{"chat_id":"42","text":"https://examples.com/rss"}"#);
+    }
+
+    #[test]
+    fn future_rss_webhook_store(){
+        let notifier = WebhookNotifier::slack("http://127.0.0.1:1/hook");
+        assert!(notifier.store("feed",FeedEvent::Updated(item())).is_ok());
+        assert!(notifier.store("feed",FeedEvent::New(item())).is_err());
+
+        let telegram = WebhookNotifier::telegram("123:secret","42").telegram_api("http://127.0.0.1:1");
+        let err = telegram.store("feed",FeedEvent::New(item())).unwrap_err().to_string();
+        assert!(err.starts_with("Failed by WebhookNotifier") && !err.contains("secret"),"{}",err);
+    }
+}
@@ -0,0 +1,583 @@
+//!
+//! Certificate Pinning
+//!
+//! [`PinnedFetcher`] holds a host to the certificates or public keys pinned
+//! for it, so an internal or high-security feed cannot be served with a
+//! certificate some other authority issued. Pinned hosts are fetched over a
+//! connection of the fetcher's own, where the server's key can be checked
+//! before the request is written; redirects are followed hop by hop, so a
+//! pin also holds when another host redirects to a pinned one.
+//!
+
+use std::collections::HashMap;
+use std::io::{BufRead,BufReader,Read,Write};
+use std::net::{TcpStream,ToSocketAddrs};
+use reqwest::blocking::Client;
+use url::Url;
+use crate::{HttpFetcher,HttpResponse,NetworkSettings,ProxyMode};
+
+/// Redirects followed before the last answer is handed back as it is
+pub static PINNED_MAX_REDIRECTS:usize = 10;
+
+///
+/// Fetcher with per-host pins, other hosts use a client built from the same
+/// [`NetworkSettings`]
+///
+/// A pinned certificate is either the server's own (self-signed) certificate
+/// or the CA that issued it, and replaces the system CAs for the host. A
+/// pinned key is the SHA-256 of the server certificate's public key
+/// (SubjectPublicKeyInfo) and is checked on top of the chain; pinning several
+/// of either allows rotating without downtime.
+///
+/// ```no_run
+/// use future_rss::{PinnedFetcher,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     // openssl x509 -in cert.pem -pubkey -noout | openssl pkey -pubin -outform der | openssl dgst -sha256
+///     let fetcher = PinnedFetcher::new()
+///         .pin("feeds.internal",&std::fs::read("feeds-internal.pem")?)?
+///         .pin_key("feeds.example.com","10f3b574158ca9c9135715d5bcb34c61a2d5c960138d0b8c51e683f8b79586ac")?;
+///     let mut parser = RssParser::new();
+///     let rss = parser.parse_url_with(&fetcher,"https://feeds.internal/rss","utf8")?;
+///     println!("{:?}",rss);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct PinnedFetcher{
+    settings:NetworkSettings,
+    /// never follows redirects, they are checked hop by hop
+    client:Client,
+    pins:HashMap<String,Pins>,
+}
+
+#[derive(Clone)]
+struct Pins{
+    certificates:Vec<native_tls::Certificate>,
+    keys:Vec<[u8;32]>,
+    tls:native_tls::TlsConnector,
+}
+
+impl std::fmt::Debug for PinnedFetcher{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        f.debug_struct("PinnedFetcher")
+            .field("settings",&self.settings)
+            .field("pinned",&self.pins.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for PinnedFetcher{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PinnedFetcher{
+
+    /// Fetcher with the settings of [`NetworkSettings::from_env`]
+    pub fn new()->Self{
+        Self::with_settings(NetworkSettings::from_env())
+            .unwrap_or_else(|_| Self::with_settings(NetworkSettings::default()).expect("Failed by PinnedFetcher::new"))
+    }
+
+    ///
+    /// Fetcher whose timeouts, user-agent and proxy come from `settings`,
+    /// for pinned hosts and the others alike
+    ///
+    pub fn with_settings(settings:NetworkSettings)->Result<Self,std::io::Error>{
+        let client = settings.client_builder()?
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        Ok(Self{ settings, client, pins:HashMap::new() })
+    }
+
+    ///
+    /// Trust `certificate`, PEM or DER encoded, for `host` and stop trusting
+    /// the system CAs there
+    ///
+    pub fn pin(mut self,host:&str,certificate:&[u8])->Result<Self,std::io::Error>{
+        let certificate = if certificate.starts_with(b"-----BEGIN") {
+            native_tls::Certificate::from_pem(certificate)
+        }else {
+            native_tls::Certificate::from_der(certificate)
+        }.map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput,format!("Failed by PinnedFetcher::pin: {}",e)))?;
+
+        let host = host.trim().to_ascii_lowercase();
+        let (mut certificates,keys) = self.pins.remove(&host).map(|pins| (pins.certificates,pins.keys)).unwrap_or_default();
+        certificates.push(certificate);
+        self.pins.insert(host,Pins::new(certificates,keys)?);
+        Ok(self)
+    }
+
+    ///
+    /// Accept only servers of `host` whose certificate key has the SHA-256
+    /// digest `sha256`, in hex with optional `:` separators
+    ///
+    pub fn pin_key(mut self,host:&str,sha256:&str)->Result<Self,std::io::Error>{
+        let digits = sha256.trim().replace(':',"");
+        let mut key = [0u8;32];
+        let valid = digits.len() == 64 && digits.is_ascii() && key.iter_mut().enumerate()
+            .all(|(i,byte)| u8::from_str_radix(&digits[i * 2..i * 2 + 2],16).map(|value| *byte = value).is_ok());
+        if !valid {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Failed by PinnedFetcher::pin_key: {:?} is not a hex SHA-256 digest",sha256)
+            ));
+        }
+
+        let host = host.trim().to_ascii_lowercase();
+        let (certificates,mut keys) = self.pins.remove(&host).map(|pins| (pins.certificates,pins.keys)).unwrap_or_default();
+        keys.push(key);
+        self.pins.insert(host,Pins::new(certificates,keys)?);
+        Ok(self)
+    }
+
+    /// Host has at least one pin
+    pub fn is_pinned(&self,host:&str)->bool{
+        self.pins.contains_key(&host.to_ascii_lowercase())
+    }
+
+    /// One request without following redirects
+    fn fetch_once(&self,url:&Url,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let host = url.host_str().unwrap_or("");
+        match self.pins.get(&host.to_ascii_lowercase()) {
+            Some(_) if url.scheme() != "https" => Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Failed by PinnedFetcher: {} is pinned, plain {} is refused",host,url.scheme())
+            )),
+            Some(pins) => self.fetch_pinned(url,pins,headers),
+            None => self.client.fetch(url.as_str(),headers),
+        }
+    }
+
+    fn fetch_pinned(&self,url:&Url,pins:&Pins,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let host = url.host_str().unwrap_or("");
+        let port = url.port_or_known_default().unwrap_or(443);
+        let stream = self.connect(host,port)?;
+        let mut stream = pins.tls.connect(host.trim_start_matches('[').trim_end_matches(']'),stream)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied,format!("Failed by PinnedFetcher: {}",e)))?;
+        if !pins.keys.is_empty() {
+            let certificate = stream.peer_certificate()
+                .and_then(|certificate| certificate.map(|c| c.to_der()).transpose())
+                .map_err(std::io::Error::other)?;
+            let key = certificate.as_deref().and_then(public_key).map(sha256);
+            if !key.is_some_and(|key| pins.keys.contains(&key)) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("Failed by PinnedFetcher: the key of {} matches none of its pins",host)
+                ));
+            }
+        }
+
+        let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nAccept-Encoding: identity\r\n",&url[url::Position::BeforePath..url::Position::AfterQuery],&url[url::Position::BeforeHost..url::Position::AfterPort]);
+        let has_agent = headers.iter().any(|(name,_)| name.eq_ignore_ascii_case("user-agent"));
+        if let (Some(agent),false) = (&self.settings.user_agent,has_agent) {
+            request.push_str(&format!("User-Agent: {}\r\n",agent));
+        }
+        for (name,value) in headers {
+            request.push_str(&format!("{}: {}\r\n",name,value));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+        read_response(BufReader::new(stream))
+    }
+
+    /// Connection to `host`, through the proxy of the settings when there is one
+    fn connect(&self,host:&str,port:u16)->Result<TcpStream,std::io::Error>{
+        let proxy = match &self.settings.proxy {
+            _ if self.settings.bypasses_proxy(host) => None,
+            ProxyMode::Direct => None,
+            ProxyMode::Url(url) => Some(url.clone()),
+            ProxyMode::Env => ["HTTPS_PROXY","https_proxy"].iter()
+                .find_map(|name| std::env::var(name).ok().filter(|value| !value.trim().is_empty())),
+        };
+        let proxy = match proxy {
+            Some(proxy) => Url::parse(proxy.trim()).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput,format!("Failed by PinnedFetcher: proxy {}",e)))?,
+            None => return self.open(host,port),
+        };
+        if proxy.scheme() != "http" {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Failed by PinnedFetcher: {} proxies are not supported for pinned hosts",proxy.scheme())
+            ));
+        }
+        let mut stream = self.open(proxy.host_str().unwrap_or(""),proxy.port_or_known_default().unwrap_or(80))?;
+        let mut request = format!("CONNECT {}:{} HTTP/1.1\r\nHost: {}:{}\r\n",host,port,host,port);
+        if !proxy.username().is_empty() {
+            let credentials = format!("{}:{}",decode_component(proxy.username()),decode_component(proxy.password().unwrap_or("")));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n",base64(credentials.as_bytes())));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes())?;
+
+        // byte by byte, nothing past the answer may be taken from the tunnel
+        let mut head = Vec::new();
+        let mut byte = [0u8;1];
+        while !head.ends_with(b"\r\n\r\n") {
+            if stream.read(&mut byte)? == 0 || head.len() > 64 * 1024 {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData,"Failed by PinnedFetcher: proxy closed the tunnel"));
+            }
+            head.push(byte[0]);
+        }
+        match status_of(&String::from_utf8_lossy(&head)) {
+            Some(status) if (200..300).contains(&status) => Ok(stream),
+            status => Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                format!("Failed by PinnedFetcher: proxy answered {:?} to CONNECT",status)
+            )),
+        }
+    }
+
+    /// Tcp connection with the timeouts of the settings, the whole request
+    /// timeout bounds every read and write
+    fn open(&self,host:&str,port:u16)->Result<TcpStream,std::io::Error>{
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let mut last = std::io::Error::new(std::io::ErrorKind::NotFound,format!("Failed by PinnedFetcher: {} has no address",host));
+        for address in (host,port).to_socket_addrs()? {
+            let timeout = self.settings.request_timeout();
+            match TcpStream::connect_timeout(&address,self.settings.connect_timeout.unwrap_or(timeout)) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    return Ok(stream);
+                }
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+}
+
+impl Pins{
+
+    fn new(certificates:Vec<native_tls::Certificate>,keys:Vec<[u8;32]>)->Result<Self,std::io::Error>{
+        let mut tls = native_tls::TlsConnector::builder();
+        if !certificates.is_empty() {
+            tls.disable_built_in_roots(true);
+        }
+        for certificate in certificates.iter() {
+            tls.add_root_certificate(certificate.clone());
+        }
+        let tls = tls.build().map_err(std::io::Error::other)?;
+        Ok(Self{ certificates, keys, tls })
+    }
+}
+
+impl HttpFetcher for PinnedFetcher{
+    fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let mut url = Url::parse(url)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput,e.to_string()))?;
+        let origin = url.host_str().map(str::to_ascii_lowercase);
+        let mut redirects = 0;
+        loop {
+            // credentials stay with the host they were meant for
+            let same_host = url.host_str().map(str::to_ascii_lowercase) == origin;
+            let hop_headers = headers.iter()
+                .filter(|(name,_)| same_host || !["authorization","cookie"].iter().any(|secret| name.eq_ignore_ascii_case(secret)))
+                .copied()
+                .collect::<Vec<_>>();
+            let response = self.fetch_once(&url,&hop_headers)?;
+            let location = match response.status {
+                301 | 302 | 303 | 307 | 308 if redirects < PINNED_MAX_REDIRECTS => response.headers.iter()
+                    .find(|(name,_)| name == "location")
+                    .and_then(|(_,location)| url.join(location).ok()),
+                _ => None,
+            };
+            match location {
+                Some(next) => {
+                    url = next;
+                    redirects += 1;
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+}
+
+fn status_of(head:&str)->Option<u16>{
+    let line = head.lines().next()?;
+    if !line.starts_with("HTTP/") {
+        return None;
+    }
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Answer of a request sent with `Connection: close`
+fn read_response<S:Read+Send+'static>(mut stream:BufReader<S>)->Result<HttpResponse,std::io::Error>{
+    let invalid = |message:&str| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by PinnedFetcher: {}",message));
+    let mut line = String::new();
+    stream.read_line(&mut line)?;
+    let status = status_of(&line).ok_or_else(|| invalid("malformed status line"))?;
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if stream.read_line(&mut line)? == 0 {
+            return Err(invalid("answer ends in its head"));
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name,value) = header.split_once(':').ok_or_else(|| invalid("malformed header"))?;
+        headers.push((name.trim().to_ascii_lowercase(),value.trim().to_string()));
+    }
+    let header = |key:&str| headers.iter().find(|(name,_)| name == key).map(|(_,value)| value.as_str());
+    let body:Box<dyn Read+Send> = if status == 204 || status == 304 || (100..200).contains(&status) {
+        Box::new(std::io::empty())
+    }else if header("transfer-encoding").is_some_and(|coding| coding.to_ascii_lowercase().contains("chunked")) {
+        Box::new(Chunked{ stream, left:0, done:false })
+    }else if let Some(length) = header("content-length").and_then(|length| length.parse::<u64>().ok()) {
+        Box::new(stream.take(length))
+    }else {
+        Box::new(stream)
+    };
+    Ok(HttpResponse{ status, headers, body })
+}
+
+/// Body sent with `Transfer-Encoding: chunked`
+struct Chunked<S>{
+    stream:BufReader<S>,
+    /// bytes left in the current chunk
+    left:u64,
+    done:bool,
+}
+
+impl<S:Read> Read for Chunked<S>{
+    fn read(&mut self,buf:&mut [u8])->std::io::Result<usize>{
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.left == 0 {
+            let mut line = String::new();
+            self.stream.read_line(&mut line)?;
+            // the line ending the previous chunk
+            if line.trim().is_empty() {
+                line.clear();
+                self.stream.read_line(&mut line)?;
+            }
+            let size = line.split(';').next().unwrap_or("").trim();
+            self.left = u64::from_str_radix(size,16)
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData,"Failed by PinnedFetcher: malformed chunk size"))?;
+            if self.left == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+        }
+        let wanted = self.left.min(buf.len() as u64) as usize;
+        let read = self.stream.read(&mut buf[..wanted])?;
+        if read == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof,"Failed by PinnedFetcher: body ends in a chunk"));
+        }
+        self.left -= read as u64;
+        Ok(read)
+    }
+}
+
+/// Percent-decoded user name or password of a proxy url, a plus stays a plus
+fn decode_component(text:&str)->String{
+    url::form_urlencoded::parse(format!("x={}",text.replace('+',"%2B")).as_bytes())
+        .next()
+        .map(|(_,value)| value.into_owned())
+        .unwrap_or_default()
+}
+
+fn base64(data:&[u8])->String{
+    const ALPHABET:&[u8;64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32,|bits,(i,byte)| bits | u32::from(*byte) << (16 - 8 * i));
+        for i in 0..4 {
+            text.push(if i <= chunk.len() { ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char }else { '=' });
+        }
+    }
+    text
+}
+
+/// DER element at the start of `der` as (tag, contents, rest)
+fn der_element(der:&[u8])->Option<(u8,&[u8],&[u8])>{
+    let (&tag,rest) = der.split_first()?;
+    let (&first,rest) = rest.split_first()?;
+    let (length,rest) = if first < 0x80 {
+        (usize::from(first),rest)
+    }else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > std::mem::size_of::<usize>() || rest.len() < count {
+            return None;
+        }
+        let length = rest[..count].iter().fold(0usize,|length,byte| length << 8 | usize::from(*byte));
+        (length,&rest[count..])
+    };
+    if rest.len() < length {
+        return None;
+    }
+    Some((tag,&rest[..length],&rest[length..]))
+}
+
+/// SubjectPublicKeyInfo of a DER certificate, tag and length included
+fn public_key(certificate:&[u8])->Option<&[u8]>{
+    let (_,certificate,_) = der_element(certificate)?;
+    let (_,tbs,_) = der_element(certificate)?;
+    let mut rest = tbs;
+    // version, when present, then serial, signature, issuer, validity and subject
+    if rest.first() == Some(&0xa0) {
+        rest = der_element(rest)?.2;
+    }
+    for _ in 0..5 {
+        rest = der_element(rest)?.2;
+    }
+    let (tag,_,after) = der_element(rest)?;
+    if tag == 0x30 { Some(&rest[..rest.len() - after.len()]) }else { None }
+}
+
+fn sha256(data:&[u8])->[u8;32]{
+    const K:[u32;64] = [
+        0x428a2f98,0x71374491,0xb5c0fbcf,0xe9b5dba5,0x3956c25b,0x59f111f1,0x923f82a4,0xab1c5ed5,
+        0xd807aa98,0x12835b01,0x243185be,0x550c7dc3,0x72be5d74,0x80deb1fe,0x9bdc06a7,0xc19bf174,
+        0xe49b69c1,0xefbe4786,0x0fc19dc6,0x240ca1cc,0x2de92c6f,0x4a7484aa,0x5cb0a9dc,0x76f988da,
+        0x983e5152,0xa831c66d,0xb00327c8,0xbf597fc7,0xc6e00bf3,0xd5a79147,0x06ca6351,0x14292967,
+        0x27b70a85,0x2e1b2138,0x4d2c6dfc,0x53380d13,0x650a7354,0x766a0abb,0x81c2c92e,0x92722c85,
+        0xa2bfe8a1,0xa81a664b,0xc24b8b70,0xc76c51a3,0xd192e819,0xd6990624,0xf40e3585,0x106aa070,
+        0x19a4c116,0x1e376c08,0x2748774c,0x34b0bcb5,0x391c0cb3,0x4ed8aa4a,0x5b9cca4f,0x682e6ff3,
+        0x748f82ee,0x78a5636f,0x84c87814,0x8cc70208,0x90befffa,0xa4506ceb,0xbef9a3f7,0xc67178f2,
+    ];
+    let mut state:[u32;8] = [0x6a09e667,0xbb67ae85,0x3c6ef372,0xa54ff53a,0x510e527f,0x9b05688c,0x1f83d9ab,0x5be0cd19];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32;64];
+        for (i,word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0],word[1],word[2],word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+        let [mut a,mut b,mut c,mut d,mut e,mut f,mut g,mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word,value) in state.iter_mut().zip([a,b,c,d,e,f,g,h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8;32];
+    for (bytes,word) in digest.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use crate::{HttpFetcher,NetworkSettings,PinnedFetcher,ProxyMode};
+    use crate::mock::{Fixture,serve,serve_tls,FEED,TLS_CERT,TLS_OTHER_CERT};
+    use super::{base64,read_response,sha256};
+
+    /// SHA-256 of the public key of [`TLS_CERT`]
+    static TLS_KEY_SHA256:&str = "10f3b574158ca9c9135715d5bcb34c61a2d5c960138d0b8c51e683f8b79586ac";
+
+    #[test]
+    fn future_rss_pinned_fetcher(){
+        let server = serve_tls(FEED);
+        let url = format!("https://localhost:{}/feed",server.port());
+
+        let pinned = PinnedFetcher::new().pin("LOCALHOST",TLS_CERT.as_bytes()).unwrap();
+        assert!(pinned.is_pinned("localhost"));
+        assert_eq!(pinned.fetch(&url,&[]).unwrap().status,200);
+        assert!(reqwest::blocking::Client::new().fetch(&url,&[]).is_err());
+
+        let wrong = PinnedFetcher::new().pin("localhost",TLS_OTHER_CERT.as_bytes()).unwrap();
+        assert!(wrong.fetch(&url,&[]).is_err());
+        let rotated = wrong.pin("localhost",TLS_CERT.as_bytes()).unwrap();
+        let mut body = String::new();
+        rotated.fetch(&url,&[]).unwrap().body.read_to_string(&mut body).unwrap();
+        assert_eq!(body,FEED);
+
+        let plain = serve(FEED);
+        let err = rotated.fetch(&format!("http://localhost:{}/feed",plain.port()),&[]).unwrap_err();
+        assert_eq!(err.kind(),std::io::ErrorKind::PermissionDenied);
+        assert_eq!(rotated.fetch(&plain.url("/feed"),&[]).unwrap().status,200);
+
+        assert!(PinnedFetcher::new().pin("localhost",b"not a certificate").is_err());
+    }
+
+    #[test]
+    fn future_rss_pinned_keys(){
+        let server = serve_tls(FEED);
+        let url = format!("https://localhost:{}/feed",server.port());
+
+        // the key is checked on top of the chain
+        let keyed = PinnedFetcher::new().pin("localhost",TLS_CERT.as_bytes()).unwrap().pin_key("localhost",TLS_KEY_SHA256).unwrap();
+        assert_eq!(keyed.fetch(&url,&[]).unwrap().status,200);
+        let other_key = PinnedFetcher::new().pin("localhost",TLS_CERT.as_bytes()).unwrap().pin_key("localhost",&"ab".repeat(32)).unwrap();
+        assert_eq!(other_key.fetch(&url,&[]).unwrap_err().kind(),std::io::ErrorKind::PermissionDenied);
+        assert!(PinnedFetcher::new().pin_key("localhost","10:f3").is_err());
+        assert!(PinnedFetcher::new().pin_key("localhost",&"é".repeat(32)).is_err());
+
+        // a redirect from an unpinned host is held to the pins of its target
+        let plain = serve(FEED);
+        plain.mount("/redirect",Fixture::redirect(&url));
+        let redirect = plain.url("/redirect");
+        assert_eq!(keyed.fetch(&redirect,&[]).unwrap().status,200);
+        assert!(other_key.fetch(&redirect,&[]).is_err());
+
+        // pinned connections carry the settings too
+        let settings = NetworkSettings::default().user_agent("pinned-agent").proxy(ProxyMode::Direct);
+        let fetcher = PinnedFetcher::with_settings(settings).unwrap().pin("localhost",TLS_CERT.as_bytes()).unwrap();
+        assert_eq!(fetcher.fetch(&url,&[]).unwrap().status,200);
+        let head = server.received().pop().unwrap().to_lowercase();
+        assert!(head.contains("user-agent: pinned-agent"),"{}",head);
+    }
+
+    #[test]
+    fn future_rss_pinned_digest(){
+        let hex = |digest:[u8;32]| digest.iter().map(|byte| format!("{:02x}",byte)).collect::<String>();
+        assert_eq!(hex(sha256(b"")),"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(sha256(b"abc")),"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex(sha256(&[b'a';1000])),"41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+        assert_eq!(base64(b"user:pass"),"dXNlcjpwYXNz");
+        assert_eq!(base64(b"ab"),"YWI=");
+    }
+
+    #[test]
+    fn future_rss_pinned_response(){
+        let read = |answer:&str| {
+            let mut response = read_response(std::io::BufReader::new(std::io::Cursor::new(answer.as_bytes().to_vec())))?;
+            let mut body = String::new();
+            response.body.read_to_string(&mut body).map(|_| (response.status,body))
+        };
+        let chunked = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nHey!\r\n6;x=y\r\n hello\r\n0\r\n\r\n";
+        assert_eq!(read(chunked).unwrap(),(200,String::from("Hey! hello")));
+        assert_eq!(read("HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nHey!tail").unwrap().1,"Hey!");
+        assert_eq!(read("HTTP/1.0 404 Not Found\r\n\r\ngone").unwrap(),(404,String::from("gone")));
+        assert_eq!(read("HTTP/1.1 304 Not Modified\r\n\r\n").unwrap().1,"");
+        assert!(read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n").is_err());
+        assert!(read("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n9\r\nHey!").is_err());
+        assert!(read("SSH-2.0\r\n\r\n").is_err());
+    }
+}
@@ -0,0 +1,411 @@
+//!
+//! Aggregation Pipeline
+//!
+//! Runs feeds through fetch → parse → filter → store stages, every stage on
+//! its own pool of threads and connected to the next by a bounded channel,
+//! so a slow server only ever occupies a fetcher while items of the fast
+//! ones keep flowing into the sink.
+//!
+
+use std::sync::{Arc,Mutex};
+use std::sync::mpsc::{sync_channel,Receiver};
+use std::thread;
+use reqwest::blocking::Client;
+use crate::diff::content_hash;
+use crate::{RssItem,FeedWatcher,FeedEvent,PollOutcome,CancelToken,PageMeta,HttpFetcher,PoliteFetcher,SeenStore};
+
+/// Default number of threads per stage
+pub static PIPELINE_DEFAULT_WORKERS:usize = 4;
+
+/// Default number of messages buffered between two stages
+pub static PIPELINE_DEFAULT_CAPACITY:usize = 64;
+
+///
+/// Destination of the items leaving a [`Pipeline`]
+///
+/// Closures taking the feed url and the event implement it already.
+///
+pub trait RssItemSink:Send+Sync{
+    fn store(&self,feed:&str,event:FeedEvent)->Result<(),std::io::Error>;
+}
+
+impl<F> RssItemSink for F
+    where F:Fn(&str,FeedEvent)->Result<(),std::io::Error>+Send+Sync
+{
+    fn store(&self,feed:&str,event:FeedEvent)->Result<(),std::io::Error>{
+        self(feed,event)
+    }
+}
+
+///
+/// Counters of a single pipeline run
+///
+#[derive(Debug,Default)]
+pub struct PipelineReport{
+    /// Feeds downloaded and parsed
+    pub fetched:usize,
+    /// Feeds whose server answered 304
+    pub not_modified:usize,
+    /// New and updated items leaving the parse stage
+    pub items:usize,
+    /// Items dropped by the filter stage
+    pub filtered_out:usize,
+    /// Items another worker on the same [`SeenStore`] claimed first
+    pub duplicates:usize,
+    /// Items accepted by the sink
+    pub stored:usize,
+    /// Failures of any stage, keyed by feed url
+    pub errors:Vec<(String,std::io::Error)>,
+}
+
+///
+/// Pipeline Builder
+///
+/// ```no_run
+/// use future_rss::{Pipeline,FeedWatcher,FeedEvent};
+///
+/// fn main(){
+///     let mut feeds = vec![
+///         FeedWatcher::new("https://www.zhihu.com/rss","utf8"),
+///         FeedWatcher::new("https://sspai.com/feed","utf8"),
+///     ];
+///     let report = Pipeline::new()
+///         .fetchers(16)
+///         .parsers(2)
+///         .filter(|item| !item.title.is_empty())
+///         .run(&mut feeds,&|feed:&str,event:FeedEvent| {
+///             println!("{} {:?}",feed,event);
+///             Ok(())
+///         });
+///     println!("{:?}",report);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Pipeline{
+    fetchers:usize,
+    parsers:usize,
+    filters:usize,
+    storers:usize,
+    capacity:usize,
+    client:Client,
+    cancel:CancelToken,
+    enrich:bool,
+    robots:bool,
+    #[allow(clippy::type_complexity)]
+    filter:Option<Arc<dyn Fn(&RssItem)->bool+Send+Sync>>,
+    seen:Option<Arc<dyn SeenStore>>,
+}
+
+impl Default for Pipeline{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline{
+
+    pub fn new()->Self{
+        Self{
+            fetchers:PIPELINE_DEFAULT_WORKERS,
+            parsers:PIPELINE_DEFAULT_WORKERS,
+            filters:PIPELINE_DEFAULT_WORKERS,
+            storers:PIPELINE_DEFAULT_WORKERS,
+            capacity:PIPELINE_DEFAULT_CAPACITY,
+            client:crate::network::default_client(),
+            cancel:CancelToken::new(),
+            enrich:false,
+            robots:true,
+            filter:None,
+            seen:None,
+        }
+    }
+
+    /// Threads downloading feeds, at least 1
+    pub fn fetchers(mut self,workers:usize)->Self{
+        self.fetchers = workers.max(1);
+        self
+    }
+
+    /// Threads parsing and diffing downloaded bodies, at least 1
+    pub fn parsers(mut self,workers:usize)->Self{
+        self.parsers = workers.max(1);
+        self
+    }
+
+    /// Threads running the filter, at least 1
+    pub fn filters(mut self,workers:usize)->Self{
+        self.filters = workers.max(1);
+        self
+    }
+
+    /// Threads handing items to the sink, at least 1
+    pub fn storers(mut self,workers:usize)->Self{
+        self.storers = workers.max(1);
+        self
+    }
+
+    /// Messages buffered between two stages before the upstream one waits
+    pub fn capacity(mut self,capacity:usize)->Self{
+        self.capacity = capacity;
+        self
+    }
+
+    /// Share a preconfigured client between all fetchers
+    pub fn client(mut self,client:Client)->Self{
+        self.client = client;
+        self
+    }
+
+    /// Skip feeds not fetched yet once `token` is cancelled,
+    /// items already downloaded still reach the sink
+    pub fn cancel_token(mut self,token:CancelToken)->Self{
+        self.cancel = token;
+        self
+    }
+
+    /// Before filtering, fill the missing title, description and author
+    /// of items from the meta tags of their link, see [`PageMeta`]
+    pub fn enrich(mut self,enrich:bool)->Self{
+        self.enrich = enrich;
+        self
+    }
+
+    /// Page downloads of [`Pipeline::enrich`] go through a [`PoliteFetcher`],
+    /// on by default
+    pub fn robots(mut self,robots:bool)->Self{
+        self.robots = robots;
+        self
+    }
+
+    /// Only items for which `filter` returns true reach the sink
+    pub fn filter<F>(mut self,filter:F)->Self
+        where F:Fn(&RssItem)->bool+Send+Sync+'static
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    ///
+    /// Only events this pipeline is the first to insert into `seen` reach
+    /// the filter, so workers sharing the store emit every change once;
+    /// when the store fails the event goes on and the error is reported
+    ///
+    pub fn seen_store(mut self,seen:Arc<dyn SeenStore>)->Self{
+        self.seen = Some(seen);
+        self
+    }
+
+    ///
+    /// Push every watcher through the stages and wait until the sink has seen
+    /// the last item
+    ///
+    pub fn run<S:RssItemSink+?Sized>(&self,watchers:&mut [FeedWatcher],sink:&S)->PipelineReport{
+        let shared = Mutex::new(PipelineReport::default());
+        let (fetch_tx,fetch_rx) = sync_channel::<&mut FeedWatcher>(self.capacity);
+        let (parse_tx,parse_rx) = sync_channel(self.capacity);
+        let (filter_tx,filter_rx) = sync_channel::<(Arc<str>,FeedEvent)>(self.capacity);
+        let (store_tx,store_rx) = sync_channel::<(Arc<str>,FeedEvent)>(self.capacity);
+        let (fetch_rx,parse_rx) = (&Mutex::new(fetch_rx),&Mutex::new(parse_rx));
+        let (filter_rx,store_rx) = (&Mutex::new(filter_rx),&Mutex::new(store_rx));
+        let report = &shared;
+        let polite = PoliteFetcher::new(self.client.clone());
+        let pages:&dyn HttpFetcher = if self.robots { &polite } else { &self.client };
+
+        thread::scope(|scope| {
+            for _ in 0..self.fetchers {
+                let parse_tx = parse_tx.clone();
+                scope.spawn(move || {
+                    while let Some(watcher) = next(fetch_rx) {
+                        if self.cancel.is_cancelled() {
+                            report.lock().unwrap().errors.push((watcher.url.clone(),CancelToken::error()));
+                            continue;
+                        }
+                        let download = watcher.download(&self.client);
+                        if parse_tx.send((watcher,download)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(parse_tx);
+
+            for _ in 0..self.parsers {
+                let filter_tx = filter_tx.clone();
+                scope.spawn(move || {
+                    while let Some((watcher,download)) = next(parse_rx) {
+                        let watcher:&mut FeedWatcher = watcher;
+                        let feed:Arc<str> = Arc::from(watcher.url.as_str());
+                        let events = match watcher.complete(download) {
+                            Ok(PollOutcome::NotModified) => {
+                                report.lock().unwrap().not_modified += 1;
+                                continue;
+                            }
+                            Ok(PollOutcome::Fetched(events)) => events,
+                            Err(e) => {
+                                report.lock().unwrap().errors.push((feed.to_string(),e));
+                                continue;
+                            }
+                        };
+                        {
+                            let mut report = report.lock().unwrap();
+                            report.fetched += 1;
+                            report.items += events.len();
+                        }
+                        for event in events {
+                            if filter_tx.send((feed.clone(),event)).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+            drop(filter_tx);
+
+            for _ in 0..self.filters {
+                let store_tx = store_tx.clone();
+                scope.spawn(move || {
+                    while let Some((feed,mut event)) = next(filter_rx) {
+                        if let Some(seen) = &self.seen {
+                            match seen.insert(&claim_key(&event)) {
+                                Ok(true) => (),
+                                Ok(false) => {
+                                    report.lock().unwrap().duplicates += 1;
+                                    continue;
+                                }
+                                Err(e) => report.lock().unwrap().errors.push((feed.to_string(),e)),
+                            }
+                        }
+                        if self.enrich {
+                            if let Err(e) = enrich(pages,event.item_mut()) {
+                                report.lock().unwrap().errors.push((feed.to_string(),e));
+                            }
+                        }
+                        let keep = match &self.filter {
+                            Some(filter) => filter(event.item()),
+                            None => true,
+                        };
+                        if !keep {
+                            report.lock().unwrap().filtered_out += 1;
+                        }else if store_tx.send((feed,event)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(store_tx);
+
+            for _ in 0..self.storers {
+                scope.spawn(move || {
+                    while let Some((feed,event)) = next(store_rx) {
+                        let stored = sink.store(&feed,event);
+                        let mut report = report.lock().unwrap();
+                        match stored {
+                            Ok(()) => report.stored += 1,
+                            Err(e) => report.errors.push((feed.to_string(),e)),
+                        }
+                    }
+                });
+            }
+
+            for watcher in watchers.iter_mut() {
+                if fetch_tx.send(watcher).is_err() {
+                    break;
+                }
+            }
+            drop(fetch_tx);
+        });
+
+        shared.into_inner().unwrap()
+    }
+}
+
+/// Fetch the page of an item missing a field, a failure leaves it as is
+fn enrich(pages:&dyn HttpFetcher,item:&mut RssItem)->Result<(),std::io::Error>{
+    let missing = [&item.title,&item.description,&item.author].iter().any(|v| v.trim().is_empty());
+    if missing && !item.link.trim().is_empty() {
+        PageMeta::fetch(pages,item.link.trim(),"utf8")?.fill(item);
+    }
+    Ok(())
+}
+
+/// Key of an event in a seen store, an edit is claimed apart from the item
+fn claim_key(event:&FeedEvent)->String{
+    match event {
+        FeedEvent::New(item) => item.key().to_string(),
+        FeedEvent::Updated(item) => format!("{}#{:x}",item.key(),content_hash(item)),
+    }
+}
+
+/// Receive from a channel shared by every worker of a stage
+fn next<T>(receiver:&Mutex<Receiver<T>>)->Option<T>{
+    receiver.lock().unwrap().recv().ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc,Mutex};
+    use crate::{Pipeline,FeedWatcher,FeedEvent,CancelToken,MemoryCache};
+    use crate::mock::{serve,FEED};
+
+    #[test]
+    fn future_rss_pipeline(){
+        let server = serve(FEED);
+        let mut feeds = (0..3)
+            .map(|i| FeedWatcher::new(&server.url(&format!("/feed/{}",i)),"utf8"))
+            .collect::<Vec<_>>();
+        feeds.push(FeedWatcher::new("not a url","utf8"));
+
+        let stored = Mutex::new(Vec::new());
+        let sink = |feed:&str,event:FeedEvent| {
+            stored.lock().unwrap().push((feed.to_string(),event));
+            Ok(())
+        };
+        let pipeline = Pipeline::new().fetchers(2).parsers(1).capacity(1);
+        let report = pipeline.clone().run(&mut feeds,&sink);
+        assert_eq!(report.fetched,3);
+        assert_eq!(report.items,3);
+        assert_eq!(report.stored,3);
+        assert_eq!(report.errors.len(),1);
+        assert_eq!(stored.lock().unwrap().len(),3);
+
+        let report = pipeline.run(&mut feeds,&sink);
+        assert_eq!(report.not_modified,3);
+        assert_eq!(report.stored,0);
+
+        let mut fresh = vec![FeedWatcher::new(&server.url("/feed/fresh"),"utf8")];
+        let report = Pipeline::new()
+            .filter(|item| item.title != "Hey!")
+            .run(&mut fresh,&sink);
+        assert_eq!(report.items,1);
+        assert_eq!(report.filtered_out,1);
+        assert_eq!(report.stored,0);
+
+        let page = serve(r#"<head><meta name="author" content="Page author"></head>"#);
+        let feed = format!("<?xml version=\"1.0\"?><rss><item><title>Bare</title><link>{}</link></item></rss>",page.url("/a"));
+        let server_bare = serve(Box::leak(feed.into_boxed_str()));
+        let mut bare = vec![FeedWatcher::new(&server_bare.url("/feed"),"utf8")];
+        let report = Pipeline::new()
+            .enrich(true)
+            .filter(|item| item.author == "Page author")
+            .run(&mut bare,&sink);
+        assert_eq!(report.stored,1);
+        assert!(report.errors.is_empty());
+
+        let seen = Arc::new(MemoryCache::new());
+        let mut workers = (0..2)
+            .map(|_| vec![FeedWatcher::new(&server.url("/feed/shared"),"utf8")])
+            .collect::<Vec<_>>();
+        let reports = workers.iter_mut()
+            .map(|feeds| Pipeline::new().seen_store(seen.clone()).run(feeds,&sink))
+            .collect::<Vec<_>>();
+        assert_eq!(reports.iter().map(|report| (report.items,report.stored,report.duplicates)).collect::<Vec<_>>(),[(1,1,0),(1,0,1)]);
+
+        let token = CancelToken::new();
+        token.cancel();
+        let mut fresh = vec![FeedWatcher::new(&server.url("/feed/cancel"),"utf8")];
+        let report = Pipeline::new().cancel_token(token).run(&mut fresh,&sink);
+        assert_eq!(report.fetched,0);
+        assert_eq!(report.errors[0].1.kind(),std::io::ErrorKind::Interrupted);
+    }
+}
@@ -0,0 +1,353 @@
+//!
+//! Query Language
+//!
+//! Item filters written as text, e.g.
+//! `title ~ "rust" AND published > 2024-01-01 AND author != "bot"`, so the
+//! command line and configuration files can filter without code.
+//!
+
+use crate::{RssItem,Timestamp};
+
+///
+/// Compiled filter over items
+///
+/// A query is comparisons joined by `AND`, `OR` and `NOT` (in that order of
+/// precedence) and grouped by parentheses. A comparison is a field (`title`,
+/// `link`, `author`, `description`, `guid`, `published`, `updated`), an
+/// operator and a value, quoted when it holds spaces:
+///
+/// * `~` and `!~` find the value in the field, ignoring case
+/// * `=` and `!=` compare the whole field, ignoring case
+/// * `>`, `>=`, `<` and `<=` compare the date fields, items whose date is
+///   missing or not understood never match
+///
+/// ```
+/// use future_rss::{Query,RssItem};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let query = Query::parse(r#"title ~ "rust" AND published > 2024-01-01 AND author != "bot""#)?;
+///     let item = RssItem{
+///         title:String::from("Rust 2024 is out"),
+///         publish:String::from("Thu, 20 Feb 2025 10:00:00 GMT"),
+///         ..RssItem::default()
+///     };
+///     assert!(query.matches(&item));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq)]
+pub struct Query{
+    root:Node,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Field{
+    Title,
+    Link,
+    Author,
+    Description,
+    Guid,
+    Published,
+    Updated,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Operator{
+    Contains,
+    NotContains,
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+}
+
+#[derive(Debug,Clone,PartialEq)]
+enum Node{
+    And(Box<Node>,Box<Node>),
+    Or(Box<Node>,Box<Node>),
+    Not(Box<Node>),
+    Compare(Field,Operator,String),
+    /// Date comparison, the value parsed once
+    Date(Field,Operator,Timestamp),
+}
+
+#[derive(Debug,Clone,PartialEq)]
+enum Token{
+    Word(String),
+    Quoted(String),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+impl Query{
+
+    ///
+    /// Compile `text`, `InvalidInput` naming what is wrong when it is no
+    /// valid query
+    ///
+    pub fn parse(text:&str)->Result<Self,std::io::Error>{
+        let tokens = tokenize(text)?;
+        let mut parser = Parser{ tokens:&tokens, at:0 };
+        let root = parser.or()?;
+        match parser.tokens.get(parser.at) {
+            None => Ok(Self{ root }),
+            Some(token) => Err(invalid(format!("unexpected {:?}",token))),
+        }
+    }
+
+    /// `item` passes the filter
+    pub fn matches(&self,item:&RssItem)->bool{
+        self.root.matches(item)
+    }
+}
+
+impl std::str::FromStr for Query{
+    type Err = std::io::Error;
+
+    fn from_str(text:&str)->Result<Self,Self::Err>{
+        Self::parse(text)
+    }
+}
+
+impl Field{
+
+    fn parse(name:&str)->Option<Self>{
+        Some(match name.to_ascii_lowercase().as_str() {
+            "title" => Field::Title,
+            "link" => Field::Link,
+            "author" => Field::Author,
+            "description" => Field::Description,
+            "guid" => Field::Guid,
+            "published" | "publish" => Field::Published,
+            "updated" => Field::Updated,
+            _ => return None,
+        })
+    }
+
+    fn text<'i>(&self,item:&'i RssItem)->&'i str{
+        match self {
+            Field::Title => &item.title,
+            Field::Link => &item.link,
+            Field::Author => &item.author,
+            Field::Description => &item.description,
+            Field::Guid => &item.guid,
+            Field::Published => &item.publish,
+            Field::Updated => &item.updated,
+        }
+    }
+
+    fn is_date(&self)->bool{
+        matches!(self,Field::Published | Field::Updated)
+    }
+}
+
+impl Node{
+
+    fn matches(&self,item:&RssItem)->bool{
+        match self {
+            Node::And(left,right) => left.matches(item) && right.matches(item),
+            Node::Or(left,right) => left.matches(item) || right.matches(item),
+            Node::Not(node) => !node.matches(item),
+            Node::Compare(field,operator,value) => {
+                let text = field.text(item).to_lowercase();
+                match operator {
+                    Operator::Contains => text.contains(value.as_str()),
+                    Operator::NotContains => !text.contains(value.as_str()),
+                    Operator::Equal => text.trim() == value,
+                    _ => text.trim() != value,
+                }
+            }
+            Node::Date(field,operator,value) => {
+                let date = match Timestamp::parse(field.text(item)) {
+                    Some(date) => date,
+                    None => return false,
+                };
+                match operator {
+                    Operator::Equal => date == *value,
+                    Operator::NotEqual => date != *value,
+                    Operator::Greater => date > *value,
+                    Operator::GreaterEqual => date >= *value,
+                    Operator::Less => date < *value,
+                    _ => date <= *value,
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(text:&str)->Result<Vec<Token>,std::io::Error>{
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start,c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_,'\\')) => value.extend(chars.next().map(|(_,c)| c)),
+                        Some((_,end)) if end == c => break,
+                        Some((_,c)) => value.push(c),
+                        None => return Err(invalid(format!("unterminated string at {}",start))),
+                    }
+                }
+                Token::Quoted(value)
+            }
+            '~' => Token::Operator(Operator::Contains),
+            '=' => Token::Operator(Operator::Equal),
+            '!' | '>' | '<' => {
+                let equal = chars.next_if(|(_,c)| *c == '=').is_some();
+                let operator = match (c,equal) {
+                    ('!',true) => Operator::NotEqual,
+                    ('!',false) if chars.next_if(|(_,c)| *c == '~').is_some() => Operator::NotContains,
+                    ('>',true) => Operator::GreaterEqual,
+                    ('>',false) => Operator::Greater,
+                    ('<',true) => Operator::LessEqual,
+                    ('<',false) => Operator::Less,
+                    _ => return Err(invalid(format!("unknown operator at {}",start))),
+                };
+                Token::Operator(operator)
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some((_,c)) = chars.next_if(|(_,c)| !c.is_whitespace() && !"()~=!<>\"'".contains(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, one method per precedence level
+struct Parser<'t>{
+    tokens:&'t [Token],
+    at:usize,
+}
+
+impl Parser<'_>{
+
+    fn keyword(&mut self,keyword:&str)->bool{
+        match self.tokens.get(self.at) {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.at += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn next(&mut self)->Option<&Token>{
+        let token = self.tokens.get(self.at);
+        self.at += 1;
+        token
+    }
+
+    fn or(&mut self)->Result<Node,std::io::Error>{
+        let mut node = self.and()?;
+        while self.keyword("or") {
+            node = Node::Or(Box::new(node),Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+
+    fn and(&mut self)->Result<Node,std::io::Error>{
+        let mut node = self.not()?;
+        while self.keyword("and") {
+            node = Node::And(Box::new(node),Box::new(self.not()?));
+        }
+        Ok(node)
+    }
+
+    fn not(&mut self)->Result<Node,std::io::Error>{
+        if self.keyword("not") {
+            return Ok(Node::Not(Box::new(self.not()?)));
+        }
+        if self.tokens.get(self.at) == Some(&Token::Open) {
+            self.at += 1;
+            let node = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(node),
+                _ => Err(invalid("missing )")),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self)->Result<Node,std::io::Error>{
+        let field = match self.next() {
+            Some(Token::Word(name)) => Field::parse(name).ok_or_else(|| invalid(format!("unknown field {}",name)))?,
+            Some(token) => return Err(invalid(format!("expected a field, found {:?}",token))),
+            None => return Err(invalid("expected a field")),
+        };
+        let operator = match self.next() {
+            Some(Token::Operator(operator)) => *operator,
+            _ => return Err(invalid("expected an operator")),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value.clone(),
+            _ => return Err(invalid("expected a value")),
+        };
+        let ordering = matches!(operator,Operator::Greater | Operator::GreaterEqual | Operator::Less | Operator::LessEqual);
+        if field.is_date() && (ordering || matches!(operator,Operator::Equal | Operator::NotEqual)) {
+            let date = Timestamp::parse(&value).ok_or_else(|| invalid(format!("invalid date {}",value)))?;
+            return Ok(Node::Date(field,operator,date));
+        }
+        if ordering {
+            return Err(invalid("only published and updated can be ordered"));
+        }
+        Ok(Node::Compare(field,operator,value.to_lowercase()))
+    }
+}
+
+fn invalid<E:std::fmt::Display>(error:E)->std::io::Error{
+    std::io::Error::new(std::io::ErrorKind::InvalidInput,format!("Failed by Query::parse: {}",error))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{Query,RssItem};
+
+    fn item(title:&str,author:&str,publish:&str)->RssItem{
+        RssItem{
+            title:String::from(title),
+            author:String::from(author),
+            publish:String::from(publish),
+            ..RssItem::default()
+        }
+    }
+
+    #[test]
+    fn future_rss_query(){
+        let query:Query = r#"title ~ "rust" AND published > 2024-01-01 AND author != "bot""#.parse().unwrap();
+        assert!(query.matches(&item("Learning Rust","MeteorCat","2024-03-01T10:00:00Z")));
+        assert!(!query.matches(&item("Learning Rust","Bot","2024-03-01T10:00:00Z")));
+        assert!(!query.matches(&item("Learning Rust","MeteorCat","Sun, 31 Dec 2023 23:00:00 GMT")));
+        assert!(!query.matches(&item("Learning Rust","MeteorCat","yesterday")));
+        assert!(!query.matches(&item("Learning Go","MeteorCat","2024-03-01T10:00:00Z")));
+
+        let query = Query::parse("title ~ go or not (author = 'bot' OR title !~ \"tom \\\"&\\\" jerry\")").unwrap();
+        assert!(query.matches(&item("Go","bot","")));
+        assert!(query.matches(&item("Tom \"&\" Jerry","MeteorCat","")));
+        assert!(!query.matches(&item("Tom \"&\" Jerry","BOT","")));
+        assert!(!query.matches(&item("Rust","MeteorCat","")));
+
+        // AND binds tighter than OR
+        let query = Query::parse("title = a OR title = b AND author = c").unwrap();
+        assert!(query.matches(&item("a","","")));
+        assert!(!query.matches(&item("b","","")));
+
+        for broken in ["","title","title ~","size > 3","title > a","published < never","(title ~ a","title ~ a)","title ~ \"a","title ^ a"] {
+            let error = Query::parse(broken).unwrap_err();
+            assert_eq!(error.kind(),std::io::ErrorKind::InvalidInput,"{}",broken);
+        }
+    }
+}
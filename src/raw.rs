@@ -0,0 +1,204 @@
+//!
+//! Lazily Decoded Items
+//!
+//! Fields keep the escaped bytes found in the document and are only
+//! unescaped and utf8-checked when read, so filters that throw most items
+//! away never pay for decoding them.
+//!
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use quick_xml::events::BytesText;
+use crate::{Category,Enclosure,RssItem,RssItemRef};
+
+///
+/// Escaped field text as found in the document
+///
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct RawField<'a>{
+    raw:Cow<'a,[u8]>,
+    cdata:bool,
+    pub(crate) namespace:Option<Arc<str>>,
+}
+
+impl<'a> RawField<'a>{
+
+    pub(crate) fn new(raw:Cow<'a,[u8]>,cdata:bool)->Self{
+        Self{ raw, cdata, namespace:None }
+    }
+
+    ///
+    /// Namespace uri the field's element was bound to, e.g.
+    /// `http://purl.org/dc/elements/1.1/` for `<dc:creator>`
+    ///
+    pub fn namespace(&self)->Option<&str>{
+        self.namespace.as_deref()
+    }
+
+    /// Bytes before unescaping, CDATA content is kept without its markers
+    pub fn as_bytes(&self)->&[u8]{
+        &self.raw
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.raw.is_empty()
+    }
+
+    /// Unescaped text, borrowed when no entity had to be replaced
+    pub fn decode(&self)->Result<Cow<'_,str>,std::io::Error>{
+        match unescape(&self.raw,self.cdata)? {
+            Cow::Borrowed(bytes) => from_utf8(bytes).map(Cow::Borrowed),
+            Cow::Owned(bytes) => from_utf8_owned(bytes).map(Cow::Owned),
+        }
+    }
+
+    /// Same as [`RawField::decode`] but keeps borrowing from the document
+    pub fn into_decoded(self)->Result<Cow<'a,str>,std::io::Error>{
+        match self.raw {
+            Cow::Borrowed(bytes) => match unescape(bytes,self.cdata)? {
+                Cow::Borrowed(bytes) => from_utf8(bytes).map(Cow::Borrowed),
+                Cow::Owned(bytes) => from_utf8_owned(bytes).map(Cow::Owned),
+            },
+            Cow::Owned(bytes) => match unescape(&bytes,self.cdata)? {
+                Cow::Borrowed(_) => from_utf8_owned(bytes).map(Cow::Owned),
+                Cow::Owned(bytes) => from_utf8_owned(bytes).map(Cow::Owned),
+            },
+        }
+    }
+}
+
+fn unescape(bytes:&[u8],cdata:bool)->Result<Cow<'_,[u8]>,std::io::Error>{
+    if cdata || !bytes.contains(&b'&') {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    BytesText::from_escaped(bytes)
+        .unescaped()
+        .map(|bytes| Cow::Owned(bytes.into_owned()))
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,format!("{:?}",e)))
+}
+
+/// Text of a field nobody asked for, broken entities are kept as found
+/// rather than failing the item
+fn lossy(field:RawField<'_>)->Cow<'_,str>{
+    if field.decode().is_err() {
+        return Cow::Owned(String::from_utf8_lossy(field.as_bytes()).into_owned());
+    }
+    field.into_decoded().unwrap_or_default()
+}
+
+fn from_utf8(bytes:&[u8])->Result<&str,std::io::Error>{
+    std::str::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,e))
+}
+
+fn from_utf8_owned(bytes:Vec<u8>)->Result<String,std::io::Error>{
+    String::from_utf8(bytes).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData,e))
+}
+
+///
+/// Rss Item Node with undecoded fields
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut parser = RssParser::new();
+///     parser.set_xml(String::from(
+///         "<rss><item><title>Rust &amp; Go</title><description>long text</description></item></rss>"
+///     ));
+///     for item in parser.parse_raw()? {
+///         // only the title is ever unescaped
+///         if item.title.decode()?.contains("Rust") {
+///             println!("{:?}",item.decode()?);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Default)]
+pub struct RawRssItem<'a>{
+    pub title: RawField<'a>,
+    pub link: RawField<'a>,
+    pub author: RawField<'a>,
+    pub description: RawField<'a>,
+    pub content: RawField<'a>,
+    pub guid: RawField<'a>,
+    pub publish: RawField<'a>,
+    pub updated: RawField<'a>,
+    /// Decoded when the start tag is read, their attributes are short
+    pub enclosures: Vec<Enclosure>,
+    pub categories: Vec<Category>,
+    /// Direct children of the item no configured tag captures, by name as
+    /// found, e.g. `dc:subject`; elements without text keep an empty value
+    pub extensions: BTreeMap<String,Vec<RawField<'a>>>,
+    #[cfg(feature = "podcast")]
+    pub podcast: Option<crate::ItunesItem>,
+}
+
+impl<'a> RawRssItem<'a>{
+
+    /// Decode every field, text stays borrowed where possible
+    pub fn decode(self)->Result<RssItemRef<'a>,std::io::Error>{
+        let title = self.title.into_decoded()?;
+        #[cfg(feature = "podcast")]
+        let podcast = self.podcast.map(|mut podcast| {
+            crate::itunes::numbers_from(&mut podcast,&title);
+            podcast
+        });
+        Ok(RssItemRef{
+            title,
+            link:self.link.into_decoded()?,
+            author:self.author.into_decoded()?,
+            description:self.description.into_decoded()?,
+            content:self.content.into_decoded()?,
+            guid:self.guid.into_decoded()?,
+            publish:self.publish.into_decoded()?,
+            updated:self.updated.into_decoded()?,
+            enclosures:self.enclosures,
+            categories:self.categories,
+            extensions:self.extensions.into_iter()
+                .map(|(name,values)| (name,values.into_iter().map(lossy).collect()))
+                .collect(),
+            #[cfg(feature = "podcast")]
+            podcast,
+        })
+    }
+
+    pub fn into_owned(self)->Result<RssItem,std::io::Error>{
+        self.decode().map(RssItemRef::into_owned)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use crate::RssParser;
+
+    #[test]
+    fn future_rss_raw_fields(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            "<rss><item><title>A &amp; B</title><link><![CDATA[x&y]]></link><guid>&bogus;</guid></item></rss>"
+        ));
+        let items = parser.parse_raw().unwrap();
+        assert_eq!(items[0].title.as_bytes(),b"A &amp; B");
+        assert_eq!(items[0].title.decode().unwrap(),"A & B");
+        assert!(matches!(items[0].link.decode().unwrap(),Cow::Borrowed("x&y")));
+        assert_eq!(items[0].title.namespace(),None);
+        // broken entities only fail when the field is read
+        assert!(items[0].guid.decode().is_err());
+        assert!(items[0].clone().decode().is_err());
+    }
+
+    #[test]
+    fn future_rss_raw_namespace(){
+        let mut parser = RssParser::new();
+        parser.author_tag = String::from("dc:creator");
+        parser.set_xml(String::from(
+            r#"<rss xmlns:dc="http://purl.org/dc/elements/1.1/"><item><dc:creator>MeteorCat</dc:creator></item></rss>"#
+        ));
+        let items = parser.parse_raw().unwrap();
+        assert_eq!(items[0].author.namespace(),Some("http://purl.org/dc/elements/1.1/"));
+    }
+}
@@ -0,0 +1,144 @@
+//!
+//! Error Recovery
+//!
+//! One malformed item should not cost the whole feed: after an xml error
+//! parsing resumes at the next item boundary, and every failure is kept
+//! with its position next to the items that could be read.
+//!
+
+use quick_xml::Reader;
+use crate::{RssParser,RssItem,RawRssItem};
+use crate::items::RawItems;
+
+///
+/// Failure met while reading a document
+///
+#[derive(Debug)]
+pub struct ItemError{
+    /// Byte offset in the document where the failure was noticed
+    pub offset:usize,
+    pub error:std::io::Error,
+}
+
+///
+/// Items read around the broken parts of a document
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main(){
+///     let mut parser = RssParser::new();
+///     parser.set_xml(String::from(
+///         "<rss><item><title>1</title></item><item><title>2</titl></item><item><title>3</title></item></rss>"
+///     ));
+///     assert!(parser.parse_vec().is_err());
+///
+///     let recovered = parser.parse_recover();
+///     assert_eq!(recovered.items.len(),2);
+///     assert_eq!(recovered.items[1].title,"3");
+///     assert_eq!(recovered.errors.len(),1);
+/// }
+/// ```
+#[derive(Debug,Default)]
+pub struct RecoveredItems{
+    pub items:Vec<RssItem>,
+    pub errors:Vec<ItemError>,
+}
+
+impl RecoveredItems{
+
+    /// Document was read without any failure
+    pub fn is_complete(&self)->bool{
+        self.errors.is_empty()
+    }
+}
+
+pub(crate) fn parse_recover(parser:&RssParser,xml:&str)->RecoveredItems{
+    let mut recovered = RecoveredItems::default();
+    let mut base = 0;
+    loop {
+        let slice = &xml[base..];
+        let mut items = RawItems::new(parser,Reader::from_str(slice),Some(slice),Vec::new());
+        if base > 0 {
+            items = items.resumed();
+        }
+        let mut failed = None;
+        while let Some(item) = items.next() {
+            // text that fails to decode only costs its own item
+            match item.and_then(RawRssItem::into_owned) {
+                Ok(item) => recovered.items.push(item),
+                Err(error) if error.kind() == std::io::ErrorKind::InvalidData => {
+                    recovered.errors.push(ItemError{ offset:base + items.position(), error });
+                }
+                Err(error) => {
+                    failed = Some(ItemError{ offset:base + items.position(), error });
+                    break;
+                }
+            }
+        }
+        let failed = match failed {
+            Some(failed) => failed,
+            None => break,
+        };
+        // running out of time or memory is not something to skip past
+        let fatal = matches!(failed.error.kind(),std::io::ErrorKind::TimedOut | std::io::ErrorKind::OutOfMemory);
+        let next = next_item(xml,failed.offset.max(base + 1),&parser.node_tag);
+        recovered.errors.push(failed);
+        match next {
+            Some(next) if !fatal => base = next,
+            _ => break,
+        }
+    }
+    recovered
+}
+
+/// Offset of the next `<node_tag` start tag at or after `from`
+fn next_item(xml:&str,from:usize,node_tag:&str)->Option<usize>{
+    let rest = xml.get(from..)?;
+    rest.match_indices('<')
+        .map(|(i,_)| from + i)
+        .find(|&start| {
+            let name = xml.get(start + 1..start + 1 + node_tag.len());
+            let after = xml.as_bytes().get(start + 1 + node_tag.len());
+            name.is_some_and(|name| name.eq_ignore_ascii_case(node_tag))
+                && after.is_some_and(|b| b.is_ascii_whitespace() || *b == b'>' || *b == b'/')
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::RssParser;
+
+    #[test]
+    fn future_rss_parse_recover(){
+        let mut parser = RssParser::new();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0"><channel>
+                    <item><title>1</title></item>
+                    <item><title>2</title><link>x</lnk></item>
+                    <item><title>3</title><guid>&bogus;</guid></item>
+                    <item><title>4</title><author><b>a</i></author></item>
+                    <ITEM><title>5</title></ITEM>
+                    <items>not an item</items>
+                </channel></rss>
+        "#));
+        let recovered = parser.parse_recover();
+        let titles = recovered.items.iter().map(|item| item.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles,["1","5"]);
+        assert_eq!(recovered.errors.len(),3);
+        assert!(recovered.errors.windows(2).all(|w| w[0].offset < w[1].offset));
+        assert!(!recovered.is_complete());
+
+        parser.set_xml(String::from("<rss><channel><item><title>1</title></item></channel></rss>"));
+        let recovered = parser.parse_recover();
+        assert!(recovered.is_complete());
+        assert_eq!(recovered.items.len(),1);
+
+        // the broken item is the last one
+        parser.set_xml(String::from("<rss><item><title>1</title></item><item><title>2</tite></item></rss>"));
+        let recovered = parser.parse_recover();
+        assert_eq!((recovered.items.len(),recovered.errors.len()),(1,1));
+    }
+}
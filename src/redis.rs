@@ -0,0 +1,519 @@
+//!
+//! Redis Cache Backend
+//!
+//! [`RedisCache`] speaks the Redis protocol (RESP) over a plain TCP
+//! connection, so several aggregator instances share their validators and
+//! seen items without a client library; [`RedisSeenStore`] lets them claim
+//! new items in a Redis set.
+//!
+
+use std::io::{BufRead,BufReader,Read,Write};
+use std::net::{TcpStream,ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+use crate::{CacheBackend,SeenStore,CACHE_KEY_PREFIX};
+
+/// Port used when the url names none
+pub static REDIS_DEFAULT_PORT:u16 = 6379;
+
+/// Connect, read and write timeout
+pub static REDIS_DEFAULT_TIMEOUT:Duration = Duration::from_secs(5);
+
+/// Longest bulk string read from the server, the limit of Redis itself
+pub static REDIS_MAX_BULK_LENGTH:usize = 512 * 1024 * 1024;
+
+///
+/// Reply to a command
+///
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub(crate) enum Reply{
+    Status(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<String>),
+    Array(Vec<Reply>),
+}
+
+///
+/// Cache backend on a Redis server
+///
+/// The connection is opened on first use and opened again once when it
+/// breaks; entries never expire unless [`RedisCache::ttl`] is set.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use future_rss::{FeedWatcher,RedisCache};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let cache = RedisCache::new("redis://:secret@127.0.0.1:6379/2")?
+///         .ttl(Duration::from_secs(30 * 24 * 3600));
+///     let mut watcher = FeedWatcher::new("https://www.zhihu.com/rss","utf8")
+///         .with_cache(Arc::new(cache));
+///     watcher.poll()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RedisCache{
+    host:String,
+    port:u16,
+    username:Option<String>,
+    password:Option<String>,
+    database:u32,
+    ttl:Option<Duration>,
+    timeout:Duration,
+    connection:Mutex<Option<BufReader<TcpStream>>>,
+}
+
+impl RedisCache{
+
+    ///
+    /// Backend for `redis://[[user]:password@]host[:port][/database]`,
+    /// `InvalidInput` for any other url
+    ///
+    pub fn new(url:&str)->Result<Self,std::io::Error>{
+        let invalid = |reason:&str| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Failed by RedisCache::new: {} in {:?}",reason,url)
+        );
+        let parsed = url::Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+        if parsed.scheme() != "redis" {
+            return Err(invalid("scheme is not redis"));
+        }
+        let host = parsed.host_str().filter(|host| !host.is_empty()).ok_or_else(|| invalid("no host"))?;
+        let database = match parsed.path().trim_start_matches('/') {
+            "" => 0,
+            database => database.parse().map_err(|_| invalid("invalid database"))?,
+        };
+        // percent-decoding of the credentials, a plus stays a plus
+        let decode = |text:&str| url::form_urlencoded::parse(format!("v={}",text.replace('+',"%2B")).as_bytes())
+            .next()
+            .map(|(_,value)| value.into_owned())
+            .filter(|value| !value.is_empty());
+        Ok(Self{
+            host:host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port:parsed.port().unwrap_or(REDIS_DEFAULT_PORT),
+            username:decode(parsed.username()),
+            password:parsed.password().and_then(decode),
+            database,
+            ttl:None,
+            timeout:REDIS_DEFAULT_TIMEOUT,
+            connection:Mutex::new(None),
+        })
+    }
+
+    /// Let written entries expire after `ttl`, rounded up to a second
+    pub fn ttl(mut self,ttl:Duration)->Self{
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn timeout(mut self,timeout:Duration)->Self{
+        self.timeout = timeout;
+        self
+    }
+
+    ///
+    /// Send a command and wait for its reply, error replies become errors
+    ///
+    pub(crate) fn command(&self,args:&[&str])->Result<Reply,std::io::Error>{
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        // a connection the server closed while idle is replaced before sending
+        if connection.as_ref().is_some_and(is_stale) {
+            *connection = None;
+        }
+        let mut retried = false;
+        loop {
+            let stream = match connection.as_mut() {
+                Some(stream) => stream,
+                None => connection.insert(self.connect()?),
+            };
+            // a command that was not written whole never ran and is sent again
+            if let Err(e) = stream.get_mut().write_all(&encode(args)) {
+                *connection = None;
+                if retried {
+                    return Err(e);
+                }
+                retried = true;
+                continue;
+            }
+            return match read_reply(stream) {
+                Ok(Reply::Error(message)) => Err(std::io::Error::other(format!("Failed by RedisCache: {}",message))),
+                Ok(reply) => Ok(reply),
+                // the command may have run, sending it again could apply it twice
+                Err(e) => {
+                    *connection = None;
+                    Err(e)
+                }
+            };
+        }
+    }
+
+    fn connect(&self)->Result<BufReader<TcpStream>,std::io::Error>{
+        let mut last = None;
+        for address in (self.host.as_str(),self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address,self.timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(self.timeout))?;
+                    stream.set_write_timeout(Some(self.timeout))?;
+                    let mut stream = BufReader::new(stream);
+                    self.handshake(&mut stream)?;
+                    return Ok(stream);
+                }
+                Err(e) => last = Some(e),
+            }
+        }
+        Err(last.unwrap_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Failed by RedisCache: no address for {}",self.host)
+        )))
+    }
+
+    fn handshake(&self,stream:&mut BufReader<TcpStream>)->Result<(),std::io::Error>{
+        let database = self.database.to_string();
+        let mut commands = Vec::new();
+        match (&self.username,&self.password) {
+            (Some(username),Some(password)) => commands.push(vec!["AUTH",username.as_str(),password.as_str()]),
+            (None,Some(password)) => commands.push(vec!["AUTH",password.as_str()]),
+            _ => (),
+        }
+        if self.database != 0 {
+            commands.push(vec!["SELECT",database.as_str()]);
+        }
+        for command in commands {
+            if let Reply::Error(message) = exchange(stream,&command)? {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("Failed by RedisCache: {}",message)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CacheBackend for RedisCache{
+
+    fn get(&self,key:&str)->Result<Option<String>,std::io::Error>{
+        match self.command(&["GET",key])? {
+            Reply::Bulk(value) => Ok(value),
+            reply => Err(unexpected(&reply)),
+        }
+    }
+
+    fn set(&self,key:&str,value:&str)->Result<(),std::io::Error>{
+        let seconds = self.ttl.map(|ttl| (ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0)).max(1).to_string());
+        let reply = match &seconds {
+            Some(seconds) => self.command(&["SET",key,value,"EX",seconds])?,
+            None => self.command(&["SET",key,value])?,
+        };
+        match reply {
+            Reply::Status(_) => Ok(()),
+            reply => Err(unexpected(&reply)),
+        }
+    }
+
+    fn remove(&self,key:&str)->Result<(),std::io::Error>{
+        match self.command(&["DEL",key])? {
+            Reply::Integer(_) => Ok(()),
+            reply => Err(unexpected(&reply)),
+        }
+    }
+}
+
+///
+/// Seen store on Redis sets with a time to live
+///
+/// Keys go into a set per `ttl` long window, which expires two windows
+/// later; a key also found in the previous window counts as seen and moves
+/// on to the current one, so it is remembered for at least `ttl` after it
+/// was last inserted.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use future_rss::{Pipeline,RedisSeenStore,FeedEvent};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let seen = RedisSeenStore::new("redis://127.0.0.1","news",Duration::from_secs(7 * 24 * 3600))?;
+///     let report = Pipeline::new()
+///         .seen_store(Arc::new(seen))
+///         .run(&mut [],&|feed:&str,event:FeedEvent| Ok(println!("{} {:?}",feed,event)));
+///     println!("{:?}",report);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RedisSeenStore{
+    redis:RedisCache,
+    name:String,
+    ttl:Duration,
+}
+
+impl RedisSeenStore{
+
+    ///
+    /// Store in the sets `future_rss:<name>:<window>` of the server at
+    /// `url`, see [`RedisCache::new`]; `ttl` is at least a second
+    ///
+    pub fn new(url:&str,name:&str,ttl:Duration)->Result<Self,std::io::Error>{
+        Ok(Self{
+            redis:RedisCache::new(url)?,
+            name:name.to_string(),
+            ttl:ttl.max(Duration::from_secs(1)),
+        })
+    }
+
+    pub fn timeout(mut self,timeout:Duration)->Self{
+        self.redis = self.redis.timeout(timeout);
+        self
+    }
+
+    /// Sets of the current and the previous window
+    fn windows(&self)->(String,String){
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let window = now / self.ttl.as_secs();
+        let set = |window:u64| format!("{}{}:{}",CACHE_KEY_PREFIX,self.name,window);
+        (set(window),set(window.saturating_sub(1)))
+    }
+
+    fn is_member(&self,set:&str,key:&str)->Result<bool,std::io::Error>{
+        match self.redis.command(&["SISMEMBER",set,key])? {
+            Reply::Integer(found) => Ok(found == 1),
+            reply => Err(unexpected(&reply)),
+        }
+    }
+}
+
+impl SeenStore for RedisSeenStore{
+
+    fn insert(&self,key:&str)->Result<bool,std::io::Error>{
+        let (current,previous) = self.windows();
+        let seen_before = self.is_member(&previous,key)?;
+        let added = match self.redis.command(&["SADD",&current,key])? {
+            Reply::Integer(added) => added == 1,
+            reply => return Err(unexpected(&reply)),
+        };
+        let seconds = (self.ttl.as_secs() * 2).to_string();
+        self.redis.command(&["EXPIRE",&current,&seconds])?;
+        Ok(added && !seen_before)
+    }
+
+    fn contains(&self,key:&str)->Result<bool,std::io::Error>{
+        let (current,previous) = self.windows();
+        Ok(self.is_member(&current,key)? || self.is_member(&previous,key)?)
+    }
+}
+
+fn unexpected(reply:&Reply)->std::io::Error{
+    std::io::Error::new(std::io::ErrorKind::InvalidData,format!("Failed by RedisCache: unexpected reply {:?}",reply))
+}
+
+///
+/// Server closed the idle connection or left bytes nobody asked for on it,
+/// told without waiting
+///
+fn is_stale(stream:&BufReader<TcpStream>)->bool{
+    if !stream.buffer().is_empty() {
+        return true;
+    }
+    let socket = stream.get_ref();
+    if socket.set_nonblocking(true).is_err() {
+        return true;
+    }
+    let idle = matches!(socket.peek(&mut [0u8;1]),Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock);
+    socket.set_nonblocking(false).is_err() || !idle
+}
+
+fn exchange(stream:&mut BufReader<TcpStream>,args:&[&str])->Result<Reply,std::io::Error>{
+    stream.get_mut().write_all(&encode(args))?;
+    read_reply(stream)
+}
+
+/// Command as an array of bulk strings
+pub(crate) fn encode(args:&[&str])->Vec<u8>{
+    let mut buffer = format!("*{}\r\n",args.len()).into_bytes();
+    for arg in args {
+        buffer.extend_from_slice(format!("${}\r\n",arg.len()).as_bytes());
+        buffer.extend_from_slice(arg.as_bytes());
+        buffer.extend_from_slice(b"\r\n");
+    }
+    buffer
+}
+
+pub(crate) fn read_reply<R:BufRead>(reader:&mut R)->Result<Reply,std::io::Error>{
+    let invalid = |line:&str| std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Failed by RedisCache: invalid reply {:?}",line)
+    );
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof,"Failed by RedisCache: connection closed"));
+    }
+    let line = line.trim_end_matches(['\r','\n']);
+    if line.is_empty() {
+        return Err(invalid(line));
+    }
+    let (kind,rest) = line.split_at(line.chars().next().map_or(0,char::len_utf8));
+    let number = || rest.parse::<i64>().map_err(|_| invalid(line));
+    match kind {
+        "+" => Ok(Reply::Status(rest.to_string())),
+        "-" => Ok(Reply::Error(rest.to_string())),
+        ":" => Ok(Reply::Integer(number()?)),
+        "$" if number()? < 0 => Ok(Reply::Bulk(None)),
+        "$" => {
+            let length = number()?;
+            if length > REDIS_MAX_BULK_LENGTH as i64 {
+                return Err(invalid(line));
+            }
+            // the buffer grows with what arrives, not with what was announced
+            let framed = (length as usize).checked_add(2).ok_or_else(|| invalid(line))?;
+            let mut value = Vec::new();
+            reader.by_ref().take(framed as u64).read_to_end(&mut value)?;
+            if value.len() < framed {
+                return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof,"Failed by RedisCache: connection closed"));
+            }
+            if !value.ends_with(b"\r\n") {
+                return Err(invalid(line));
+            }
+            value.truncate(length as usize);
+            Ok(Reply::Bulk(Some(String::from_utf8_lossy(&value).into_owned())))
+        }
+        "*" if number()? < 0 => Ok(Reply::Array(Vec::new())),
+        "*" => (0..number()?).map(|_| read_reply(reader)).collect::<Result<_,_>>().map(Reply::Array),
+        _ => Err(invalid(line)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap,HashSet};
+    use std::io::{BufReader,Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc,Mutex};
+    use std::time::Duration;
+    use crate::{CacheBackend,SeenStore,RedisCache,RedisSeenStore};
+    use super::{Reply,read_reply};
+
+    /// Redis stand-in keeping its keys in memory and logging every command
+    fn serve(log:Arc<Mutex<Vec<String>>>)->u16{
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let data = Arc::new(Mutex::new((HashMap::<String,String>::new(),HashMap::<String,HashSet<String>>::new())));
+        std::thread::spawn(move || for stream in listener.incoming() {
+            let (log,data) = (log.clone(),data.clone());
+            let mut reader = BufReader::new(stream.unwrap());
+            std::thread::spawn(move || {
+                while let Ok(Reply::Array(args)) = read_reply(&mut reader) {
+                    let args = args.into_iter()
+                        .map(|arg| match arg { Reply::Bulk(Some(arg)) => arg, _ => String::new() })
+                        .collect::<Vec<_>>();
+                    log.lock().unwrap().push(args.join(" "));
+                    let (store,sets) = &mut *data.lock().unwrap();
+                    let reply = match args[0].as_str() {
+                        "GET" if args[1] == "list" => String::from("-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"),
+                        "GET" => match store.get(&args[1]) {
+                            Some(value) => format!("${}\r\n{}\r\n",value.len(),value),
+                            None => String::from("$-1\r\n"),
+                        },
+                        "SET" => {
+                            store.insert(args[1].clone(),args[2].clone());
+                            String::from("+OK\r\n")
+                        }
+                        "DEL" => format!(":{}\r\n",store.remove(&args[1]).map_or(0,|_| 1)),
+                        "SADD" => format!(":{}\r\n",u8::from(sets.entry(args[1].clone()).or_default().insert(args[2].clone()))),
+                        "SISMEMBER" => format!(":{}\r\n",u8::from(sets.get(&args[1]).is_some_and(|set| set.contains(&args[2])))),
+                        "EXPIRE" => String::from(":1\r\n"),
+                        "QUIT" => break,
+                        // answers, then hangs up
+                        "BYE" => {
+                            let _ = reader.get_mut().write_all(b"+OK\r\n");
+                            break;
+                        }
+                        "SLEEP" => {
+                            std::thread::sleep(Duration::from_millis(300));
+                            String::from("+OK\r\n")
+                        }
+                        _ => String::from("+OK\r\n"),
+                    };
+                    reader.get_mut().write_all(reply.as_bytes()).unwrap();
+                }
+            });
+        });
+        port
+    }
+
+    #[test]
+    fn future_rss_redis_cache(){
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let port = serve(log.clone());
+        let cache = RedisCache::new(&format!("redis://:s%40c+ret@127.0.0.1:{}/2",port)).unwrap()
+            .ttl(Duration::from_millis(1500));
+        assert_eq!(cache.get("a").unwrap(),None);
+        cache.set("a","line\r\nbreak").unwrap();
+        assert_eq!(cache.get("a").unwrap().as_deref(),Some("line\r\nbreak"));
+        cache.remove("a").unwrap();
+        assert!(cache.get("list").unwrap_err().to_string().contains("WRONGTYPE"));
+
+        // the server hangs up, the next command connects again
+        cache.command(&["QUIT"]).unwrap_err();
+        assert_eq!(cache.get("a").unwrap(),None);
+        assert_eq!(log.lock().unwrap()[..4],["AUTH s@c+ret","SELECT 2","GET a","SET a line\r\nbreak EX 2"]);
+
+        // closed while idle, noticed before the next command is sent
+        cache.command(&["BYE"]).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        cache.set("b","1").unwrap();
+        // a reply that is late is not asked for twice
+        let slow = RedisCache::new(&format!("redis://127.0.0.1:{}",port)).unwrap().timeout(Duration::from_millis(100));
+        assert!(slow.command(&["SLEEP"]).is_err());
+        std::thread::sleep(Duration::from_millis(400));
+        let log = log.lock().unwrap();
+        assert_eq!(log.iter().filter(|command| command.starts_with("SET b")).count(),1);
+        assert_eq!(log.iter().filter(|command| *command == "SLEEP").count(),1);
+    }
+
+    #[test]
+    fn future_rss_redis_seen_store(){
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let port = serve(log.clone());
+        let url = format!("redis://127.0.0.1:{}",port);
+        let first = RedisSeenStore::new(&url,"news",Duration::from_secs(60)).unwrap();
+        let second = RedisSeenStore::new(&url,"news",Duration::from_secs(60)).unwrap();
+        assert!(first.insert("a").unwrap());
+        assert!(!second.insert("a").unwrap());
+        assert!(second.contains("a").unwrap());
+        assert!(!second.contains("b").unwrap());
+
+        // keys of the previous window are still seen
+        let (current,previous) = first.windows();
+        first.redis.command(&["SADD",&previous,"old"]).unwrap();
+        assert!(!second.insert("old").unwrap());
+        assert!(log.lock().unwrap().contains(&format!("EXPIRE {} 120",current)));
+        assert!(current.starts_with("future_rss:news:"));
+    }
+
+    #[test]
+    fn future_rss_redis_reply(){
+        let read = |reply:&str| read_reply(&mut BufReader::new(reply.as_bytes()));
+        assert_eq!(read("$5\r\nHey!\n\r\n").unwrap(),Reply::Bulk(Some(String::from("Hey!\n"))));
+        assert_eq!(read("*2\r\n:1\r\n$-1\r\n").unwrap(),Reply::Array(vec![Reply::Integer(1),Reply::Bulk(None)]));
+        assert_eq!(read("$9223372036854775807\r\n").unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+        assert_eq!(read("$536870913\r\n").unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+        assert_eq!(read("$4\r\nHey!").unwrap_err().kind(),std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(read("$4\r\nHey!!!").unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+        assert_eq!(read("é\r\n").unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+        assert_eq!(read("\r\n").unwrap_err().kind(),std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn future_rss_redis_url(){
+        for broken in ["http://127.0.0.1","redis://127.0.0.1/db","redis:///0","not a url"] {
+            assert!(RedisCache::new(broken).is_err(),"{}",broken);
+        }
+        let offline = RedisCache::new("redis://127.0.0.1:1").unwrap().timeout(Duration::from_millis(100));
+        assert!(offline.get("a").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//!
+//! Request Retries
+//!
+//! A [`RetryPolicy`] set on [`RequestOptions`](crate::RequestOptions)
+//! sends a failed request again after an exponentially growing wait:
+//! connection errors and timeouts always count as failures, 5xx answers
+//! unless told otherwise. The last outcome is handed back as it is.
+//!
+
+use std::time::Duration;
+
+/// Attempts of a request, the first one included
+pub static RETRY_DEFAULT_ATTEMPTS:u32 = 3;
+
+/// Wait before the first retry
+pub static RETRY_DEFAULT_BACKOFF:Duration = Duration::from_millis(500);
+
+/// Longest wait between two attempts
+pub static RETRY_DEFAULT_MAX_BACKOFF:Duration = Duration::from_secs(30);
+
+///
+/// How often and how late a request is sent again
+///
+/// ```
+/// use std::time::Duration;
+/// use future_rss::RetryPolicy;
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(4)
+///     .backoff(Duration::from_secs(1),Duration::from_secs(3));
+/// assert_eq!(policy.delay(1),Duration::from_secs(1));
+/// assert_eq!(policy.delay(2),Duration::from_secs(2));
+/// assert_eq!(policy.delay(3),Duration::from_secs(3));
+/// ```
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct RetryPolicy{
+    /// Attempts in all, the first one included; 1 never retries
+    pub max_attempts:u32,
+    /// Wait before the first retry, doubled before each next one
+    pub backoff:Duration,
+    pub max_backoff:Duration,
+    /// Retry 5xx answers as well as failed requests
+    pub retry_server_errors:bool,
+}
+
+impl Default for RetryPolicy{
+    fn default() -> Self {
+        Self{
+            max_attempts:RETRY_DEFAULT_ATTEMPTS,
+            backoff:RETRY_DEFAULT_BACKOFF,
+            max_backoff:RETRY_DEFAULT_MAX_BACKOFF,
+            retry_server_errors:true,
+        }
+    }
+}
+
+impl RetryPolicy{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    pub fn max_attempts(mut self,attempts:u32)->Self{
+        self.max_attempts = attempts;
+        self
+    }
+
+    pub fn backoff(mut self,initial:Duration,max:Duration)->Self{
+        self.backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn retry_server_errors(mut self,retry:bool)->Self{
+        self.retry_server_errors = retry;
+        self
+    }
+
+    /// Wait before retry number `retry`, counted from 1
+    pub fn delay(&self,retry:u32)->Duration{
+        let factor = 1u32.checked_shl(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.backoff.checked_mul(factor).unwrap_or(self.max_backoff).min(self.max_backoff)
+    }
+
+    /// Outcome of attempt number `attempt` calls for another one
+    fn retries<T,E>(&self,attempt:u32,result:&Result<T,E>,status:&impl Fn(&T)->u16)->bool{
+        attempt < self.max_attempts && match result {
+            Ok(response) => self.retry_server_errors && (500..600).contains(&status(response)),
+            Err(_) => true,
+        }
+    }
+}
+
+///
+/// Run `send` until it succeeds or `policy` gives up, sleeping on the
+/// calling thread in between; `status` reads the HTTP status of an answer
+///
+pub(crate) fn with_retry<T,E>(policy:Option<&RetryPolicy>,mut send:impl FnMut()->Result<T,E>,status:impl Fn(&T)->u16)->Result<T,E>{
+    let policy = match policy { Some(policy) => policy, None => return send() };
+    let mut attempt = 1;
+    loop {
+        let result = send();
+        if !policy.retries(attempt,&result,&status) {
+            return result;
+        }
+        std::thread::sleep(policy.delay(attempt));
+        attempt += 1;
+    }
+}
+
+///
+/// [`with_retry`] for async requests, the waits run off the async task
+/// and leave the executor free
+///
+#[cfg(all(feature = "http-async",not(target_arch = "wasm32")))]
+pub(crate) async fn with_retry_async<T,E,F,R>(policy:Option<&RetryPolicy>,mut send:F,status:impl Fn(&T)->u16)->Result<T,E>
+    where F:FnMut()->R,R:std::future::Future<Output=Result<T,E>>
+{
+    let policy = match policy { Some(policy) => policy, None => return send().await };
+    let mut attempt = 1;
+    loop {
+        let result = send().await;
+        if !policy.retries(attempt,&result,&status) {
+            return result;
+        }
+        let delay = policy.delay(attempt);
+        // a failed wait only shortens it
+        let _ = crate::runtime::spawn_blocking(move || std::thread::sleep(delay)).await;
+        attempt += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+    use crate::RetryPolicy;
+    use super::with_retry;
+
+    #[test]
+    fn future_rss_retry_policy(){
+        let policy = RetryPolicy::new().backoff(Duration::from_millis(1),Duration::from_millis(3));
+        assert_eq!(policy.delay(1),Duration::from_millis(1));
+        assert_eq!(policy.delay(3),Duration::from_millis(3));
+        assert_eq!(policy.delay(64),Duration::from_millis(3));
+
+        let sent = Cell::new(0);
+        let answer = |status:u16| { sent.set(sent.get() + 1); Ok::<u16,()>(status) };
+        assert_eq!(with_retry(Some(&policy),|| answer(503),|s| *s),Ok(503));
+        assert_eq!(sent.replace(0),3);
+        assert_eq!(with_retry(Some(&policy.retry_server_errors(false)),|| answer(503),|s| *s),Ok(503));
+        assert_eq!(sent.replace(0),1);
+        assert_eq!(with_retry(None,|| answer(503),|s| *s),Ok(503));
+        assert_eq!(sent.replace(0),1);
+
+        // fails twice, then answers
+        let flaky = || { sent.set(sent.get() + 1); if sent.get() < 3 { Err(()) }else { Ok(200) } };
+        assert_eq!(with_retry(Some(&policy),flaky,|s:&u16| *s),Ok(200));
+        assert_eq!(with_retry(Some(&policy.max_attempts(1)),|| Err::<u16,()>(()),|s| *s),Err(()));
+    }
+}
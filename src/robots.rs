@@ -0,0 +1,377 @@
+//!
+//! Polite Fetching
+//!
+//! [`PoliteFetcher`] wraps any [`HttpFetcher`]: before the first request to a
+//! host it downloads `/robots.txt`, refuses paths the file disallows for the
+//! crawler and spaces requests to the same host by its `Crawl-delay`.
+//!
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration,Instant};
+use crate::{HttpFetcher,HttpResponse};
+
+/// User-agent sent with every request and matched against robots.txt groups
+pub static ROBOTS_DEFAULT_USER_AGENT:&str = concat!("future_rss/",env!("CARGO_PKG_VERSION"));
+
+/// Longest `Crawl-delay` honoured, larger values are capped
+pub static ROBOTS_MAX_CRAWL_DELAY:Duration = Duration::from_secs(60);
+
+/// How long a robots.txt that failed to download keeps a host closed
+pub static ROBOTS_DEFAULT_FAILURE_RETRY:Duration = Duration::from_secs(300);
+
+///
+/// Rules of a robots.txt that apply to one crawler
+///
+/// ```
+/// use future_rss::RobotsTxt;
+///
+/// fn main(){
+///     let robots = RobotsTxt::parse("User-agent: *\nDisallow: /private\nAllow: /private/feed.xml\n","future_rss");
+///     assert!(robots.allowed("/rss"));
+///     assert!(!robots.allowed("/private/notes"));
+///     assert!(robots.allowed("/private/feed.xml"));
+/// }
+/// ```
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct RobotsTxt{
+    /// Path patterns and whether they are allowed
+    rules:Vec<(String,bool)>,
+    pub crawl_delay:Option<Duration>,
+}
+
+impl RobotsTxt{
+
+    /// Everything allowed, used when a host has no robots.txt
+    pub fn allow_all()->Self{
+        Self::default()
+    }
+
+    /// Nothing allowed, used while a host answers robots.txt with 5xx
+    pub fn disallow_all()->Self{
+        Self{
+            rules:vec![(String::from("/"),false)],
+            crawl_delay:None,
+        }
+    }
+
+    ///
+    /// Keep the groups matching the product token of `user_agent`,
+    /// or the `*` groups when none does
+    ///
+    pub fn parse(text:&str,user_agent:&str)->Self{
+        let product = user_agent.split('/').next().unwrap_or("").trim().to_ascii_lowercase();
+        let mut exact = Self::default();
+        let mut wildcard = Self::default();
+        let (mut matched_exact,mut matched_wildcard) = (false,false);
+        let mut agents:Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let (key,value) = match line.split_once(':') {
+                Some((key,value)) => (key.trim().to_ascii_lowercase(),value.trim()),
+                None => continue,
+            };
+            if key == "user-agent" {
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.to_ascii_lowercase());
+                continue;
+            }
+            in_rules = true;
+            let target = if agents.contains(&product) {
+                matched_exact = true;
+                &mut exact
+            }else if agents.iter().any(|a| a == "*") {
+                matched_wildcard = true;
+                &mut wildcard
+            }else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" if !value.is_empty() => target.rules.push((value.to_string(),true)),
+                "disallow" if !value.is_empty() => target.rules.push((value.to_string(),false)),
+                "crawl-delay" => {
+                    target.crawl_delay = value.parse::<f64>()
+                        .ok()
+                        .filter(|s| s.is_finite() && *s >= 0.0)
+                        .map(|s| Duration::from_secs_f64(s).min(ROBOTS_MAX_CRAWL_DELAY));
+                }
+                _ => {}
+            }
+        }
+        match (matched_exact,matched_wildcard) {
+            (true,_) => exact,
+            (false,true) => wildcard,
+            _ => Self::allow_all(),
+        }
+    }
+
+    ///
+    /// The longest matching pattern decides, `Allow` wins a tie
+    ///
+    pub fn allowed(&self,path:&str)->bool{
+        let mut best:Option<(usize,bool)> = None;
+        for (pattern,allow) in self.rules.iter() {
+            if !matches(pattern,path) {
+                continue;
+            }
+            let better = match best {
+                None => true,
+                Some((len,allowed)) => pattern.len() > len || (pattern.len() == len && *allow && !allowed),
+            };
+            if better {
+                best = Some((pattern.len(),*allow));
+            }
+        }
+        best.map(|(_,allow)| allow).unwrap_or(true)
+    }
+}
+
+/// Prefix match of a robots.txt pattern, with `*` wildcards and a `$` end anchor
+fn matches(pattern:&str,path:&str)->bool{
+    let (pattern,anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern,true),
+        None => (pattern,false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !path.starts_with(first) {
+        return false;
+    }
+    let mut position = first.len();
+    let mut parts = parts.peekable();
+    if parts.peek().is_none() {
+        return !anchored || position == path.len();
+    }
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() && anchored {
+            return path.len() - position >= part.len() && path.ends_with(part);
+        }
+        match path[position..].find(part) {
+            Some(found) => position += found + part.len(),
+            None => return false,
+        }
+    }
+    true
+}
+
+/// `scheme://authority` and the path with its query of an absolute url
+fn split_url(url:&str)->Option<(&str,&str)>{
+    let start = url.find("://")? + 3;
+    let end = url[start..].find(['/','?','#']).map(|i| i + start).unwrap_or(url.len());
+    let path = url[end..].split('#').next().unwrap_or("");
+    Some((&url[..end],path))
+}
+
+#[derive(Debug)]
+struct Host{
+    robots:RobotsTxt,
+    next_request:Instant,
+    /// set for rules that stand in for a failed download
+    expires:Option<Instant>,
+}
+
+///
+/// Fetcher respecting robots.txt and Crawl-delay per host
+///
+/// ```no_run
+/// use future_rss::{PoliteFetcher,RssParser};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     let fetcher = PoliteFetcher::new(reqwest::blocking::Client::new());
+///     let mut parser = RssParser::new();
+///     let rss = parser.parse_url_with(&fetcher,"https://www.zhihu.com/rss","utf8")?;
+///     println!("{:?}",rss);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PoliteFetcher<F>{
+    inner:F,
+    user_agent:String,
+    failure_retry:Duration,
+    hosts:Mutex<HashMap<String,Host>>,
+}
+
+impl<F:HttpFetcher> PoliteFetcher<F>{
+
+    pub fn new(inner:F)->Self{
+        Self{
+            inner,
+            user_agent:String::from(ROBOTS_DEFAULT_USER_AGENT),
+            failure_retry:ROBOTS_DEFAULT_FAILURE_RETRY,
+            hosts:Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// User-agent sent and looked up in robots.txt, see [`ROBOTS_DEFAULT_USER_AGENT`]
+    pub fn user_agent(mut self,user_agent:&str)->Self{
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Download robots.txt again this long after a failed attempt, see [`ROBOTS_DEFAULT_FAILURE_RETRY`]
+    pub fn failure_retry(mut self,retry:Duration)->Self{
+        self.failure_retry = retry;
+        self
+    }
+
+    ///
+    /// Rules of the host of `url`, downloaded on first use:
+    /// a 4xx answer allows everything, a 5xx or failed one nothing until
+    /// it is downloaded again after [`PoliteFetcher::failure_retry`]
+    ///
+    pub fn robots(&self,url:&str)->Result<RobotsTxt,std::io::Error>{
+        let (origin,_) = split_url(url).ok_or_else(|| invalid_url(url))?;
+        let key = origin.to_ascii_lowercase();
+        if let Some(host) = self.hosts.lock().unwrap().get(&key) {
+            match host.expires {
+                Some(expires) if expires <= Instant::now() => (),
+                _ => return Ok(host.robots.clone()),
+            }
+        }
+        let (robots,failed) = match self.inner.fetch(&format!("{}/robots.txt",origin),&[("User-Agent",&self.user_agent)]) {
+            Ok(response) if response.status < 300 => (RobotsTxt::parse(&response.text("utf8")?,&self.user_agent),false),
+            Ok(response) if (400..500).contains(&response.status) => (RobotsTxt::allow_all(),false),
+            _ => (RobotsTxt::disallow_all(),true),
+        };
+        let mut hosts = self.hosts.lock().unwrap();
+        let host = hosts.entry(key).or_insert_with(|| Host{ robots:RobotsTxt::allow_all(), next_request:Instant::now(), expires:None });
+        host.robots = robots;
+        host.expires = if failed { Some(Instant::now() + self.failure_retry) }else { None };
+        Ok(host.robots.clone())
+    }
+
+    /// Forget the cached robots.txt of every host
+    pub fn clear(&self){
+        self.hosts.lock().unwrap().clear();
+    }
+}
+
+impl<F:HttpFetcher> HttpFetcher for PoliteFetcher<F>{
+    fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+        let robots = self.robots(url)?;
+        let (origin,path) = split_url(url).ok_or_else(|| invalid_url(url))?;
+        let path = if path.is_empty() { "/" } else { path };
+        if !robots.allowed(path) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("Failed by robots.txt: {} is disallowed",url)
+            ));
+        }
+
+        if let Some(delay) = robots.crawl_delay {
+            let wait = {
+                let mut hosts = self.hosts.lock().unwrap();
+                let now = Instant::now();
+                // cleared since the rules were read, they are downloaded again next time
+                let host = hosts.entry(origin.to_ascii_lowercase())
+                    .or_insert_with(|| Host{ robots:robots.clone(), next_request:now, expires:Some(now) });
+                let start = host.next_request.max(now);
+                host.next_request = start + delay;
+                start - now
+            };
+            std::thread::sleep(wait);
+        }
+
+        let mut all = headers.to_vec();
+        if !headers.iter().any(|(name,_)| name.eq_ignore_ascii_case("user-agent")) {
+            all.push(("User-Agent",&self.user_agent));
+        }
+        self.inner.fetch(url,&all)
+    }
+}
+
+fn invalid_url(url:&str)->std::io::Error{
+    std::io::Error::new(std::io::ErrorKind::InvalidInput,format!("Failed by PoliteFetcher: {} is not an absolute url",url))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::RobotsTxt;
+
+    #[test]
+    fn future_rss_robots_parse(){
+        let text = "\
+# comment
+User-agent: Googlebot
+User-agent: future_rss
+Disallow: /tmp/
+Allow: /tmp/*.xml$
+Crawl-delay: 2.5
+
+User-agent: *
+Disallow: /
+";
+        let robots = RobotsTxt::parse(text,"future_rss/1.0");
+        assert_eq!(robots.crawl_delay,Some(Duration::from_millis(2500)));
+        assert!(robots.allowed("/rss"));
+        assert!(!robots.allowed("/tmp/a.html"));
+        assert!(robots.allowed("/tmp/a/feed.xml"));
+        assert!(!robots.allowed("/tmp/feed.xml?x=1"));
+
+        let other = RobotsTxt::parse(text,"SomeBot");
+        assert!(!other.allowed("/rss"));
+        assert_eq!(other.crawl_delay,None);
+        assert!(RobotsTxt::parse("User-agent: Googlebot\nDisallow: /\n","future_rss").allowed("/"));
+        assert!(RobotsTxt::parse("","future_rss").allowed("/"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_polite_fetcher(){
+        use std::time::Instant;
+        use crate::{HttpFetcher,PoliteFetcher};
+
+        let server = crate::mock::serve("User-agent: *\nDisallow: /private\nCrawl-delay: 0.1\n");
+        let fetcher = PoliteFetcher::new(reqwest::blocking::Client::new());
+        let err = fetcher.fetch(&server.url("/private/feed"),&[]).unwrap_err();
+        assert_eq!(err.kind(),std::io::ErrorKind::PermissionDenied);
+
+        let start = Instant::now();
+        assert_eq!(fetcher.fetch(&server.url("/feed"),&[]).unwrap().status,200);
+        assert_eq!(fetcher.fetch(&server.url("/feed?page=2"),&[]).unwrap().status,200);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let unreachable = PoliteFetcher::new(reqwest::blocking::Client::new());
+        assert!(!unreachable.robots("http://127.0.0.1:1/feed").unwrap().allowed("/feed"));
+    }
+
+    #[test]
+    fn future_rss_polite_fetcher_failures(){
+        use std::sync::atomic::{AtomicUsize,Ordering};
+        use crate::{HttpFetcher,HttpResponse,PoliteFetcher};
+
+        /// fails its first request, then serves a robots.txt
+        struct Flaky(AtomicUsize);
+        impl HttpFetcher for Flaky{
+            fn fetch(&self,_url:&str,_headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+                match self.0.fetch_add(1,Ordering::SeqCst) {
+                    0 => Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused,"down")),
+                    _ => Ok(HttpResponse{
+                        status:200,
+                        headers:Vec::new(),
+                        body:Box::new(std::io::Cursor::new(b"User-agent: *\nDisallow: /private\n".to_vec())),
+                    }),
+                }
+            }
+        }
+
+        let fetcher = PoliteFetcher::new(Flaky(AtomicUsize::new(0))).failure_retry(Duration::from_millis(20));
+        assert!(!fetcher.robots("http://examples.com/feed").unwrap().allowed("/feed"));
+        assert!(!fetcher.robots("http://examples.com/feed").unwrap().allowed("/feed"));
+        std::thread::sleep(Duration::from_millis(30));
+        let robots = fetcher.robots("http://examples.com/feed").unwrap();
+        assert!(robots.allowed("/feed") && !robots.allowed("/private"));
+        // downloaded rules stay
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(fetcher.robots("http://examples.com/feed").unwrap().allowed("/feed"));
+        assert_eq!(fetcher.inner.0.load(Ordering::SeqCst),2);
+    }
+}
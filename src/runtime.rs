@@ -0,0 +1,161 @@
+//!
+//! Async Runtime Glue
+//!
+//! Blocking work behind the async APIs goes through `spawn_blocking`: with
+//! the `rt-tokio` feature it runs on tokio's blocking pool, otherwise on a
+//! dedicated thread whose completion wakes the task, so any executor can
+//! drive the futures of this crate. [`join_limited`] runs several futures
+//! of the same task side by side.
+//!
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc,Mutex};
+use std::task::{Context,Poll,Waker};
+
+#[cfg(not(feature = "rt-tokio"))]
+pub(crate) use self::spawn_thread as spawn_blocking;
+
+///
+/// Run `work` on tokio's blocking pool and wait for its result
+///
+#[cfg(feature = "rt-tokio")]
+pub(crate) async fn spawn_blocking<T,F>(work:F)->Result<T,std::io::Error>
+    where T:Send+'static,F:FnOnce()->T+Send+'static
+{
+    tokio::task::spawn_blocking(work).await.map_err(std::io::Error::other)
+}
+
+///
+/// Run `work` on a new thread and wait for its result, executor agnostic
+///
+#[cfg_attr(feature = "rt-tokio",allow(dead_code))]
+pub(crate) async fn spawn_thread<T,F>(work:F)->Result<T,std::io::Error>
+    where T:Send+'static,F:FnOnce()->T+Send+'static
+{
+    let shared = Arc::new(Mutex::new(Slot{ value:None, waker:None }));
+    let done = shared.clone();
+    std::thread::Builder::new().spawn(move || {
+        let value = work();
+        let mut slot = done.lock().unwrap();
+        slot.value = Some(value);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    })?;
+    Ok(Blocking{ shared }.await)
+}
+
+///
+/// Drive `futures` at most `limit` at a time, outputs come back in input order
+///
+#[cfg_attr(not(all(feature = "http-async",not(target_arch = "wasm32"))),allow(dead_code))]
+pub(crate) async fn join_limited<F:Future>(futures:Vec<F>,limit:usize)->Vec<F::Output>{
+    let mut outputs = futures.iter().map(|_| None).collect::<Vec<_>>();
+    let mut pending = futures.into_iter().map(Box::pin).enumerate();
+    let mut running = Vec::new();
+    std::future::poll_fn(|cx| loop {
+        while running.len() < limit.max(1) {
+            match pending.next() {
+                Some(next) => running.push(next),
+                None => break,
+            }
+        }
+        if running.is_empty() {
+            return Poll::Ready(());
+        }
+        // a future that finished frees a place: start the next ones right away
+        let before = running.len();
+        running.retain_mut(|(index,future):&mut (usize,Pin<Box<F>>)| match future.as_mut().poll(cx) {
+            Poll::Ready(output) => {
+                outputs[*index] = Some(output);
+                false
+            }
+            Poll::Pending => true,
+        });
+        if running.len() == before {
+            return Poll::Pending;
+        }
+    }).await;
+    outputs.into_iter().map(|output| output.expect("Failed by join_limited: future left unfinished")).collect()
+}
+
+struct Slot<T>{
+    value:Option<T>,
+    waker:Option<Waker>,
+}
+
+struct Blocking<T>{
+    shared:Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> Future for Blocking<T>{
+    type Output = T;
+
+    fn poll(self:Pin<&mut Self>,cx:&mut Context<'_>) -> Poll<T> {
+        let mut slot = self.shared.lock().unwrap();
+        match slot.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context,Poll,Wake};
+    use std::thread::{self,Thread};
+    use super::{spawn_thread,join_limited};
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark{
+        fn wake(self:Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor for driving futures in tests
+    pub(crate) fn block_on<F:Future>(future:F)->F::Output{
+        let mut future = Box::pin(future);
+        let waker = Arc::new(Unpark(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn future_rss_spawn_thread(){
+        let value = block_on(spawn_thread(|| 1 + 1));
+        assert_eq!(value.unwrap(),2);
+    }
+
+    #[test]
+    fn future_rss_join_limited(){
+        let running = std::sync::atomic::AtomicUsize::new(0);
+        let peak = std::sync::atomic::AtomicUsize::new(0);
+        let work = (0..5u64).rev().map(|i| {
+            let (running,peak) = (&running,&peak);
+            async move {
+                let now = running.fetch_add(1,std::sync::atomic::Ordering::SeqCst) + 1;
+                peak.fetch_max(now,std::sync::atomic::Ordering::SeqCst);
+                spawn_thread(move || thread::sleep(std::time::Duration::from_millis(i * 5))).await.unwrap();
+                running.fetch_sub(1,std::sync::atomic::Ordering::SeqCst);
+                i
+            }
+        }).collect();
+        assert_eq!(block_on(join_limited(work,2)),[4,3,2,1,0]);
+        assert_eq!(peak.into_inner(),2);
+        assert!(block_on(join_limited(Vec::<std::future::Ready<()>>::new(),0)).is_empty());
+    }
+}
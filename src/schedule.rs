@@ -0,0 +1,165 @@
+//!
+//! Adaptive Refresh Scheduling
+//!
+//! Learns how often a feed publishes from the polls that brought new items
+//! and derives the next poll interval from it: busy feeds are polled at about
+//! twice their posting rate, feeds that stay quiet back off exponentially.
+//!
+
+use std::collections::VecDeque;
+use std::time::{Duration,Instant};
+
+/// Number of item arrivals remembered per feed
+pub static SCHEDULE_HISTORY_LEN:usize = 16;
+
+/// Shortest interval chosen by default, 5 minutes
+pub static SCHEDULE_DEFAULT_MIN:Duration = Duration::from_secs(5 * 60);
+
+/// Longest interval chosen by default, 1 day
+pub static SCHEDULE_DEFAULT_MAX:Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Interval used until a cadence is known, 1 hour
+pub static SCHEDULE_DEFAULT_INITIAL:Duration = Duration::from_secs(60 * 60);
+
+///
+/// Poll interval learned from posting history
+///
+/// ```
+/// use std::time::{Duration,Instant};
+/// use future_rss::AdaptiveSchedule;
+///
+/// fn main(){
+///     let mut schedule = AdaptiveSchedule::new();
+///     let start = Instant::now();
+///     for hour in 0..4 {
+///         schedule.record_at(start + Duration::from_secs(hour * 3600),1);
+///     }
+///     // one post per hour, polled every 30 minutes
+///     assert_eq!(schedule.interval(),Duration::from_secs(1800));
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct AdaptiveSchedule{
+    pub min_interval:Duration,
+    pub max_interval:Duration,
+    pub initial_interval:Duration,
+    arrivals:VecDeque<(Instant,usize)>,
+    idle_polls:u32,
+    last_poll:Option<Instant>,
+}
+
+impl Default for AdaptiveSchedule{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdaptiveSchedule{
+
+    pub fn new()->Self{
+        Self{
+            min_interval:SCHEDULE_DEFAULT_MIN,
+            max_interval:SCHEDULE_DEFAULT_MAX,
+            initial_interval:SCHEDULE_DEFAULT_INITIAL,
+            arrivals:VecDeque::with_capacity(SCHEDULE_HISTORY_LEN),
+            idle_polls:0,
+            last_poll:None,
+        }
+    }
+
+    /// Record a poll happening now which found `new_items` items
+    pub fn record(&mut self,new_items:usize){
+        self.record_at(Instant::now(),new_items)
+    }
+
+    /// Record a poll at `at` which found `new_items` items
+    pub fn record_at(&mut self,at:Instant,new_items:usize){
+        self.last_poll = Some(at);
+        if new_items == 0 {
+            self.idle_polls = self.idle_polls.saturating_add(1);
+            return;
+        }
+        self.idle_polls = 0;
+        if self.arrivals.len() == SCHEDULE_HISTORY_LEN {
+            self.arrivals.pop_front();
+        }
+        self.arrivals.push_back((at,new_items));
+    }
+
+    ///
+    /// Average time between two posts over the remembered history,
+    /// None until at least two arrivals were observed
+    ///
+    pub fn posting_gap(&self)->Option<Duration>{
+        let (first,_) = self.arrivals.front()?;
+        let (last,_) = self.arrivals.back()?;
+        // items of the first arrival were posted before the window started
+        let posts:usize = self.arrivals.iter().skip(1).map(|(_,n)| n).sum();
+        if posts == 0 {
+            return None;
+        }
+        Some(last.duration_since(*first) / posts as u32)
+    }
+
+    /// Interval to wait before the next poll
+    pub fn interval(&self)->Duration{
+        let base = match self.posting_gap() {
+            Some(gap) => gap / 2,
+            None => self.initial_interval,
+        };
+        // the first quiet poll is expected at twice the posting rate
+        let backoff = self.idle_polls.saturating_sub(1).min(16);
+        base.checked_mul(1 << backoff)
+            .unwrap_or(self.max_interval)
+            .max(self.min_interval)
+            .min(self.max_interval)
+    }
+
+    /// Instant the next poll is due, None if never polled
+    pub fn next_poll(&self)->Option<Instant>{
+        self.last_poll.map(|at| at + self.interval())
+    }
+
+    /// Feed should be polled at `now`
+    pub fn is_due(&self,now:Instant)->bool{
+        match self.next_poll() {
+            Some(next) => now >= next,
+            None => true,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration,Instant};
+    use crate::AdaptiveSchedule;
+
+    #[test]
+    fn future_rss_schedule_backoff(){
+        let mut schedule = AdaptiveSchedule::new();
+        let start = Instant::now();
+        assert!(schedule.is_due(start));
+        assert_eq!(schedule.interval(),Duration::from_secs(3600));
+
+        let minute = Duration::from_secs(60);
+        schedule.record_at(start,3);
+        schedule.record_at(start + minute * 20,2);
+        assert_eq!(schedule.posting_gap(),Some(minute * 10));
+        // clamped to the 5 minute floor
+        assert_eq!(schedule.interval(),minute * 5);
+
+        schedule.record_at(start + minute * 25,0);
+        assert_eq!(schedule.interval(),minute * 5);
+        schedule.record_at(start + minute * 30,0);
+        schedule.record_at(start + minute * 40,0);
+        assert_eq!(schedule.interval(),minute * 20);
+        assert!(!schedule.is_due(start + minute * 50));
+        assert!(schedule.is_due(start + minute * 60));
+
+        for _ in 0..40 {
+            schedule.record_at(start,0);
+        }
+        assert_eq!(schedule.interval(),schedule.max_interval);
+    }
+}
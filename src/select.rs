@@ -0,0 +1,165 @@
+//!
+//! Path Selectors
+//!
+//! Values nested deep in namespaced elements, e.g. the url of
+//! `<media:group><media:content url="..."/></media:group>`, are picked out
+//! of every item with a path such as `item/media:group/media:content/@url`
+//! instead of parsing code of their own.
+//!
+
+use std::collections::BTreeMap;
+use crate::RssParser;
+use crate::extension::{scan_items,Element};
+
+///
+/// Path of an element below the item, ending in `@attribute` to take an
+/// attribute instead of the text
+///
+/// Segments are element names compared without case, `*` stands for any
+/// one element; a leading segment naming the parser's node tag is skipped.
+///
+/// ```
+/// use future_rss::RssParser;
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut parser = RssParser::new();
+///     parser.select("video","item/media:group/media:content/@url")?;
+///     parser.set_xml(String::from(
+///        r#"<?xml version="1.0" encoding="UTF-8" ?>
+///         <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/"><channel>
+///             <item>
+///                 <title>Hey!</title>
+///                 <media:group><media:content url="https://examples.com/1.mp4"/></media:group>
+///             </item>
+///         </channel></rss>
+///         "#
+///     ));
+///     let extras = parser.parse_extras()?;
+///     assert_eq!(extras[0]["video"],"https://examples.com/1.mp4");
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Selector{
+    path:Vec<String>,
+    attribute:Option<String>,
+}
+
+impl Selector{
+
+    /// Compile `path`, `InvalidInput` when it selects nothing
+    pub fn parse(path:&str)->Result<Self,std::io::Error>{
+        let invalid = |reason:&str| std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Failed by Selector::parse: {} in {:?}",reason,path)
+        );
+        let mut segments = path.trim().trim_start_matches('/').split('/').collect::<Vec<_>>();
+        let attribute = match segments.last() {
+            Some(last) if last.starts_with('@') => {
+                let attribute = last[1..].trim().to_ascii_lowercase();
+                if attribute.is_empty() {
+                    return Err(invalid("empty attribute"));
+                }
+                segments.pop();
+                Some(attribute)
+            }
+            _ => None,
+        };
+        let path = segments.iter()
+            .map(|segment| match segment.trim() {
+                "" => Err(invalid("empty segment")),
+                segment if segment.contains('@') => Err(invalid("attribute before the last segment")),
+                segment => Ok(segment.to_ascii_lowercase()),
+            })
+            .collect::<Result<Vec<_>,_>>()?;
+        if path.is_empty() {
+            return Err(invalid("no element"));
+        }
+        Ok(Self{ path, attribute })
+    }
+
+    /// Value of `element` when the selector points at it
+    pub(crate) fn select<'e>(&self,node_tag:&str,element:&'e Element)->Option<&'e str>{
+        let path = match self.path.split_first() {
+            Some((first,rest)) if !rest.is_empty() && first.eq_ignore_ascii_case(node_tag) => rest,
+            _ => &self.path[..],
+        };
+        let names = element.parents.iter().chain(std::iter::once(&element.name));
+        if path.len() != element.parents.len() + 1 || !path.iter().zip(names).all(|(segment,name)| segment == "*" || segment == name) {
+            return None;
+        }
+        match &self.attribute {
+            Some(attribute) => element.attribute(attribute),
+            None => Some(element.text.trim()),
+        }
+    }
+}
+
+impl std::str::FromStr for Selector{
+    type Err = std::io::Error;
+
+    fn from_str(path:&str)->Result<Self,Self::Err>{
+        Self::parse(path)
+    }
+}
+
+///
+/// Values of the parser's selectors for every item of `xml`, by selector
+/// name; the first match wins, empty values are left out
+///
+pub(crate) fn parse_items(parser:&RssParser,xml:&str)->Result<Vec<BTreeMap<String,String>>,std::io::Error>{
+    scan_items(parser,xml,"RssParser::parse_extras",|extras:&mut BTreeMap<String,String>,element| {
+        for (name,selector) in parser.selectors.iter() {
+            match selector.select(&parser.node_tag,element) {
+                Some(value) if !value.is_empty() && !extras.contains_key(name) => {
+                    extras.insert(name.clone(),value.to_string());
+                }
+                _ => (),
+            }
+        }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,Selector,Feed};
+
+    #[test]
+    fn future_rss_selector(){
+        let mut parser = RssParser::new();
+        parser.select("thumbnail","media:group/*/media:thumbnail/@URL").unwrap();
+        parser.select("credit","/item/media:group/media:content/media:credit").unwrap();
+        parser.select("group","item/media:group/@id").unwrap();
+        parser.set_xml(String::from(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/"><channel>
+                    <item>
+                        <media:group id="g1">
+                            <media:content url="https://examples.com/1.mp4">
+                                <media:thumbnail url="https://examples.com/1.jpg"/>
+                                <media:credit> MeteorCat </media:credit>
+                            </media:content>
+                            <media:content url="https://examples.com/2.mp4">
+                                <media:thumbnail url="https://examples.com/2.jpg"/>
+                            </media:content>
+                        </media:group>
+                    </item>
+                    <item><title>plain</title></item>
+                </channel></rss>
+        "#));
+        let extras = parser.parse_extras().unwrap();
+        assert_eq!(extras.len(),2);
+        assert_eq!(extras[0]["thumbnail"],"https://examples.com/1.jpg");
+        assert_eq!(extras[0]["credit"],"MeteorCat");
+        assert_eq!(extras[0]["group"],"g1");
+        assert!(extras[1].is_empty());
+
+        let feed = Feed::parse_using(&parser,parser.get_xml()).unwrap();
+        assert_eq!(feed.entries[0].extensions["thumbnail"],"https://examples.com/1.jpg");
+
+        for broken in ["","/","item//title","item/@","@url","item/@url/title"] {
+            assert!(broken.parse::<Selector>().is_err(),"{}",broken);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//!
+//! Bounded Item Stream
+//!
+//! A worker thread parses into a fixed-size queue which an async consumer
+//! drains through [`futures_core::Stream`]; when the consumer falls behind
+//! the worker blocks instead of buffering the whole feed. The same queue
+//! carries the events of a [`FeedWatcher`](crate::FeedWatcher) polling in
+//! the background.
+//!
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc,Condvar,Mutex};
+use std::task::{Context,Poll,Waker};
+use std::thread;
+use futures_core::Stream;
+use crate::RssItem;
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+use crate::FeedEvent;
+
+struct State<T>{
+    queue:VecDeque<T>,
+    capacity:usize,
+    /// worker finished, nothing more will be queued
+    closed:bool,
+    /// stream was dropped, the worker should stop
+    dropped:bool,
+    waker:Option<Waker>,
+}
+
+struct Shared<T>{
+    state:Mutex<State<T>>,
+    space:Condvar,
+}
+
+///
+/// Producing half handed to the worker
+///
+pub(crate) struct Sender<T>{
+    shared:Arc<Shared<T>>,
+}
+
+impl<T> Sender<T>{
+
+    /// Queue one value, blocking while the queue is full.
+    /// Returns false once the stream was dropped.
+    pub(crate) fn send(&self,value:T)->bool{
+        let mut state = self.shared.state.lock().unwrap();
+        while state.queue.len() >= state.capacity && !state.dropped {
+            state = self.shared.space.wait(state).unwrap();
+        }
+        if state.dropped {
+            return false;
+        }
+        state.queue.push_back(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    /// Sleep for `timeout` or until the stream is dropped.
+    /// Returns false once the stream was dropped.
+    #[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+    pub(crate) fn wait(&self,timeout:std::time::Duration)->bool{
+        let state = self.shared.state.lock().unwrap();
+        let (state,_) = self.shared.space
+            .wait_timeout_while(state,timeout,|state| !state.dropped)
+            .unwrap();
+        !state.dropped
+    }
+}
+
+impl<T> Drop for Sender<T>{
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+///
+/// Consuming half shared by the public streams
+///
+struct Receiver<T>{
+    shared:Arc<Shared<T>>,
+}
+
+impl<V:Send+'static> Receiver<Result<V,std::io::Error>>{
+
+    /// Run `work` on a new thread feeding a queue of `capacity` values,
+    /// an error returned by `work` is queued as the last value
+    fn spawn<F>(capacity:usize,work:F)->Self
+        where F:FnOnce(&Sender<Result<V,std::io::Error>>)->Result<(),std::io::Error>+Send+'static
+    {
+        let shared = Arc::new(Shared{
+            state:Mutex::new(State{
+                queue:VecDeque::new(),
+                capacity:capacity.max(1),
+                closed:false,
+                dropped:false,
+                waker:None,
+            }),
+            space:Condvar::new(),
+        });
+        let sender = Sender{ shared:shared.clone() };
+        thread::spawn(move || {
+            if let Err(e) = work(&sender) {
+                sender.send(Err(e));
+            }
+        });
+        Self{ shared }
+    }
+}
+
+impl<T> Receiver<T>{
+
+    fn poll_next(&self,cx:&mut Context<'_>)->Poll<Option<T>>{
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(value) = state.queue.pop_front() {
+            self.shared.space.notify_one();
+            return Poll::Ready(Some(value));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T>{
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().dropped = true;
+        self.shared.space.notify_all();
+    }
+}
+
+///
+/// Async stream of parsed items with bounded buffering,
+/// see [`RssParser::item_stream`](crate::RssParser::item_stream)
+///
+pub struct ItemStream{
+    receiver:Receiver<Result<RssItem,std::io::Error>>,
+}
+
+impl ItemStream{
+
+    /// Run `work` on a new thread feeding a queue of `capacity` items,
+    /// an error returned by `work` ends the stream
+    pub(crate) fn spawn<F>(capacity:usize,work:F)->Self
+        where F:FnOnce(&Sender<Result<RssItem,std::io::Error>>)->Result<(),std::io::Error>+Send+'static
+    {
+        Self{ receiver:Receiver::spawn(capacity,work) }
+    }
+}
+
+impl Stream for ItemStream{
+    type Item = Result<RssItem,std::io::Error>;
+
+    fn poll_next(self:Pin<&mut Self>,cx:&mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next(cx)
+    }
+}
+
+///
+/// Async stream of changes seen by a watcher polling in the background,
+/// see [`FeedWatcher::into_stream`](crate::FeedWatcher::into_stream)
+///
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+pub struct EventStream{
+    receiver:Receiver<Result<FeedEvent,std::io::Error>>,
+}
+
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+impl EventStream{
+
+    /// Run the polling loop `work` on a new thread feeding a queue of `capacity` events
+    pub(crate) fn spawn<F>(capacity:usize,work:F)->Self
+        where F:FnOnce(&Sender<Result<FeedEvent,std::io::Error>>)->Result<(),std::io::Error>+Send+'static
+    {
+        Self{ receiver:Receiver::spawn(capacity,work) }
+    }
+}
+
+#[cfg(all(feature = "http",not(target_arch = "wasm32")))]
+impl Stream for EventStream{
+    type Item = Result<FeedEvent,std::io::Error>;
+
+    fn poll_next(self:Pin<&mut Self>,cx:&mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next(cx)
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::pin::Pin;
+    use futures_core::Stream;
+    use futures_util::StreamExt;
+    use crate::RssParser;
+    use crate::runtime::tests::block_on;
+    use crate::mock::FEED;
+
+    pub(crate) fn next<S:Stream+Unpin>(stream:&mut S)->Option<S::Item>{
+        block_on(std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)))
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn future_rss_item_stream(){
+        let server = crate::mock::serve(FEED);
+        let parser = RssParser::new();
+        let mut items = parser.item_stream(&server.url("/feed"),"utf8",1);
+        assert_eq!(next(&mut items).unwrap().unwrap().title,"Hey!");
+        assert!(next(&mut items).is_none());
+
+        let mut items = parser.item_stream("http://127.0.0.1:1/feed","utf8",1);
+        assert!(next(&mut items).unwrap().is_err());
+        assert!(next(&mut items).is_none());
+    }
+
+    #[test]
+    fn future_rss_reader_stream(){
+        let xml = FEED.replace("</channel>",
+            "<item><title>Bye!</title><guid>other key</guid></item></channel>");
+        let items = RssParser::new().reader_stream(std::io::Cursor::new(xml),1);
+        let mut titles = items
+            .filter_map(|item| std::future::ready(item.ok()))
+            .map(|item| item.title)
+            .take(1);
+        assert_eq!(next(&mut titles).as_deref(),Some("Hey!"));
+        assert!(next(&mut titles).is_none());
+    }
+}
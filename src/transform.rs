@@ -0,0 +1,135 @@
+//!
+//! Field Transformers
+//!
+//! A [`Transformer`] chains normalization steps such as stripping markup and
+//! shortening text; attached to a field with [`RssParser::transform`], it
+//! runs on every item as it is parsed.
+//!
+
+use std::sync::Arc;
+use crate::RssItem;
+use crate::html::{strip_tags,unescape};
+
+type Step = Arc<dyn Fn(String)->String+Send+Sync>;
+
+///
+/// Steps run in order on a field value
+///
+/// ```
+/// use future_rss::{RssParser,Transformer};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>>{
+///     let mut parser = RssParser::new();
+///     parser.transform("description",Transformer::new()
+///         .strip_html()
+///         .trim()
+///         .truncate(12)
+///         .map(|text| text.to_uppercase()))?;
+///     parser.set_xml(String::from(
+///         "<rss><item><description><![CDATA[<p>Hello,\n  <b>world</b> &amp; friends!</p>]]></description></item></rss>"
+///     ));
+///     assert_eq!(parser.parse_vec()?[0].description,"HELLO, WORL…");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone,Default)]
+pub struct Transformer{
+    steps:Vec<(&'static str,Step)>,
+}
+
+impl std::fmt::Debug for Transformer{
+    fn fmt(&self,f:&mut std::fmt::Formatter<'_>)->std::fmt::Result{
+        f.debug_list().entries(self.steps.iter().map(|(name,_)| name)).finish()
+    }
+}
+
+impl Transformer{
+
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    /// Drop tags and resolve entities, leaving the text of the markup
+    pub fn strip_html(self)->Self{
+        self.step("strip_html",|text| unescape(&strip_tags(&text)))
+    }
+
+    /// Trim and collapse runs of whitespace into a single space
+    pub fn trim(self)->Self{
+        self.step("trim",|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Keep at most `max` characters, an ellipsis included when text is cut
+    pub fn truncate(self,max:usize)->Self{
+        self.step("truncate",move |text| {
+            if text.chars().count() <= max {
+                return text;
+            }
+            let mut short = text.chars().take(max.saturating_sub(1)).collect::<String>();
+            short.truncate(short.trim_end().len());
+            if max > 0 {
+                short.push('…');
+            }
+            short
+        })
+    }
+
+    /// Any other step, such as a translation
+    pub fn map<F>(self,step:F)->Self
+        where F:Fn(String)->String+Send+Sync+'static
+    {
+        self.step("map",step)
+    }
+
+    pub fn apply(&self,text:&str)->String{
+        self.steps.iter().fold(text.to_string(),|text,(_,step)| step(text))
+    }
+
+    fn step<F>(mut self,name:&'static str,step:F)->Self
+        where F:Fn(String)->String+Send+Sync+'static
+    {
+        self.steps.push((name,Arc::new(step)));
+        self
+    }
+}
+
+///
+/// Field of `item` called `field`, one of the [`RssItem`] field names
+///
+pub(crate) fn field_mut<'i>(item:&'i mut RssItem,field:&str)->Option<&'i mut String>{
+    match field {
+        "title" => Some(&mut item.title),
+        "link" => Some(&mut item.link),
+        "author" => Some(&mut item.author),
+        "description" => Some(&mut item.description),
+        "content" => Some(&mut item.content),
+        "guid" => Some(&mut item.guid),
+        "publish" => Some(&mut item.publish),
+        "updated" => Some(&mut item.updated),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{RssParser,Transformer};
+
+    #[test]
+    fn future_rss_transformer(){
+        assert_eq!(Transformer::new().truncate(5).apply("short"),"short");
+        assert_eq!(Transformer::new().truncate(5).apply("a bit longer"),"a bi…");
+        assert_eq!(Transformer::new().truncate(3).apply("ab cd"),"ab…");
+        assert_eq!(Transformer::new().truncate(0).apply("text"),"");
+        assert_eq!(Transformer::new().strip_html().apply("<a href=\"x\">Tom &amp; Jerry</a>&#33;"),"Tom & Jerry!");
+        assert_eq!(format!("{:?}",Transformer::new().trim().map(|text| text)),"[\"trim\", \"map\"]");
+
+        let mut parser = RssParser::new();
+        parser.transform("title",Transformer::new().trim()).unwrap();
+        parser.transform("title",Transformer::new().map(|title| format!("[{}]",title))).unwrap();
+        assert!(parser.transform("body",Transformer::new()).is_err());
+        parser.set_xml(String::from("<rss><item><title>  Hey \n you </title><link>a  b</link></item></rss>"));
+        let items = parser.parse_vec().unwrap();
+        assert_eq!((items[0].title.as_str(),items[0].link.as_str()),("[Hey you]","a  b"));
+    }
+}
@@ -0,0 +1,459 @@
+//!
+//! Feed Watcher
+//!
+//! Polls a single feed with conditional GET (ETag / Last-Modified) and
+//! reports what changed since the previous poll.
+//!
+
+use reqwest::blocking::Client;
+use reqwest::header::{ETAG,LAST_MODIFIED,IF_NONE_MATCH,IF_MODIFIED_SINCE};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::{RssParser,FeedDiff,FeedEvent,AdaptiveSchedule,HttpFetcher,CacheBackend,CACHE_KEY_PREFIX};
+#[cfg(feature = "stream")]
+use crate::EventStream;
+
+///
+/// Result of a single poll
+///
+#[derive(Debug,Clone,PartialEq)]
+pub enum PollOutcome{
+    /// Server answered 304, or the body hashed the same with
+    /// [`FeedWatcher::hash_body`], nothing was parsed
+    NotModified,
+    /// Feed was downloaded and parsed, carrying the new and updated items
+    Fetched(Vec<FeedEvent>),
+}
+
+///
+/// Response of a conditional GET
+///
+#[derive(Debug)]
+pub(crate) enum Download{
+    NotModified,
+    Body{
+        body:String,
+        etag:Option<String>,
+        last_modified:Option<String>,
+    },
+}
+
+///
+/// Watch a feed for new and updated items
+///
+/// ```no_run
+/// use future_rss::{FeedWatcher,PollOutcome};
+///
+/// fn main()->Result<(),Box<dyn std::error::Error>> {
+///     let mut watcher = FeedWatcher::new("https://www.zhihu.com/rss","utf8");
+///     watcher.parser.author_tag = String::from("dc:creator");
+///     if let PollOutcome::Fetched(events) = watcher.poll()? {
+///         println!("{:?}",events);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug,Clone)]
+pub struct FeedWatcher{
+    pub url:String,
+    pub charset:String,
+    /// Tag configuration used to parse every fetched body
+    pub parser:RssParser,
+    /// Learns the posting cadence from every poll
+    pub schedule:AdaptiveSchedule,
+    /// Skip parsing a body identical to the previous one,
+    /// for servers ignoring conditional requests; the body hash is stable
+    /// across builds, so watchers sharing a cache agree on it
+    pub hash_body:bool,
+    etag:Option<String>,
+    last_modified:Option<String>,
+    body_hash:Option<u64>,
+    diff:FeedDiff,
+    cache:Option<Arc<dyn CacheBackend>>,
+}
+
+impl FeedWatcher{
+
+    pub fn new(url:&str,charset:&str)->Self{
+        Self{
+            url:String::from(url),
+            charset:String::from(charset),
+            parser:RssParser::new(),
+            schedule:AdaptiveSchedule::new(),
+            hash_body:false,
+            etag:None,
+            last_modified:None,
+            body_hash:None,
+            diff:FeedDiff::new(),
+            cache:None,
+        }
+    }
+
+    ///
+    /// Keep the validators and the seen items in `cache` instead of the
+    /// watcher, under keys starting with [`CACHE_KEY_PREFIX`] and the url,
+    /// so watchers of the same feed in other processes share them
+    ///
+    pub fn with_cache(mut self,cache:Arc<dyn CacheBackend>)->Self{
+        self.diff = FeedDiff::with_backend(cache.clone(),&self.cache_key("seen:"));
+        self.cache = Some(cache);
+        self
+    }
+
+    ///
+    /// Poll with a one-off client
+    ///
+    pub fn poll(&mut self)->Result<PollOutcome,std::io::Error>{
+        self.poll_with(&crate::network::default_client())
+    }
+
+    ///
+    /// Poll reusing an existing client,
+    /// validators from the previous response are sent along
+    ///
+    pub fn poll_with(&mut self,client:&Client)->Result<PollOutcome,std::io::Error>{
+        self.poll_using(client)
+    }
+
+    ///
+    /// Poll through another HTTP backend
+    ///
+    pub fn poll_using(&mut self,fetcher:&dyn HttpFetcher)->Result<PollOutcome,std::io::Error>{
+        trace_span!("poll",url=self.url.as_str());
+        let download = self.download(fetcher);
+        self.complete(download)
+    }
+
+    /// Next poll is due according to [`FeedWatcher::schedule`]
+    pub fn is_due(&self)->bool{
+        self.schedule.is_due(Instant::now())
+    }
+
+    ///
+    /// Network half of a poll, nothing on the watcher changes
+    ///
+    pub(crate) fn download(&self,fetcher:&dyn HttpFetcher)->Result<Download,std::io::Error>{
+        let (etag,last_modified) = match &self.cache {
+            Some(cache) => (cache.get(&self.cache_key("etag"))?,cache.get(&self.cache_key("last_modified"))?),
+            None => (self.etag.clone(),self.last_modified.clone()),
+        };
+        // the validators go first, then the headers of `parser.request`
+        let mut headers = Vec::new();
+        if let Some(etag) = &etag {
+            headers.push((IF_NONE_MATCH.as_str(),etag.as_str()));
+        }
+        if let Some(modified) = &last_modified {
+            headers.push((IF_MODIFIED_SINCE.as_str(),modified.as_str()));
+        }
+        headers.extend(self.parser.request.header_pairs());
+
+        trace_event!("fetch started");
+        let retry = self.parser.request.retry.as_ref();
+        let response = crate::retry::with_retry(retry,|| fetcher.fetch(self.url.as_str(),&headers),|response| response.status)?;
+        trace_event!(status=response.status,"fetch finished");
+        if response.status == StatusCode::NOT_MODIFIED.as_u16() {
+            return Ok(Download::NotModified);
+        }
+        let response = response.error_for_status()?;
+
+        let etag = response.header(ETAG.as_str()).map(String::from);
+        let last_modified = response.header(LAST_MODIFIED.as_str()).map(String::from);
+        let body = response.text(self.charset.as_str())?;
+        Ok(Download::Body{ body, etag, last_modified })
+    }
+
+    ///
+    /// Parsing half of a poll: diff the downloaded items, keep the validators
+    /// and feed the outcome to the schedule
+    ///
+    pub(crate) fn complete(&mut self,download:Result<Download,std::io::Error>)->Result<PollOutcome,std::io::Error>{
+        let outcome = download.and_then(|download| self.apply(download));
+        let added = match &outcome {
+            Ok(PollOutcome::Fetched(events)) => events.iter()
+                .filter(|e| matches!(e,FeedEvent::New(_)))
+                .count(),
+            _ => 0,
+        };
+        self.schedule.record(added);
+        #[cfg(feature="metrics")]
+        match &outcome {
+            Ok(PollOutcome::NotModified) => crate::metrics().record_not_modified(),
+            Ok(PollOutcome::Fetched(events)) => crate::metrics().record_fetched(events.len()),
+            Err(_) => crate::metrics().record_error(),
+        }
+        #[cfg(feature="tracing")]
+        match &outcome {
+            Ok(PollOutcome::NotModified) => tracing::debug!("not modified"),
+            Ok(PollOutcome::Fetched(events)) => tracing::debug!(events=events.len(),new=added,"items emitted"),
+            Err(e) => tracing::debug!(error=%e,"poll failed"),
+        }
+        outcome
+    }
+
+    fn apply(&mut self,download:Download)->Result<PollOutcome,std::io::Error>{
+        let (body,etag,last_modified) = match download {
+            Download::NotModified => return Ok(PollOutcome::NotModified),
+            Download::Body{ body, etag, last_modified } => (body,etag,last_modified),
+        };
+        // kept in the shared cache, so the algorithm must not vary between builds
+        let hash = self.hash_body.then(|| {
+            let mut hasher = crate::diff::StableHasher::new();
+            hasher.write(body.as_bytes());
+            hasher.finish()
+        });
+        let previous = match &self.cache {
+            Some(cache) => cache.get(&self.cache_key("body_hash"))?.and_then(|hash| hash.parse().ok()),
+            None => self.body_hash,
+        };
+        if hash.is_some() && hash == previous {
+            self.store(etag,last_modified,hash)?;
+            return Ok(PollOutcome::NotModified);
+        }
+        self.parser.set_xml(body);
+        self.parser.unwrap_html();
+        // tags configured on the parser win over the detected dialect
+        if self.parser.has_default_tags() {
+            self.parser.detect_format();
+        }
+        if !self.parser.check_xml() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Failed by RssParser::check_xml"
+            ));
+        }
+        let items = self.parser.parse_vec()?;
+        self.parser.set_xml(String::new());
+
+        let events = self.diff.try_diff(items)?;
+        self.store(etag,last_modified,hash)?;
+        Ok(PollOutcome::Fetched(events))
+    }
+
+    /// Remember the validators of the last response
+    fn store(&mut self,etag:Option<String>,last_modified:Option<String>,body_hash:Option<u64>)->Result<(),std::io::Error>{
+        if let Some(cache) = &self.cache {
+            let values = [("etag",etag.clone()),("last_modified",last_modified.clone()),("body_hash",body_hash.map(|hash| hash.to_string()))];
+            for (name,value) in values.iter() {
+                match value {
+                    Some(value) => cache.set(&self.cache_key(name),value)?,
+                    None => cache.remove(&self.cache_key(name))?,
+                }
+            }
+        }
+        self.etag = etag;
+        self.last_modified = last_modified;
+        self.body_hash = body_hash;
+        Ok(())
+    }
+
+    fn cache_key(&self,name:&str)->String{
+        format!("{}{}:{}",CACHE_KEY_PREFIX,self.url,name)
+    }
+
+    ///
+    /// Keep polling on a background thread whenever [`FeedWatcher::schedule`]
+    /// says so, yielding every change as it is seen
+    ///
+    /// A failed poll yields an `Err` and the watcher tries again at the next
+    /// scheduled poll; dropping the stream stops the thread.
+    ///
+    /// ```no_run
+    /// use future_rss::FeedWatcher;
+    ///
+    /// async fn follow(){
+    ///     let mut events = FeedWatcher::new("https://www.zhihu.com/rss","utf8").into_stream(16);
+    ///     // with futures::StreamExt
+    ///     // while let Some(event) = events.next().await { println!("{:?}",event); }
+    /// }
+    /// ```
+    #[cfg(feature = "stream")]
+    pub fn into_stream(mut self,capacity:usize)->EventStream{
+        EventStream::spawn(capacity,move |sender| {
+            let client = crate::network::default_client();
+            loop {
+                let wait = self.schedule.next_poll()
+                    .map(|at| at.saturating_duration_since(Instant::now()))
+                    .unwrap_or_default();
+                if !sender.wait(wait) {
+                    return Ok(());
+                }
+                match self.poll_with(&client) {
+                    Ok(PollOutcome::NotModified) => {}
+                    Ok(PollOutcome::Fetched(events)) => {
+                        for event in events {
+                            if !sender.send(Ok(event)) {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        if !sender.send(Err(e)) {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Host part of the feed url, used for politeness limits
+    pub fn host(&self)->Option<String>{
+        reqwest::Url::parse(self.url.as_str())
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{FeedWatcher,PollOutcome,HttpFetcher,HttpResponse};
+    use crate::mock::{serve,FEED};
+
+    #[test]
+    fn future_rss_watcher_not_modified()->Result<(),Box<dyn std::error::Error>> {
+        let server = serve(FEED);
+        let mut watcher = FeedWatcher::new(&server.url("/feed"),"utf8");
+        match watcher.poll()? {
+            PollOutcome::Fetched(events) => assert_eq!(events.len(),1),
+            PollOutcome::NotModified => panic!("first poll must fetch"),
+        }
+        assert_eq!(watcher.poll()?,PollOutcome::NotModified);
+        Ok(())
+    }
+
+    #[test]
+    fn future_rss_watcher_atom()->Result<(),Box<dyn std::error::Error>> {
+        use crate::mock::{Fixture,fixture_items};
+
+        let server = serve(FEED);
+        server.mount("/atom",Fixture::atom(&fixture_items(2)));
+        let mut watcher = FeedWatcher::new(&server.url("/atom"),"utf8");
+        match watcher.poll()? {
+            PollOutcome::Fetched(events) => assert_eq!(events.len(),2),
+            PollOutcome::NotModified => panic!("first poll must fetch"),
+        }
+        assert_eq!(watcher.parser.node_tag,"entry");
+
+        // an RSS parser set up by hand is left alone
+        let mut custom = FeedWatcher::new(&server.url("/feed"),"utf8");
+        custom.parser.author_tag = String::from("dc:creator");
+        custom.poll()?;
+        assert_eq!(custom.parser.author_tag,"dc:creator");
+        Ok(())
+    }
+
+    #[test]
+    fn future_rss_watcher_request_options()->Result<(),Box<dyn std::error::Error>> {
+        let server = serve(FEED);
+        let mut watcher = FeedWatcher::new(&server.url("/feed"),"utf8");
+        watcher.parser.request = crate::RequestOptions::new()
+            .user_agent("reader/1.0")
+            .accept_language("zh-CN")
+            .header("X-Token","1");
+        watcher.poll()?;
+        watcher.poll()?;
+        for head in server.received().iter().map(|head| head.to_lowercase()) {
+            assert!(head.contains("\r\nuser-agent: reader/1.0\r\n"),"{}",head);
+            assert!(head.contains("\r\naccept-language: zh-cn\r\n"));
+            assert!(head.contains("\r\nx-token: 1\r\n"));
+        }
+        assert!(server.received()[1].to_lowercase().contains("\r\nif-none-match: \"v1\"\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn future_rss_watcher_shared_cache()->Result<(),Box<dyn std::error::Error>> {
+        use std::sync::Arc;
+        use crate::{CacheBackend,MemoryCache};
+
+        let server = serve(FEED);
+        let cache = Arc::new(MemoryCache::new());
+        let mut first = FeedWatcher::new(&server.url("/feed"),"utf8").with_cache(cache.clone());
+        assert!(matches!(first.poll()?,PollOutcome::Fetched(events) if events.len() == 1));
+        assert!(cache.get(&format!("future_rss:{}:etag",server.url("/feed")))?.is_some());
+        // validators come from the cache, the item is known to it too
+        let mut second = FeedWatcher::new(&server.url("/feed"),"utf8").with_cache(cache.clone());
+        assert_eq!(second.poll()?,PollOutcome::NotModified);
+        cache.remove(&format!("future_rss:{}:etag",server.url("/feed")))?;
+        cache.remove(&format!("future_rss:{}:last_modified",server.url("/feed")))?;
+        assert_eq!(second.poll()?,PollOutcome::Fetched(Vec::new()));
+        Ok(())
+    }
+
+    /// Host ignoring conditional requests
+    struct NoValidators(reqwest::blocking::Client);
+
+    impl HttpFetcher for NoValidators{
+        fn fetch(&self,url:&str,_:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+            self.0.fetch(url,&[])
+        }
+    }
+
+    #[test]
+    fn future_rss_watcher_hash_body(){
+        let server = serve(FEED);
+        let fetcher = NoValidators(reqwest::blocking::Client::new());
+        let mut watcher = FeedWatcher::new(&server.url("/feed"),"utf8");
+        watcher.poll_using(&fetcher).unwrap();
+        assert_eq!(watcher.poll_using(&fetcher).unwrap(),PollOutcome::Fetched(Vec::new()));
+
+        watcher.hash_body = true;
+        assert_eq!(watcher.poll_using(&fetcher).unwrap(),PollOutcome::Fetched(Vec::new()));
+        assert_eq!(watcher.poll_using(&fetcher).unwrap(),PollOutcome::NotModified);
+    }
+
+    /// Host failing its first `failures` requests
+    struct Flaky{
+        client:reqwest::blocking::Client,
+        failures:std::sync::atomic::AtomicUsize,
+    }
+
+    impl HttpFetcher for Flaky{
+        fn fetch(&self,url:&str,headers:&[(&str,&str)])->Result<HttpResponse,std::io::Error>{
+            use std::sync::atomic::Ordering;
+            match self.failures.fetch_update(Ordering::SeqCst,Ordering::SeqCst,|left| left.checked_sub(1)) {
+                Ok(_) => Err(std::io::Error::new(std::io::ErrorKind::ConnectionReset,"Failed by Flaky")),
+                Err(_) => self.client.fetch(url,headers),
+            }
+        }
+    }
+
+    #[test]
+    fn future_rss_watcher_retry(){
+        use std::time::Duration;
+        use crate::RetryPolicy;
+
+        let server = serve(FEED);
+        let fetcher = Flaky{ client:reqwest::blocking::Client::new(), failures:std::sync::atomic::AtomicUsize::new(2) };
+        let mut watcher = FeedWatcher::new(&server.url("/feed"),"utf8");
+        assert!(watcher.poll_using(&fetcher).is_err());
+
+        watcher.parser.request = crate::RequestOptions::new()
+            .retry(RetryPolicy::new().backoff(Duration::from_millis(1),Duration::from_millis(1)));
+        fetcher.failures.store(2,std::sync::atomic::Ordering::SeqCst);
+        assert!(matches!(watcher.poll_using(&fetcher).unwrap(),PollOutcome::Fetched(events) if events.len() == 1));
+    }
+
+    #[cfg(feature = "stream")]
+    #[test]
+    fn future_rss_watcher_stream(){
+        use std::time::Duration;
+        use crate::FeedEvent;
+        use crate::stream::tests::next;
+
+        let server = serve(FEED);
+        let mut watcher = FeedWatcher::new(&server.url("/feed"),"utf8");
+        watcher.schedule.min_interval = Duration::from_millis(10);
+        watcher.schedule.max_interval = Duration::from_millis(10);
+        let mut events = watcher.into_stream(1);
+        match next(&mut events).unwrap().unwrap() {
+            FeedEvent::New(item) => assert_eq!(item.title,"Hey!"),
+            event => panic!("unexpected {:?}",event),
+        }
+
+        let mut events = FeedWatcher::new("http://127.0.0.1:1/feed","utf8").into_stream(1);
+        assert!(next(&mut events).unwrap().is_err());
+    }
+}